@@ -0,0 +1,381 @@
+//! Generates the static pokemon table from `data/names.csv` and `data/types.csv`,
+//! so the list doesn't have to be parsed every time pokeget starts.
+//! The pokedex entries, base stats and terminal presets get tables of their own.
+//!
+//! Also sorts the embedded sprites into sets which can be left out with features:
+//! the shiny sprites, the female sprites in `female/` and the sprites of forms,
+//! whose filenames aren't in the list. Each set is packed into an [`Archive`] of deflate frames,
+//! which compresses the sprites together far better than their PNG files do on their own.
+//!
+//! [`Archive`]: ../src/archive.rs
+
+use std::{
+    collections::HashSet,
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const NAMES: &str = "data/names.csv";
+const TYPES: &str = "data/types.csv";
+const DEX: &str = "data/dex.csv";
+const STATS: &str = "data/stats.csv";
+const TERMINALS: &str = "data/terminals.csv";
+const SPRITES: &str = "data/pokesprite/pokemon-gen8";
+const ITEMS: &str = "data/pokesprite/items";
+
+/// Uncompressed size a frame of an archive is filled to before it's compressed.
+/// Reading a sprite decompresses its whole frame, so they're kept small.
+const FRAME_SIZE: usize = 64 * 1024;
+
+/// Splits a line of CSV into its fields, which may be quoted to hold commas
+fn fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            // Two quotes within a quoted field stand for one
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields.into_iter().map(|x| x.trim().to_string()).collect()
+}
+
+/// Splits the lines of a CSV file into fields, skipping blank lines and `#` comments
+fn records(path: &str) -> Vec<Vec<String>> {
+    let contents = fs::read_to_string(path).unwrap_or_else(|err| panic!("reading {path}: {err}"));
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(fields)
+        .collect()
+}
+
+/// Reads a CSV file whose lines have `len` fields, sorted by the first one
+fn table(path: &str, len: usize) -> Vec<Vec<String>> {
+    let mut records = records(path);
+
+    for (i, record) in records.iter().enumerate() {
+        assert_eq!(
+            record.len(),
+            len,
+            "line {} of {path} has {} fields instead of {len}",
+            i + 1,
+            record.len()
+        );
+    }
+
+    records.sort_by(|a, b| a[0].cmp(&b[0]));
+    records
+}
+
+/// Parses a field of `path`, naming it if it isn't valid
+fn number<T: std::str::FromStr>(path: &str, field: &str) -> T {
+    field
+        .parse()
+        .unwrap_or_else(|_| panic!("{field:?} in {path} is not a valid number"))
+}
+
+/// Writes the pokedex entries, sorted by filename
+fn dex(out: &mut String) {
+    let entries = table(DEX, 5);
+
+    writeln!(out, "/// Filenames and pokedex entries, sorted by filename").unwrap();
+    writeln!(
+        out,
+        "static ENTRIES: [(&str, DexEntry); {}] = [",
+        entries.len()
+    )
+    .unwrap();
+    for entry in entries {
+        let height: f32 = number(DEX, &entry[2]);
+        let weight: f32 = number(DEX, &entry[3]);
+
+        writeln!(
+            out,
+            "    ({:?}, DexEntry {{ category: {:?}, height: {height:?}, weight: {weight:?}, flavor_text: {:?} }}),",
+            entry[0], entry[1], entry[4]
+        )
+        .unwrap();
+    }
+    writeln!(out, "];").unwrap();
+}
+
+/// Writes the base stats, sorted by filename
+fn stats(out: &mut String) {
+    let entries = table(STATS, 7);
+
+    writeln!(out, "/// Filenames and base stats, sorted by filename").unwrap();
+    writeln!(
+        out,
+        "static ENTRIES: [(&str, BaseStats); {}] = [",
+        entries.len()
+    )
+    .unwrap();
+    for entry in entries {
+        let stats: Vec<u8> = entry[1..].iter().map(|x| number(STATS, x)).collect();
+
+        writeln!(
+            out,
+            "    ({:?}, BaseStats {{ hp: {}, attack: {}, defense: {}, special_attack: {}, special_defense: {}, speed: {} }}),",
+            entry[0], stats[0], stats[1], stats[2], stats[3], stats[4], stats[5]
+        )
+        .unwrap();
+    }
+    writeln!(out, "];").unwrap();
+}
+
+/// Writes the built in terminal presets, in the order they're checked
+fn presets(out: &mut String) {
+    let entries = records(TERMINALS);
+
+    writeln!(
+        out,
+        "/// Variables, values, renderers and color depths of the built in presets"
+    )
+    .unwrap();
+    writeln!(out, "static BUILTIN: [[&str; 4]; {}] = [", entries.len()).unwrap();
+    for entry in entries {
+        assert_eq!(entry.len(), 4, "{entry:?} in {TERMINALS} needs 4 fields");
+        writeln!(out, "    {entry:?},").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+}
+
+/// Turns a type like "grass" into its variant, `Type::Grass`
+fn variant(name: &str) -> String {
+    let mut chars = name.chars();
+    let first = chars.next().map(|x| x.to_ascii_uppercase());
+
+    format!("Type::{}{}", first.unwrap_or_default(), chars.as_str())
+}
+
+/// Paths of the PNG files under `dir`, relative to `root`
+fn pngs(root: &Path, dir: &Path, out: &mut Vec<PathBuf>) {
+    let entries = fs::read_dir(dir).unwrap_or_else(|err| panic!("reading {dir:?}: {err}"));
+
+    for entry in entries {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            pngs(root, &path, out);
+        } else if path.extension().is_some_and(|x| x == "png") {
+            out.push(path.strip_prefix(root).unwrap().to_path_buf());
+        }
+    }
+}
+
+/// Packs the sprites of both colorings into archives, each set gated by its features,
+/// and writes `sprite_sets` returning the ones in the build
+fn sprite_sets(out: &mut String, species: &HashSet<&str>) {
+    let root = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join(SPRITES);
+
+    // Features and files of each set
+    let mut sets: Vec<(Vec<&str>, Vec<PathBuf>)> = Vec::new();
+    for coloring in ["regular", "shiny"] {
+        let mut paths = Vec::new();
+        pngs(&root, &root.join(coloring), &mut paths);
+        paths.sort();
+
+        let (female, rest): (Vec<_>, Vec<_>) = paths
+            .into_iter()
+            .partition(|x| x.components().any(|x| x.as_os_str() == "female"));
+        let (base, forms): (Vec<_>, Vec<_>) = rest.into_iter().partition(|x| {
+            let stem = x.file_stem().unwrap().to_str().unwrap();
+            species.contains(stem)
+        });
+
+        // Shiny sets also need the shiny feature
+        let features = |feature: Option<&'static str>| {
+            let shiny = (coloring == "shiny").then_some("shiny");
+            shiny.into_iter().chain(feature).collect::<Vec<_>>()
+        };
+        sets.push((features(None), base));
+        sets.push((features(Some("female")), female));
+        sets.push((features(Some("forms")), forms));
+    }
+
+    let cfg = |features: &[&str], indent: &str| match features {
+        [] => String::new(),
+        features => {
+            let features: Vec<String> = features
+                .iter()
+                .map(|x| format!("feature = {x:?}"))
+                .collect();
+            format!("{indent}#[cfg(all({}))]\n", features.join(", "))
+        }
+    };
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    for (i, (features, paths)) in sets.iter().enumerate() {
+        let mut blob = Vec::new();
+        let mut frames = Vec::new();
+        let mut files = Vec::new();
+        let mut frame = Vec::new();
+
+        for (j, path) in paths.iter().enumerate() {
+            let file = root.join(path);
+            let key = path.to_str().unwrap().replace('\\', "/");
+            files.push((
+                key,
+                frames.len(),
+                frame.len(),
+                fs::metadata(&file).unwrap().len(),
+            ));
+            frame.extend(fs::read(&file).unwrap());
+
+            if frame.len() >= FRAME_SIZE || j == paths.len() - 1 {
+                let start = blob.len();
+                blob.extend(miniz_oxide::deflate::compress_to_vec(&frame, 10));
+                frames.push((start, blob.len()));
+                frame.clear();
+            }
+        }
+
+        let name = format!("sprites_{i}.bin");
+        fs::write(out_dir.join(&name), blob).unwrap();
+
+        write!(out, "{}", cfg(features, "")).unwrap();
+        writeln!(
+            out,
+            "static SPRITES_{i}: archive::Archive = archive::Archive {{"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    blob: include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{name}\")),"
+        )
+        .unwrap();
+        writeln!(out, "    frames: &{frames:?},").unwrap();
+        writeln!(out, "    files: &[").unwrap();
+        for (key, frame, offset, len) in files {
+            writeln!(out, "        ({key:?}, {frame}, {offset}, {len}),").unwrap();
+        }
+        writeln!(out, "    ],").unwrap();
+        writeln!(out, "}};").unwrap();
+    }
+
+    let set = "&'static archive::Archive";
+    writeln!(out, "/// The archives of embedded sprites in this build").unwrap();
+    writeln!(out, "#[allow(clippy::vec_init_then_push)]").unwrap();
+    writeln!(out, "fn sprite_sets() -> Vec<{set}> {{").unwrap();
+    writeln!(out, "    let mut sets: Vec<{set}> = Vec::new();").unwrap();
+    for (i, (features, _)) in sets.iter().enumerate() {
+        write!(out, "{}", cfg(features, "    ")).unwrap();
+        writeln!(out, "    sets.push(&SPRITES_{i});").unwrap();
+    }
+    writeln!(out, "    sets").unwrap();
+    writeln!(out, "}}").unwrap();
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={NAMES}");
+    println!("cargo:rerun-if-changed={TYPES}");
+    println!("cargo:rerun-if-changed={DEX}");
+    println!("cargo:rerun-if-changed={STATS}");
+    println!("cargo:rerun-if-changed={TERMINALS}");
+    println!("cargo:rerun-if-changed={SPRITES}");
+    println!("cargo:rerun-if-changed={ITEMS}");
+
+    // rust-embed would otherwise fail with errors about missing methods on `ItemData`
+    if env::var_os("CARGO_FEATURE_ITEMS").is_some() && !Path::new(ITEMS).is_dir() {
+        panic!(
+            "the `items` feature embeds {ITEMS}, which is missing. \
+             Copy the `items` directory of pokesprite there, or build without the feature \
+             and read items from $POKEGET_SPRITE_DIR instead"
+        );
+    }
+
+    let names = records(NAMES);
+    let types = records(TYPES);
+    assert_eq!(
+        names.len(),
+        types.len(),
+        "{NAMES} and {TYPES} differ in length"
+    );
+
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "/// Display names, filenames and types in order of Pokedex ID"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "static POKEMON: [(&str, &str, &[Type]); {}] = [",
+        names.len()
+    )
+    .unwrap();
+    for (name, types) in names.iter().zip(&types) {
+        let variants: Vec<String> = types[1..]
+            .iter()
+            .filter(|x| !x.is_empty())
+            .map(|x| variant(x))
+            .collect();
+
+        writeln!(
+            out,
+            "    ({:?}, {:?}, &[{}]),",
+            name[0],
+            name[1],
+            variants.join(", ")
+        )
+        .unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    let mut by_filename: Vec<(&str, usize)> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name[1].as_str(), i + 1))
+        .collect();
+    by_filename.sort_unstable();
+
+    writeln!(out, "/// Filenames and Pokedex IDs, sorted by filename").unwrap();
+    writeln!(
+        out,
+        "static BY_FILENAME: [(&str, usize); {}] = [",
+        by_filename.len()
+    )
+    .unwrap();
+    for (filename, id) in by_filename {
+        writeln!(out, "    ({filename:?}, {id}),").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("list.rs");
+    fs::write(dest, out).unwrap();
+
+    let species: HashSet<&str> = names.iter().map(|x| x[1].as_str()).collect();
+    let mut out = String::new();
+    sprite_sets(&mut out, &species);
+
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("sprites.rs");
+    fs::write(dest, out).unwrap();
+
+    for (name, write) in [
+        ("dex.rs", dex as fn(&mut String)),
+        ("stats.rs", stats),
+        ("presets.rs", presets),
+    ] {
+        let mut out = String::new();
+        write(&mut out);
+
+        let dest = Path::new(&env::var("OUT_DIR").unwrap()).join(name);
+        fs::write(dest, out).unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+import csv
+import json
+import urllib.request
+
+# Generates data/dex.csv from PokeAPI, using the first english flavor text of each species.
+
+API = 'https://pokeapi.co/api/v2'
+
+def get(url):
+    with urllib.request.urlopen(url) as response:
+        return json.load(response)
+
+with open('data/names.csv', 'r') as file:
+    filenames = [row[1] for row in csv.reader(file)]
+
+with open('data/dex.csv', 'w', newline='') as file:
+    writer = csv.writer(file)
+
+    for filename in filenames:
+        species = get(f'{API}/pokemon-species/{filename}')
+        default = next(x for x in species['varieties'] if x['is_default'])
+        pokemon = get(default['pokemon']['url'])
+
+        category = next(x['genus'] for x in species['genera'] if x['language']['name'] == 'en')
+        category = category.removesuffix(' Pokémon')
+
+        flavor = next(x['flavor_text'] for x in species['flavor_text_entries'] if x['language']['name'] == 'en')
+        flavor = ' '.join(flavor.split()).replace('POKéMON', 'Pokémon')
+
+        writer.writerow([filename, category, pokemon['height'] / 10, pokemon['weight'] / 10, flavor])
@@ -0,0 +1,25 @@
+import csv
+import json
+import urllib.request
+
+# Generates data/stats.csv from PokeAPI.
+
+API = 'https://pokeapi.co/api/v2'
+STATS = ['hp', 'attack', 'defense', 'special-attack', 'special-defense', 'speed']
+
+def get(url):
+    with urllib.request.urlopen(url) as response:
+        return json.load(response)
+
+with open('data/names.csv', 'r') as file:
+    filenames = [row[1] for row in csv.reader(file)]
+
+with open('data/stats.csv', 'w', newline='') as file:
+    writer = csv.writer(file)
+
+    for filename in filenames:
+        species = get(f'{API}/pokemon-species/{filename}')
+        default = next(x for x in species['varieties'] if x['is_default'])
+        pokemon = get(default['pokemon']['url'])
+        base = {x['stat']['name']: x['base_stat'] for x in pokemon['stats']}
+        writer.writerow([filename] + [base[stat] for stat in STATS])
@@ -0,0 +1,103 @@
+//! Playful achievements unlocked by long-term use.
+//!
+//! Progress comes from the usage statistics, so nothing is earned
+//! unless they're enabled with `pokeget stats enable`.
+
+use crate::{list::List, pokemon::Region, state::State};
+
+/// An achievement and the condition for unlocking it
+pub struct Achievement {
+    /// Stable identifier stored in the state file
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    unlocked: fn(&State, &List) -> bool,
+}
+
+impl Achievement {
+    /// Whether the state meets the condition of the achievement
+    pub fn is_unlocked(&self, state: &State, list: &List) -> bool {
+        (self.unlocked)(state, list)
+    }
+}
+
+/// Whether every pokemon of a region is registered in the living dex
+fn region_complete(state: &State, list: &List, region: Region) -> bool {
+    region.range().all(|id| {
+        list.get_by_id(id)
+            .is_ok_and(|name| state.living_dex.contains(name))
+    })
+}
+
+/// Every achievement, in the order they're listed
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first-display",
+        name: "Hello there!",
+        description: "Display your first pokemon",
+        unlocked: |state, _| state.usage.total() >= 1,
+    },
+    Achievement {
+        id: "randoms-100",
+        name: "Surprise me",
+        description: "Display 100 random pokemon",
+        unlocked: |state, _| state.usage.randoms >= 100,
+    },
+    Achievement {
+        id: "first-shiny",
+        name: "Something sparkly",
+        description: "Display your first shiny pokemon",
+        unlocked: |state, _| state.usage.shinies >= 1,
+    },
+    Achievement {
+        id: "shinies-10",
+        name: "Shiny charm",
+        description: "Display 10 shiny pokemon",
+        unlocked: |state, _| state.usage.shinies >= 10,
+    },
+    Achievement {
+        id: "species-151",
+        name: "Gotta see 'em all",
+        description: "Display 151 different pokemon",
+        unlocked: |state, _| state.usage.displays.len() >= 151,
+    },
+    Achievement {
+        id: "every-weekday",
+        name: "Daily routine",
+        description: "Display pokemon on every day of the week",
+        unlocked: |state, _| state.usage.weekdays.iter().all(|x| *x > 0),
+    },
+    Achievement {
+        id: "dream-team",
+        name: "Dream team",
+        description: "Have 6 favorite pokemon",
+        unlocked: |state, _| state.favorites.len() >= 6,
+    },
+    Achievement {
+        id: "dedicated-hunter",
+        name: "Dedicated hunter",
+        description: "Reach 1000 encounters on a shiny hunt",
+        unlocked: |state, _| state.counters.values().any(|x| *x >= 1000),
+    },
+    Achievement {
+        id: "kanto-living-dex",
+        name: "Kanto complete",
+        description: "Register every Kanto pokemon in the living dex",
+        unlocked: |state, list| region_complete(state, list, Region::Kanto),
+    },
+];
+
+/// Unlocks every achievement whose condition is now met,
+/// returning the ones which weren't unlocked before
+pub fn unlock_new(state: &mut State, list: &List) -> Vec<&'static Achievement> {
+    let new: Vec<&Achievement> = ACHIEVEMENTS
+        .iter()
+        .filter(|x| !state.achievements.contains(x.id) && x.is_unlocked(state, list))
+        .collect();
+
+    for achievement in &new {
+        state.achievements.insert(achievement.id.to_string());
+    }
+
+    new
+}
@@ -0,0 +1,70 @@
+//! Compressed archives of the embedded sprites.
+//!
+//! PNG files only compress each sprite on its own, and the sprites have a lot in common,
+//! so build.rs packs every set of sprites into one blob of deflate frames with an index.
+//! A frame holds several sprites and is decompressed the first time one of them is read,
+//! with the last few frames kept around since sprites are often read in order.
+
+use std::sync::{Arc, Mutex};
+
+/// Decompressed frames kept in memory
+const CACHED_FRAMES: usize = 8;
+
+/// A decompressed frame, keyed by the address of its archive and its index
+type CachedFrame = ((usize, usize), Arc<[u8]>);
+
+/// Decompressed frames, the most recently used last
+static CACHE: Mutex<Vec<CachedFrame>> = Mutex::new(Vec::new());
+
+/// A blob of compressed frames and the files in them
+pub struct Archive {
+    /// The compressed frames, one after another
+    pub blob: &'static [u8],
+    /// Start and end of each frame in the blob
+    pub frames: &'static [(usize, usize)],
+    /// Path, frame, offset and length in the decompressed frame of every file, sorted by path
+    pub files: &'static [(&'static str, usize, usize, usize)],
+}
+
+impl Archive {
+    /// Paths of the files in the archive
+    pub fn paths(&'static self) -> impl Iterator<Item = &'static str> {
+        self.files.iter().map(|(path, ..)| *path)
+    }
+
+    /// Reads a file by its path, decompressing its frame unless it's cached
+    pub fn get(&'static self, path: &str) -> Option<Vec<u8>> {
+        let i = self.files.binary_search_by_key(&path, |x| x.0).ok()?;
+        let (_, frame, offset, len) = self.files[i];
+
+        let frame = self.frame(frame)?;
+        frame.get(offset..offset + len).map(<[u8]>::to_vec)
+    }
+
+    /// A decompressed frame, moving it to the back of the cache
+    fn frame(&'static self, index: usize) -> Option<Arc<[u8]>> {
+        let key = (self as *const Self as usize, index);
+        // A panic while holding the lock can't leave the cache inconsistent
+        let mut cache = CACHE.lock().unwrap_or_else(|x| x.into_inner());
+
+        if let Some(position) = cache.iter().position(|(x, _)| *x == key) {
+            let entry = cache.remove(position);
+            let frame = entry.1.clone();
+            cache.push(entry);
+
+            return Some(frame);
+        }
+
+        let (start, end) = *self.frames.get(index)?;
+        let frame: Arc<[u8]> = miniz_oxide::inflate::decompress_to_vec(&self.blob[start..end])
+            .ok()?
+            .into();
+
+        if cache.len() >= CACHED_FRAMES {
+            cache.remove(0);
+        }
+        cache.push((key, frame.clone()));
+
+        Some(frame)
+    }
+}
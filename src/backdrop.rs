@@ -0,0 +1,160 @@
+//! Backdrops drawn behind the sprites with `--background`.
+//!
+//! A backdrop is a tile repeated across the whole canvas, either one of the built in ones
+//! or a PNG image. The canvas is grown to whole tiles around the sprites,
+//! which are blended over the backdrop where they're partially transparent.
+
+use std::{path::PathBuf, str::FromStr};
+
+use image::{DynamicImage, ImageError, Rgba, RgbaImage};
+
+use crate::sprites::Scene;
+
+/// Width and height of the built in tiles in pixels
+const TILE_SIZE: u32 = 16;
+
+/// Short grass with a few darker blades
+#[rustfmt::skip]
+const GRASS: [&str; TILE_SIZE as usize] = [
+    "gggggggggggggggg",
+    "ggggggggggGggggg",
+    "ggGgggggggGlgggg",
+    "gGlGgggggGgggggg",
+    "gggggggggggggggg",
+    "ggggggglgggggggg",
+    "gggggggGgggggggg",
+    "ggggggGgGggggggg",
+    "gggggggggggggGgg",
+    "gggggggggggggGlg",
+    "gGgggggggggggggg",
+    "gGlggggggggggggg",
+    "gggggggggGgggggg",
+    "ggggggggGlGggggg",
+    "gggggggggggggggg",
+    "gggggggggggggggg",
+];
+
+/// Rocky ground with scattered pebbles
+#[rustfmt::skip]
+const CAVE: [&str; TILE_SIZE as usize] = [
+    "cccccccccccccccc",
+    "cchhcccccccccccc",
+    "chdddcccccccdccc",
+    "ccddcccccccccccc",
+    "cccccccccccccccc",
+    "ccccccccchhccccc",
+    "ccccccccchdddccc",
+    "cccccccccccddccc",
+    "cdcccccccccccccc",
+    "cccccccccccccccc",
+    "cccccchhcccccccc",
+    "ccccchdddcccccch",
+    "ccccccddccccccdd",
+    "cccccccccccccccc",
+    "ccccccccccchcccc",
+    "cccccccccccdcccc",
+];
+
+/// Calm water with rows of waves
+#[rustfmt::skip]
+const WATER: [&str; TILE_SIZE as usize] = [
+    "wwwwwwwwwwwwwwww",
+    "wwwwwwwwwwwwwwww",
+    "wwffwwwwwwwwwwww",
+    "wfWWfwwwwwwwwwww",
+    "WWwwWWwwwwwwwwww",
+    "wwwwwwwwwwwwwwww",
+    "wwwwwwwwwwwwwwww",
+    "wwwwwwwwwwwwwwww",
+    "wwwwwwwwwwffwwww",
+    "wwwwwwwwwfWWfwww",
+    "wwwwwwwwWWwwWWww",
+    "wwwwwwwwwwwwwwww",
+    "wwwwwwwwwwwwwwww",
+    "wwwwwwwwwwwwwwww",
+    "wwwwwwwwwwwwwwww",
+    "wwwwwwwwwwwwwwww",
+];
+
+/// Colors of the characters in the built in tiles
+const PALETTE: &[(char, [u8; 3])] = &[
+    ('g', [88, 160, 72]),
+    ('G', [60, 120, 56]),
+    ('l', [120, 192, 88]),
+    ('c', [106, 90, 74]),
+    ('d', [78, 66, 56]),
+    ('h', [138, 122, 102]),
+    ('w', [56, 120, 200]),
+    ('W', [40, 88, 168]),
+    ('f', [144, 192, 240]),
+];
+
+/// Error returned when a backdrop can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown background: {0}, expected grass, cave, water or the path to a PNG image")]
+pub struct ParseBackdropError(String);
+
+/// A tile drawn behind the sprites
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Backdrop {
+    Grass,
+    Cave,
+    Water,
+    /// A PNG image of any size, used as the tile
+    File(PathBuf),
+}
+
+impl Backdrop {
+    /// Reads the tile of the backdrop
+    pub fn tile(&self) -> Result<RgbaImage, ImageError> {
+        let rows = match self {
+            Backdrop::Grass => &GRASS,
+            Backdrop::Cave => &CAVE,
+            Backdrop::Water => &WATER,
+            Backdrop::File(path) => return Ok(image::open(path)?.to_rgba8()),
+        };
+
+        Ok(RgbaImage::from_fn(TILE_SIZE, TILE_SIZE, |x, y| {
+            let c = rows[y as usize].as_bytes()[x as usize] as char;
+            let [r, g, b] = PALETTE
+                .iter()
+                .find(|(key, _)| *key == c)
+                .map_or([0; 3], |(_, rgb)| *rgb);
+
+            Rgba([r, g, b, 255])
+        }))
+    }
+}
+
+impl FromStr for Backdrop {
+    type Err = ParseBackdropError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "grass" => Ok(Backdrop::Grass),
+            "cave" => Ok(Backdrop::Cave),
+            "water" => Ok(Backdrop::Water),
+            lower if lower.ends_with(".png") => Ok(Backdrop::File(PathBuf::from(s))),
+            _ => Err(ParseBackdropError(s.to_string())),
+        }
+    }
+}
+
+/// Draws `image` over `tile` repeated across the canvas, centering it on a canvas grown
+/// to whole tiles. The canvas grows no wider than `max_width`, unless the image is wider.
+pub fn compose(image: &DynamicImage, tile: &RgbaImage, max_width: u32) -> DynamicImage {
+    let (tile_w, tile_h) = (tile.width().max(1), tile.height().max(1));
+    let width = (image.width().div_ceil(tile_w).max(1) * tile_w).min(max_width.max(image.width()));
+    let height = image.height().div_ceil(tile_h).max(1) * tile_h;
+
+    let tile = DynamicImage::ImageRgba8(tile.clone());
+    let mut scene = Scene::new(width, height).with_backdrop(Some(&tile));
+    scene.place(
+        image,
+        i64::from((width - image.width()) / 2),
+        i64::from((height - image.height()) / 2),
+        0,
+    );
+
+    scene.render()
+}
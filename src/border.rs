@@ -0,0 +1,125 @@
+//! Frames drawn around the rendered sprites with `--border`.
+//!
+//! The frame is measured from the rendered text rather than the sprites, so it fits
+//! whatever the renderer draws in each character, eg. two rows of pixels with half blocks.
+
+use std::str::FromStr;
+
+use crate::sprites::visible_width;
+
+/// Error returned when a border style can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown border style: {0}, expected single, double, rounded, heavy or ascii")]
+pub struct ParseBorderStyleError(String);
+
+/// The characters a frame is drawn with
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BorderStyle {
+    /// `┌─┐`
+    #[default]
+    Single,
+    /// `╔═╗`
+    Double,
+    /// `╭─╮`
+    Rounded,
+    /// `┏━┓`
+    Heavy,
+    /// `+-+`, for terminals without box drawing characters
+    Ascii,
+}
+
+/// Corners and edges of a frame
+struct Chars {
+    top_left: char,
+    top_right: char,
+    bottom_left: char,
+    bottom_right: char,
+    horizontal: char,
+    vertical: char,
+}
+
+impl BorderStyle {
+    fn chars(self) -> Chars {
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] = match self {
+            BorderStyle::Single => ['┌', '┐', '└', '┘', '─', '│'],
+            BorderStyle::Double => ['╔', '╗', '╚', '╝', '═', '║'],
+            BorderStyle::Rounded => ['╭', '╮', '╰', '╯', '─', '│'],
+            BorderStyle::Heavy => ['┏', '┓', '┗', '┛', '━', '┃'],
+            BorderStyle::Ascii => ['+', '+', '+', '+', '-', '|'],
+        };
+
+        Chars {
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+            horizontal,
+            vertical,
+        }
+    }
+
+    /// Draws a frame around `art`, with `title` set into its top edge.
+    ///
+    /// The frame is as wide as the widest line of `art` or the title, whichever is wider,
+    /// and takes up two more columns and lines than that. With a `color` escape sequence
+    /// the edges are drawn in it, each reset on its own so it can't bleed into the art.
+    pub fn frame(self, art: &str, title: Option<&str>, color: Option<&str>) -> String {
+        let chars = self.chars();
+        let paint = |edge: String| match color {
+            Some(color) => format!("{color}{edge}{}", crate::color::RESET),
+            None => edge,
+        };
+        let title = title.map(|x| format!(" {x} "));
+
+        let inner = art
+            .lines()
+            .map(visible_width)
+            .chain(title.as_deref().map(|x| visible_width(x) + 1))
+            .max()
+            .unwrap_or(0);
+
+        let horizontal = |width: usize| chars.horizontal.to_string().repeat(width);
+
+        // The title starts a column in from the corner
+        let top = match &title {
+            Some(title) => format!(
+                "{}{}{title}{}{}",
+                chars.top_left,
+                chars.horizontal,
+                horizontal(inner - 1 - visible_width(title)),
+                chars.top_right
+            ),
+            None => format!("{}{}{}", chars.top_left, horizontal(inner), chars.top_right),
+        };
+
+        let vertical = paint(chars.vertical.to_string());
+        let mut lines = vec![paint(top)];
+        lines.extend(art.lines().map(|line| {
+            let padding = " ".repeat(inner - visible_width(line));
+            format!("{vertical}{line}{padding}{vertical}")
+        }));
+        lines.push(paint(format!(
+            "{}{}{}",
+            chars.bottom_left,
+            horizontal(inner),
+            chars.bottom_right
+        )));
+
+        lines.join("\n")
+    }
+}
+
+impl FromStr for BorderStyle {
+    type Err = ParseBorderStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "single" | "light" => Ok(BorderStyle::Single),
+            "double" => Ok(BorderStyle::Double),
+            "rounded" | "round" => Ok(BorderStyle::Rounded),
+            "heavy" | "bold" => Ok(BorderStyle::Heavy),
+            "ascii" => Ok(BorderStyle::Ascii),
+            _ => Err(ParseBorderStyleError(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Speech bubbles for pokemon to talk in, like cowsay.
+
+use crate::sprites::wrap_text;
+
+/// Widest text in a bubble before it's wrapped, the same as cowsay
+pub const MAX_TEXT_WIDTH: usize = 40;
+
+/// Lines of the tail between a bubble and the sprite beneath it
+const TAIL_LENGTH: usize = 2;
+
+/// Draws a bubble around `text`, wrapped to at most `width` columns
+pub fn bubble(text: &str, width: usize) -> Vec<String> {
+    let lines = wrap_text(text, width);
+    let inner = lines.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+
+    let mut out = Vec::with_capacity(lines.len() + 2);
+    out.push(format!(" {}", "_".repeat(inner + 2)));
+
+    for (i, line) in lines.iter().enumerate() {
+        let (left, right) = match i {
+            _ if lines.len() == 1 => ('<', '>'),
+            0 => ('/', '\\'),
+            i if i == lines.len() - 1 => ('\\', '/'),
+            _ => ('|', '|'),
+        };
+        let padding = " ".repeat(inner - line.chars().count());
+
+        out.push(format!("{left} {line}{padding} {right}"));
+    }
+
+    out.push(format!(" {}", "-".repeat(inner + 2)));
+    out
+}
+
+/// Places a bubble saying `text` above rendered art,
+/// with its tail pointing down at column `target` of the art.
+///
+/// The bubble is kept within `max_width` columns.
+pub fn say(art: &str, text: &str, target: usize, max_width: usize) -> String {
+    // The tail starts a little inside the bubble and goes down to the right
+    let start = target.saturating_sub(TAIL_LENGTH);
+    let indent = start.saturating_sub(2).min(max_width / 2);
+    let width = max_width
+        .saturating_sub(indent + 4)
+        .clamp(1, MAX_TEXT_WIDTH);
+
+    let margin = " ".repeat(indent);
+    let bubble = bubble(text, width)
+        .into_iter()
+        .map(|x| format!("{margin}{x}"));
+    let tail = (0..TAIL_LENGTH).map(|i| format!("{}\\", " ".repeat(start + i)));
+
+    bubble
+        .chain(tail)
+        .chain(art.lines().map(str::to_string))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,249 @@
+//! Cache of rendered sprites, so repeated invocations can skip composing.
+//!
+//! Every entry is a file in `renders/` under the cache directory,
+//! holding the rendered output for one set of sprites and options.
+//! Entries are named after a hash of the sprite files and everything else that goes into the render,
+//! so changed sprites or a new renderer never get served stale art, and a hit skips decoding them.
+//!
+//! Reading an entry bumps its modification time, and once there are more than
+//! [`MAX_ENTRIES`] the least recently used ones are removed.
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    paths::{self, PathError},
+    pokemon::Located,
+    render::Backend,
+    sprites::{Fit, LayoutOptions},
+    terminal::ColorDepth,
+};
+
+/// Version of the rendered output, bumped whenever rendering changes
+/// so entries written by older builds are never used
+pub const RENDERER_VERSION: u32 = 2;
+
+/// Amount of renders kept, enough for every pokemon in a few sizes
+pub const MAX_ENTRIES: usize = 4096;
+
+/// Error types for cache operations
+#[derive(Debug, thiserror::Error)]
+pub enum CacheError {
+    #[error("Failed to access render cache: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("{0}")]
+    Path(#[from] PathError),
+}
+
+/// Options which change how sprites are rendered and so are part of the key
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Width of the terminal in columns, which rows of sprites wrap at
+    pub width: u32,
+    /// How the sprites are shrunk before they're rendered
+    pub fit: Fit,
+    /// Spacing and alignment of the sprites
+    pub layout: LayoutOptions,
+    /// How the sprites are drawn
+    pub renderer: Backend,
+    /// Colors the sprites are reduced to
+    pub color_depth: ColorDepth,
+}
+
+/// Size of the cache on disk
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Amount of cached renders
+    pub entries: usize,
+    /// Total size of the cached renders in bytes
+    pub bytes: u64,
+}
+
+/// Handle to the render cache directory
+pub struct RenderCache {
+    dir: PathBuf,
+}
+
+impl RenderCache {
+    /// Creates a cache in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Creates a cache in the user's default cache directory
+    pub fn open_default() -> Result<Self, CacheError> {
+        Ok(Self::new(paths::cache_dir()?.join("renders")))
+    }
+
+    /// Directory holding the cached renders
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Builds the key of a render by hashing the sprites' files and how they're drawn,
+    /// the render options and the renderer version
+    pub fn key(sprites: &[Located], options: RenderOptions) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(RENDERER_VERSION.to_le_bytes());
+        hasher.update(options.width.to_le_bytes());
+
+        let fit = options.fit;
+        hasher.update(fit.max_width.to_le_bytes());
+        hasher.update(fit.max_height.to_le_bytes());
+        hasher.update(
+            fit.rows_height
+                .map_or(0, |x| u64::from(x) + 1)
+                .to_le_bytes(),
+        );
+        hasher.update(fit.row_padding.to_le_bytes());
+        hasher.update([fit.filter as u8]);
+
+        hasher.update([options.renderer as u8, options.color_depth as u8]);
+
+        match options.layout.background {
+            Some(rgb) => hasher.update([1, rgb[0], rgb[1], rgb[2]]),
+            None => hasher.update([0]),
+        }
+
+        // The layout makes no difference to a single sprite, so it doesn't split the cache
+        if sprites.len() > 1 {
+            hasher.update(options.layout.spacing.x.to_le_bytes());
+            hasher.update(options.layout.spacing.y.to_le_bytes());
+            hasher.update([options.layout.align as u8]);
+        }
+
+        // Lengths keep the files and drawings of neighbouring sprites from running together
+        for sprite in sprites {
+            let drawing = sprite.drawing();
+            hasher.update((sprite.file.len() as u64).to_le_bytes());
+            hasher.update(&sprite.file);
+            hasher.update((drawing.len() as u64).to_le_bytes());
+            hasher.update(drawing.as_bytes());
+        }
+
+        hasher
+            .finalize()
+            .iter()
+            .map(|x| format!("{x:02x}"))
+            .collect()
+    }
+
+    /// Looks up a cached render, marking it as recently used
+    pub fn get(&self, key: &str) -> Option<String> {
+        let path = self.dir.join(key);
+        let output = fs::read_to_string(&path).ok()?;
+
+        // Only decides what gets pruned first, so a read only cache still works
+        if let Ok(file) = File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+
+        Some(output)
+    }
+
+    /// Stores a render, replacing the file atomically so readers never see a partial entry
+    pub fn insert(&self, key: &str, output: &str) -> Result<(), CacheError> {
+        fs::create_dir_all(&self.dir)?;
+
+        let path = self.dir.join(key);
+        let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+
+        let result = (|| {
+            let mut file = File::create(&tmp)?;
+            file.write_all(output.as_bytes())?;
+            fs::rename(&tmp, &path)
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+
+        result?;
+
+        // Counting the entries is far cheaper than reading when each one was last used
+        if fs::read_dir(&self.dir)?.count() > MAX_ENTRIES {
+            self.prune(MAX_ENTRIES)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the least recently used renders until at most `max` are left,
+    /// returning how many were removed.
+    ///
+    /// Once over `max` it goes down to three quarters of it, so the directory
+    /// isn't sorted again on every insert after it fills up.
+    pub fn prune(&self, max: usize) -> Result<usize, CacheError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+
+            if metadata.is_file() {
+                files.push((metadata.modified()?, entry.path()));
+            }
+        }
+
+        if files.len() <= max {
+            return Ok(0);
+        }
+
+        files.sort();
+        let excess = files.len() - max + max / 4;
+
+        // Another process may have pruned the same entries already
+        for (_, path) in &files[..excess.min(files.len())] {
+            match fs::remove_file(path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+
+        Ok(excess)
+    }
+
+    /// Removes every cached render, returning how many there were
+    pub fn clear(&self) -> Result<usize, CacheError> {
+        let entries = self.stats()?.entries;
+
+        match fs::remove_dir_all(&self.dir) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(entries),
+        }
+    }
+
+    /// Counts the cached renders and their size
+    pub fn stats(&self) -> Result<CacheStats, CacheError> {
+        let mut stats = CacheStats::default();
+
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(stats),
+            Err(err) => return Err(err.into()),
+        };
+
+        for entry in entries {
+            let metadata = entry?.metadata()?;
+
+            if metadata.is_file() {
+                stats.entries += 1;
+                stats.bytes += metadata.len();
+            }
+        }
+
+        Ok(stats)
+    }
+}
@@ -1,17 +1,232 @@
-use clap::Parser;
+use std::{io, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use crate::{
+    backdrop::Backdrop,
+    border::BorderStyle,
+    color,
+    filters::Filter,
+    greetings::Greeting,
+    limits::{Limits, Size},
+    names::NameOutput,
+    patterns::Pattern,
+    pokemon::{Attributes, AttributesBuilder, Cap, Costume, Letter, PokemonError, Region},
+    prompt::Shell,
+    render::{Backend, Format},
+    source::{self, Sources},
+    sprites::{Align, LayoutOptions, ScaleFilter, Spacing},
+    stamp::{Corner, SPRITE_CREDIT},
+    state::StateScope,
+    terminal::ColorDepth,
+    theme::ThemeFormat,
+    transition::Transition,
+    types::Type,
+};
+
+/// Argument standing for the pokemon read from standard input
+pub const STDIN_ARG: &str = "-";
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// The pokemon to display, use "random" to get a random pokemon,
-    /// use a region to get a random pokemon from that region
-    #[arg(required = true)]
+    /// use a region to get a random pokemon from that region,
+    /// or join regions with "+" (eg. "kanto+johto") to pick from several.
+    /// "-" reads names and IDs from standard input
+    #[arg(required_unless_present_any = ["stdin", "file", "scene"])]
     pub pokemon: Vec<String>,
 
-    /// Whether to hide the pokemon's name which appears above it
+    /// Read names and IDs separated by spaces or newlines from standard input,
+    /// after the ones given as arguments
+    #[arg(long, default_value_t = false)]
+    pub stdin: bool,
+
+    /// Show the pokemon listed in a file, one per line with attributes for it alone,
+    /// eg. "raichu alolan shiny" or "rotom form=wash"
+    #[arg(long, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+
+    /// Draw the sprites listed in a scene file at their positions on a canvas of fixed size.
+    /// Scene files use a subset of TOML: tables, one `key = value` per line, strings, numbers,
+    /// booleans and comments. See the README for the format
+    #[arg(long, value_name = "PATH", conflicts_with = "file")]
+    pub scene: Option<PathBuf>,
+
+    /// Only pick random pokemon with these types, eg. "fire" or "fire,flying"
+    #[arg(short, long = "type", value_delimiter = ',')]
+    pub types: Vec<Type>,
+
+    /// Pick "random" pokemon from any of these regions, eg. "kanto,johto"
+    #[arg(long, value_delimiter = ',')]
+    pub regions: Vec<Region>,
+
+    /// Show every pokemon this many times, picking random ones again each time
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub count: u32,
+
+    /// Show every pokemon in the regions given instead of a random one, eg. "johto --all"
+    #[arg(long, default_value_t = false)]
+    pub all: bool,
+
+    /// Never pick the same random pokemon twice, nor one which was given by name
+    #[arg(long, default_value_t = false)]
+    pub unique: bool,
+
+    /// Whether to hide the pokemon's name which appears above it, the same as "--names none"
     #[arg(long, default_value_t = false)]
     pub hide_name: bool,
 
+    /// Where to write the names: "stdout", "stderr", "none" or "inline" beneath each sprite
+    #[arg(long, default_value = "stderr")]
+    pub names: NameOutput,
+
+    /// Don't mark shiny pokemon with a ★ and female ones with a ♀ after their names
+    #[arg(long, default_value_t = false)]
+    pub no_indicators: bool,
+
+    /// Show each pokemon's national dex number before its name, eg. "#025 Pikachu"
+    #[arg(long, default_value_t = false)]
+    pub show_id: bool,
+
+    /// Show the region and generation each pokemon was introduced in after its name
+    #[arg(long, default_value_t = false)]
+    pub caption_region: bool,
+
+    /// Make the pokemon say something in a speech bubble, like cowsay
+    #[arg(long, value_name = "TEXT")]
+    pub say: Option<String>,
+
+    /// Make the pokemon say a fortune, read from stdin when it's piped in
+    /// or picked from the built in ones otherwise
+    #[arg(long, default_value_t = false, conflicts_with = "say")]
+    pub fortune: bool,
+
+    /// Show a greeting instead of the names: "random", "off" or the index of a greeting
+    #[arg(long, default_value = "off")]
+    pub greeting: Greeting,
+
+    /// Show each pokemon's types beneath its sprite
+    #[arg(long, default_value_t = false)]
+    pub show_types: bool,
+
+    /// Show each pokemon's name centered beneath its sprite
+    #[arg(long, default_value_t = false)]
+    pub label: bool,
+
+    /// Color the names, stat bars, credits and border with the colors of each pokemon's type
+    #[arg(long, default_value_t = false)]
+    pub theme_by_type: bool,
+
+    /// Show each pokemon's pokedex entry next to its sprite.
+    /// Only the first few pokemon have entries until data/dex.csv is generated with scripts/dex.py
+    #[arg(long, default_value_t = false)]
+    pub info: bool,
+
+    /// Show each pokemon's base stats next to its sprite.
+    /// Only the first generation has stats until data/stats.csv is generated with scripts/stats.py
+    #[arg(long, default_value_t = false)]
+    pub stats: bool,
+
+    /// Don't shrink sprites which are wider than the terminal
+    #[arg(long, default_value_t = false)]
+    pub no_downscale: bool,
+
+    /// Don't shrink sprites which are taller than the terminal
+    #[arg(long, default_value_t = false)]
+    pub no_fit: bool,
+
+    /// Print each pokemon as soon as it's loaded rather than side by side, one after another
+    #[arg(long, default_value_t = false)]
+    pub stream: bool,
+
+    /// Show new pokemon every this many seconds, picking random ones again each time,
+    /// until q or Ctrl-C is pressed
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub slideshow: Option<u64>,
+
+    /// How the slideshow goes from one pokemon to the next: "none", "fade", "slide", "wipe" or "pokeball"
+    #[arg(long, default_value = "none", requires = "slideshow")]
+    pub transition: Transition,
+
+    #[command(flatten)]
+    pub limits: LimitArgs,
+
+    /// Page output taller than the terminal through $PAGER, or a page at a time if it isn't set.
+    /// Sprites aren't shrunk to fit the terminal then
+    #[arg(long, default_value_t = false)]
+    pub paged: bool,
+
+    /// Shrink the sprites to at most this many columns, even with --no-downscale
+    #[arg(long)]
+    pub max_width: Option<u32>,
+
+    /// Shrink the sprites so all of their rows take up at most this many lines, even with --no-downscale
+    #[arg(long)]
+    pub max_height: Option<u32>,
+
+    /// How sprites are shrunk: "nearest", "linear", "cubic", "gaussian" or "lanczos"
+    #[arg(long, default_value = "nearest")]
+    pub scale_filter: ScaleFilter,
+
+    /// Gap between sprites in pixels, a line of text is two pixels tall. At most 256
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(..=256))]
+    pub spacing: u32,
+
+    /// Columns between sprites in the same row, overrides --spacing
+    #[arg(long, value_parser = clap::value_parser!(u32).range(..=256))]
+    pub spacing_x: Option<u32>,
+
+    /// Pixels between rows of sprites, overrides --spacing
+    #[arg(long, value_parser = clap::value_parser!(u32).range(..=256))]
+    pub spacing_y: Option<u32>,
+
+    /// Vertical alignment of sprites shorter than others in their row: top, center or bottom
+    #[arg(long, default_value = "bottom")]
+    pub align: Align,
+
+    /// Fill transparent pixels with a color, as a name or hex code like "#ffffff"
+    #[arg(long, value_parser = color::parse)]
+    pub bg: Option<[u8; 3]>,
+
+    /// Draw the sprites over a backdrop: grass, cave, water or the path to a PNG image,
+    /// which is repeated to fill the space around them
+    #[arg(long, value_name = "BACKDROP", conflicts_with_all = ["bg", "info", "stats", "show_types", "label", "stream"])]
+    pub background: Option<Backdrop>,
+
+    /// Draw a frame around the sprites with their names in its top edge,
+    /// optionally in a style: "single", "double", "rounded", "heavy" or "ascii"
+    #[arg(
+        long,
+        value_name = "STYLE",
+        num_args = 0..=1,
+        default_missing_value = "single",
+        conflicts_with_all = ["slideshow", "stream"]
+    )]
+    pub border: Option<BorderStyle>,
+
+    /// Print diagnostics, like whether the render cache was used, to stderr
+    #[arg(short, long, default_value_t = false)]
+    pub verbose: bool,
+
+    /// Which state to use: "user" or "system", which is shared by every user (eg. for a MOTD)
+    #[arg(
+        long = "state",
+        value_name = "STATE",
+        global = true,
+        default_value = "user"
+    )]
+    pub state_scope: StateScope,
+
+    /// Never print more than this many lines, shrinking or cropping sprites to fit.
+    /// The name above the sprites counts as a line unless it's hidden
+    #[arg(long)]
+    pub max_lines: Option<u32>,
+
     /// The form of the pokemon
     #[arg(short, long, default_value = "")]
     pub form: String,
@@ -52,7 +267,532 @@ pub struct Args {
     #[arg(long, default_value_t = false)]
     pub galar: bool,
 
+    /// Display Unown shaped like a letter: a-z, ! or ?
+    #[arg(long)]
+    pub letter: Option<Letter>,
+
+    /// Display Pikachu wearing one of Ash's caps:
+    /// original, hoenn, sinnoh, unova, kalos, alola, partner or world
+    #[arg(long)]
+    pub cap: Option<Cap>,
+
+    /// Display Cosplay Pikachu in a costume: cosplay, rock-star, belle, pop-star, phd or libre
+    #[arg(long)]
+    pub cosplay: Option<Costume>,
+
+    /// Display the therian forme of Tornadus, Thundurus, Landorus or Enamorus
+    #[arg(long, default_value_t = false)]
+    pub therian: bool,
+
+    /// Display the origin forme of Giratina, Dialga or Palkia
+    #[arg(long, default_value_t = false)]
+    pub origin: bool,
+
+    /// Display the crowned form of Zacian or Zamazenta
+    #[arg(long, default_value_t = false)]
+    pub crowned: bool,
+
+    /// Display a cosmetic pattern: Vivillon's wings, Alcremie's cream,
+    /// the color of Flabébé's flower or Minior's core
+    #[arg(long)]
+    pub pattern: Option<Pattern>,
+
+    /// Display the small menu icons instead of the full sprites, eg. for prompts
+    #[arg(long, default_value_t = false)]
+    pub mini: bool,
+
     /// Display the female variant of the pokemon if it exists
     #[arg(long, default_value_t = false)]
     pub female: bool,
+
+    /// Load sprites from this directory, laid out like pokeget's ("regular/pikachu.png"),
+    /// before falling back to the embedded ones. Can be repeated, directories are tried in order.
+    /// Defaults to $POKEGET_SPRITE_DIR
+    #[arg(long, value_name = "PATH")]
+    pub sprite_dir: Vec<PathBuf>,
+
+    /// Download sprites from this URL, laid out like --sprite-dir, before falling back to the
+    /// embedded ones. Defaults to $POKEGET_SPRITE_URL
+    #[cfg(feature = "remote")]
+    #[arg(long, value_name = "URL")]
+    pub sprite_url: Option<String>,
+
+    /// Mirror the sprites horizontally, single pokemon can be flipped with "name:flip"
+    #[arg(long, default_value_t = false)]
+    pub flip: bool,
+
+    /// Show the sprites as dark silhouettes and hide the names until enter is pressed
+    #[arg(long, default_value_t = false)]
+    pub silhouette: bool,
+
+    /// Show the names right away with --silhouette
+    #[arg(long, default_value_t = false, requires = "silhouette")]
+    pub reveal: bool,
+
+    /// How sprites are drawn: "auto", "halfblock", "quadrant" for two colors in 2x2 pixels,
+    /// "braille" for finer but single colored dots,
+    /// "ascii" without colors, or the "sixel" and "kitty" graphics protocols
+    #[arg(long, default_value = "auto")]
+    pub renderer: Backend,
+
+    /// What to write to stdout: "text", "png" (or "png-stdout") or "svg" for an image
+    /// to redirect into a file or "html" for a snippet to embed in a web page
+    #[arg(long, default_value = "text")]
+    pub format: Format,
+
+    /// Also copy the sprites to the clipboard as a PNG image, eg. to paste them into a chat
+    #[arg(long, default_value_t = false, conflicts_with_all = ["slideshow", "stream"])]
+    pub copy: bool,
+
+    /// Draw each pokemon's name beneath its sprite on images, with its Pokedex ID with --show-id
+    #[arg(long, default_value_t = false)]
+    pub captions: bool,
+
+    /// Credit the sprites' source on images, optionally with your own text
+    #[arg(long, num_args = 0..=1, default_missing_value = SPRITE_CREDIT)]
+    pub credit: Option<String>,
+
+    /// Corner of the credit: "top-left", "top-right", "bottom-left" or "bottom-right"
+    #[arg(long, default_value = "bottom-right", requires = "credit")]
+    pub credit_corner: Corner,
+
+    /// Whether to wrap sixel and kitty graphics in tmux's passthrough, detected from $TMUX by default.
+    /// `--tmux` alone turns it on, `--tmux false` turns it off
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    pub tmux: Option<bool>,
+
+    /// Colors the terminal supports: "truecolor", "256", "16" or "none", detected by default
+    #[arg(long)]
+    pub color_depth: Option<ColorDepth>,
+
+    /// Recolor the sprites: "grayscale", "sepia", "gameboy" or "invert", several are applied in order
+    #[arg(long = "filter", value_name = "FILTER", value_delimiter = ',')]
+    pub filters: Vec<Filter>,
+
+    /// Turn the colors of the sprites around the color wheel by some degrees
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true)]
+    pub hue: Option<i32>,
+
+    /// Make the sprites brighter or darker, in percent of their brightness (100 keeps it)
+    #[arg(long, value_name = "PERCENT")]
+    pub brightness: Option<u32>,
+
+    /// Make the colors of the sprites more or less vivid, in percent (0 is gray, 100 keeps them)
+    #[arg(long, value_name = "PERCENT")]
+    pub saturation: Option<u32>,
+
+    /// Draw a line of a color around the sprites, as a name or hex code like "#ffffff"
+    #[arg(long, value_name = "COLOR", value_parser = color::parse)]
+    pub outline: Option<[u8; 3]>,
+
+    /// Draw a shadow beneath the sprites
+    #[arg(long, default_value_t = false)]
+    pub shadow: bool,
+}
+
+impl Args {
+    /// The pokemon to display, with "-" and --stdin replaced by the words read from `input`
+    ///
+    /// # Errors
+    ///
+    /// Returns `io::Error` if reading `input` fails
+    pub fn collect_pokemon(&self, mut input: impl io::Read) -> io::Result<Vec<String>> {
+        if !self.reads_stdin() {
+            return Ok(self.pokemon.clone());
+        }
+
+        let mut contents = String::new();
+        input.read_to_string(&mut contents)?;
+        let read: Vec<String> = contents.split_whitespace().map(str::to_string).collect();
+
+        let mut pokemon = Vec::with_capacity(self.pokemon.len() + read.len());
+        for arg in &self.pokemon {
+            match arg == STDIN_ARG {
+                true => pokemon.extend(read.iter().cloned()),
+                false => pokemon.push(arg.clone()),
+            }
+        }
+
+        // Input is only read once, so --stdin doesn't repeat what "-" already inserted
+        if self.stdin && !self.pokemon.iter().any(|x| x == STDIN_ARG) {
+            pokemon.extend(read);
+        }
+
+        Ok(pokemon)
+    }
+
+    /// Whether pokemon are read from standard input, with "-" or --stdin
+    pub fn reads_stdin(&self) -> bool {
+        self.stdin || self.pokemon.iter().any(|x| x == STDIN_ARG)
+    }
+
+    /// Where the names are written, taking --hide-name into account
+    pub fn name_output(&self) -> NameOutput {
+        match self.hide_name {
+            true => NameOutput::None,
+            false => self.names,
+        }
+    }
+
+    /// Whether names are drawn beneath the sprites, with --label or "--names inline".
+    ///
+    /// Inline names are left out of silhouettes so they don't give the pokemon away.
+    pub fn labels_names(&self) -> bool {
+        self.label || (self.name_output() == NameOutput::Inline && !self.silhouette)
+    }
+
+    /// A builder with the attributes set by the CLI flags
+    pub fn attributes_builder(&self) -> AttributesBuilder {
+        AttributesBuilder::new()
+            .with_form(&self.form)
+            .with_mega(self.mega)
+            .with_mega_x(self.mega_x)
+            .with_mega_y(self.mega_y)
+            .with_alolan(self.alolan)
+            .with_gmax(self.gmax)
+            .with_hisui(self.hisui)
+            .with_galar(self.galar)
+            .with_noble(self.noble)
+            .with_letter(self.letter)
+            .with_cap(self.cap)
+            .with_costume(self.cosplay)
+            .with_therian(self.therian)
+            .with_origin(self.origin)
+            .with_crowned(self.crowned)
+            .with_pattern(self.pattern)
+            .with_female(self.female)
+            .with_shiny(self.shiny || Attributes::rate_is_shiny())
+            .with_mini(self.mini)
+            .with_types(&self.types)
+            .with_regions(&self.regions)
+            .with_flip(self.flip)
+            .with_silhouette(self.silhouette)
+            .with_filters(&self.all_filters())
+            .with_outline(self.outline)
+            .with_shadow(self.shadow)
+            .with_source(self.sources())
+    }
+
+    /// Where sprites are loaded from, the directories first
+    #[cfg(feature = "remote")]
+    pub fn sources(&self) -> Sources {
+        let url = self.sprite_url.clone().or_else(source::url_from_env);
+        Sources::from_dirs_and_url(self.sprite_dirs(), url)
+    }
+
+    /// Where sprites are loaded from, the directories first
+    #[cfg(not(feature = "remote"))]
+    pub fn sources(&self) -> Sources {
+        Sources::from_dirs(self.sprite_dirs())
+    }
+
+    /// Directories sprites are read from before the embedded ones,
+    /// from --sprite-dir or `$POKEGET_SPRITE_DIR`
+    pub fn sprite_dirs(&self) -> Vec<PathBuf> {
+        match self.sprite_dir.is_empty() {
+            true => source::dirs_from_env(),
+            false => self.sprite_dir.clone(),
+        }
+    }
+
+    /// The adjustments from --hue, --brightness and --saturation followed by --filter
+    pub fn all_filters(&self) -> Vec<Filter> {
+        let adjustments = [
+            self.hue.map(Filter::Hue),
+            self.brightness.map(Filter::Brightness),
+            self.saturation.map(Filter::Saturation),
+        ];
+
+        adjustments
+            .into_iter()
+            .flatten()
+            .chain(self.filters.iter().copied())
+            .collect()
+    }
+
+    /// The arrangement of sprites, combining --spacing with its overrides
+    pub fn layout(&self) -> LayoutOptions {
+        LayoutOptions {
+            spacing: Spacing {
+                x: self.spacing_x.unwrap_or(self.spacing),
+                y: self.spacing_y.unwrap_or(self.spacing),
+            },
+            align: self.align,
+            background: self.bg,
+        }
+    }
+}
+
+impl TryFrom<&Args> for Attributes {
+    type Error = PokemonError;
+
+    /// Creates attributes from the CLI flags
+    fn try_from(args: &Args) -> Result<Self, Self::Error> {
+        args.attributes_builder().build()
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show a random party of six pokemon, like the in-game party screen
+    Team {
+        /// Show a random level next to each pokemon's name
+        #[arg(long, default_value_t = false)]
+        levels: bool,
+    },
+
+    /// Show a pokemon next to information about your system, like neofetch
+    Fetch {
+        /// The pokemon to show
+        #[arg(default_value = "random")]
+        pokemon: String,
+    },
+
+    /// Guess pokemon from their silhouettes, type "quit" or press ctrl-d to stop
+    Quiz {
+        /// Only show pokemon from these regions, eg. "kanto,johto"
+        #[arg(long, value_delimiter = ',')]
+        regions: Vec<Region>,
+    },
+
+    /// Draw a pokemon with every renderer, to pick the one that looks best in your terminal
+    DebugRender {
+        /// The pokemon to draw
+        pokemon: String,
+    },
+
+    /// Draw an item, like "master-ball" or "leftovers", or "random" for a well known one
+    Item {
+        /// The item to draw
+        #[arg(default_value = "random")]
+        item: String,
+    },
+
+    /// Show two pokemon like in a battle, the first seen from behind and the second facing it
+    Battle {
+        /// Your pokemon, drawn in the bottom left
+        #[arg(default_value = "random")]
+        player: String,
+
+        /// The opposing pokemon, drawn in the top right
+        #[arg(default_value = "random")]
+        opponent: String,
+    },
+
+    /// Pick a pokemon by typing part of its name, then draw it and print its name to stdout
+    Pick,
+
+    /// Browse every pokemon, searching by typing and drawing the selected one's sprite
+    #[cfg(feature = "tui")]
+    Browse,
+
+    /// Print a pokemon and its name for a shell prompt, without a trailing newline
+    Prompt {
+        /// The pokemon to show
+        #[arg(default_value = "random")]
+        pokemon: String,
+
+        /// Lines the sprite takes up
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=2))]
+        lines: u32,
+
+        /// The shell the prompt is for, so escape codes don't count towards its width:
+        /// "plain", "bash" or "zsh"
+        #[arg(long, default_value = "plain")]
+        shell: Shell,
+    },
+
+    /// List the forms a pokemon has sprites for, like megas, regional variants and female sprites
+    Forms {
+        /// The pokemon to look up
+        pokemon: String,
+    },
+
+    /// Print the dominant colors of a pokemon's sprite as hex codes, eg. for a terminal theme
+    Palette {
+        /// The pokemon to take the colors from
+        #[arg(default_value = "random")]
+        pokemon: String,
+
+        /// Amount of colors to print
+        #[arg(
+            long,
+            short = 'n',
+            default_value_t = 5,
+            value_parser = clap::value_parser!(u32).range(1..=32)
+        )]
+        count: u32,
+
+        /// Take the colors from the shiny sprite
+        #[arg(long, default_value_t = false)]
+        shiny: bool,
+
+        /// Print a 16 color terminal scheme made from the colors instead:
+        /// "alacritty", "kitty", "xresources" or "wal"
+        #[arg(long, value_name = "FORMAT")]
+        export_theme: Option<ThemeFormat>,
+    },
+
+    /// Walk through what pokeget can draw, to see what works in your terminal
+    Demo,
+
+    /// Manage the cache of rendered sprites
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Show which pokemon you display the most, recorded locally once enabled
+    Stats {
+        #[command(subcommand)]
+        action: Option<StatsAction>,
+    },
+
+    /// Show achievements earned by displaying pokemon
+    Achievements {
+        #[command(subcommand)]
+        action: Option<AchievementsAction>,
+    },
+
+    /// Check the embedded sprites against a signed manifest of SHA-256 digests, as written by sha256sum
+    Verify {
+        /// The manifest to check against, a file or a URL
+        #[arg(long, required_unless_present = "print")]
+        manifest: Option<String>,
+
+        /// The public key the manifest was signed with, as an OpenSSH public key or a file containing one
+        #[arg(long)]
+        key: Option<String>,
+
+        /// The signature of the manifest, a file or a URL, `<manifest>.sig` by default
+        #[arg(long, requires = "key")]
+        signature: Option<String>,
+
+        /// Print the manifest of the embedded sprites instead
+        #[arg(long, default_value_t = false, conflicts_with = "manifest")]
+        print: bool,
+    },
+
+    /// Manage pokeget's saved state
+    State {
+        #[command(subcommand)]
+        action: StateAction,
+    },
+
+    /// Work with sprite packs for --sprite-dir
+    Pack {
+        #[command(subcommand)]
+        action: PackAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PackAction {
+    /// Rename the sprites in a directory to pokeget's layout, eg. "Pikachu_Shiny.png"
+    /// to "shiny/pikachu.png", listing the files which couldn't be identified
+    Normalize {
+        /// The directory of the pack
+        dir: PathBuf,
+
+        /// Only show what would be renamed
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Render sprites ahead of time so they display instantly later
+    Warm {
+        /// What to render: pokemon names or IDs, regions, "favorites" or "all".
+        /// Favorites are rendered if nothing is given
+        selection: Vec<String>,
+
+        /// Width of the terminal to render for, defaults to the current terminal
+        #[arg(long)]
+        width: Option<u32>,
+
+        /// Render the shiny sprites
+        #[arg(short, long, default_value_t = false)]
+        shiny: bool,
+
+        /// Render for --no-downscale
+        #[arg(long, default_value_t = false)]
+        no_downscale: bool,
+    },
+
+    /// Remove every cached render
+    Clear,
+
+    /// Show how many renders are cached and their size
+    Stats,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StatsAction {
+    /// Start recording displayed pokemon
+    Enable,
+
+    /// Stop recording displayed pokemon, keeping what was recorded so far
+    Disable,
+
+    /// Forget everything recorded so far
+    Reset,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AchievementsAction {
+    /// Turn announcing newly unlocked achievements after displaying on or off
+    Notify {
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StateAction {
+    /// Export favorites, living dex progress, hunt counters and aliases to a JSON file
+    Export {
+        /// The file to write to
+        file: PathBuf,
+    },
+
+    /// Replace favorites, living dex progress, hunt counters and aliases with an exported file
+    Import {
+        /// The file to read from
+        file: PathBuf,
+    },
+}
+
+/// Resource caps for output that keeps running, flattened into the commands producing it
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct LimitArgs {
+    /// Draw at most this many frames per second
+    #[arg(long, value_name = "FPS")]
+    pub max_fps: Option<u32>,
+
+    /// Spend at most this percentage of a CPU core drawing
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub max_cpu: Option<u8>,
+
+    /// Keep at most this much memory in frame buffers, eg. "64M"
+    #[arg(long, value_name = "SIZE")]
+    pub max_memory: Option<Size>,
+
+    /// Frames per second while the terminal isn't focused, 0 to pause
+    #[arg(long, value_name = "FPS")]
+    pub idle_fps: Option<u32>,
+}
+
+impl LimitArgs {
+    /// The limits from the flags, falling back to the user's limits file
+    pub fn limits(&self) -> Limits {
+        Limits {
+            fps: self.max_fps,
+            cpu: self.max_cpu,
+            memory: self.max_memory,
+            idle_fps: self.idle_fps,
+        }
+        .or(Limits::read())
+    }
 }
@@ -0,0 +1,118 @@
+//! Copying images to the system clipboard, for `--copy`.
+//!
+//! The clipboard is reached through the tools each platform has for it: wl-copy on Wayland,
+//! xclip on X11 and osascript on macOS. Without a display there's no clipboard to copy to,
+//! which is reported as [`ClipboardError::NoClipboard`] so callers can carry on without it.
+
+use std::{
+    env,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+/// Error types for copying to the clipboard
+#[derive(Debug, thiserror::Error)]
+pub enum ClipboardError {
+    #[error("No clipboard available, there's no display to copy to")]
+    NoClipboard,
+
+    #[error("{0} isn't installed")]
+    MissingTool(&'static str),
+
+    #[error("{0} failed: {1}")]
+    Failed(&'static str, String),
+
+    #[error("Failed to run {0}: {1}")]
+    Io(&'static str, io::Error),
+}
+
+/// A program which copies what it reads from stdin
+struct Tool {
+    program: &'static str,
+    args: &'static [&'static str],
+}
+
+/// The tool for the display pokeget runs on, `None` without a display
+fn tool() -> Option<Tool> {
+    let set = |key| env::var_os(key).is_some_and(|x| !x.is_empty());
+
+    if set("WAYLAND_DISPLAY") {
+        return Some(Tool {
+            program: "wl-copy",
+            args: &["--type", "image/png"],
+        });
+    }
+
+    if set("DISPLAY") {
+        return Some(Tool {
+            program: "xclip",
+            args: &["-selection", "clipboard", "-target", "image/png"],
+        });
+    }
+
+    None
+}
+
+/// Places a PNG image on the clipboard
+pub fn copy_png(png: &[u8]) -> Result<(), ClipboardError> {
+    if cfg!(target_os = "macos") {
+        return copy_png_macos(png);
+    }
+
+    let tool = tool().ok_or(ClipboardError::NoClipboard)?;
+    let program = tool.program;
+
+    let mut child = Command::new(program)
+        .args(tool.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => ClipboardError::MissingTool(program),
+            _ => ClipboardError::Io(program, err),
+        })?;
+
+    // Taken so stdin is closed once written, which is when the tool starts serving the image
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(png)
+            .map_err(|err| ClipboardError::Io(program, err))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| ClipboardError::Io(program, err))?;
+
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(ClipboardError::Failed(
+            program,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
+
+/// osascript can't read images from stdin, so the image goes through a temporary file
+fn copy_png_macos(png: &[u8]) -> Result<(), ClipboardError> {
+    const PROGRAM: &str = "osascript";
+
+    let path = env::temp_dir().join(format!("pokeget-{}.png", std::process::id()));
+    std::fs::write(&path, png).map_err(|err| ClipboardError::Io(PROGRAM, err))?;
+
+    let script = format!(
+        "set the clipboard to (read (POSIX file \"{}\") as «class PNGf»)",
+        path.display()
+    );
+    let output = Command::new(PROGRAM).args(["-e", &script]).output();
+    let _ = std::fs::remove_file(&path);
+
+    let output = output.map_err(|err| ClipboardError::Io(PROGRAM, err))?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(ClipboardError::Failed(
+            PROGRAM,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
@@ -0,0 +1,195 @@
+//! ANSI escape codes for colors, degrading to what the terminal supports.
+
+use std::fmt;
+
+use crate::terminal::ColorDepth;
+
+/// Resets all colors and styles
+pub const RESET: &str = "\x1b[0m";
+
+/// Colors which can be given by name instead of as hex
+const NAMED: &[(&str, [u8; 3])] = &[
+    ("black", [0, 0, 0]),
+    ("white", [255, 255, 255]),
+    ("gray", [128, 128, 128]),
+    ("grey", [128, 128, 128]),
+    ("red", [255, 0, 0]),
+    ("green", [0, 128, 0]),
+    ("blue", [0, 0, 255]),
+    ("yellow", [255, 255, 0]),
+    ("cyan", [0, 255, 255]),
+    ("magenta", [255, 0, 255]),
+];
+
+/// Error returned when a color can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid color: {0}, expected a name or a hex code like #1e1e2e")]
+pub struct ParseColorError(String);
+
+/// Parses a color name or a hex code like `#1e1e2e` or `fff`
+pub fn parse(s: &str) -> Result<[u8; 3], ParseColorError> {
+    let lower = s.trim().to_lowercase();
+    let error = || ParseColorError(s.to_string());
+
+    if let Some((_, rgb)) = NAMED.iter().find(|(name, _)| *name == lower) {
+        return Ok(*rgb);
+    }
+
+    let hex = lower.strip_prefix('#').unwrap_or(&lower);
+    if !hex.chars().all(|x| x.is_ascii_hexdigit()) {
+        return Err(error());
+    }
+
+    let digit = |i: usize, len: usize| u8::from_str_radix(&hex[i..i + len], 16);
+
+    let rgb = match hex.len() {
+        3 => [digit(0, 1), digit(1, 1), digit(2, 1)].map(|x| x.map(|x| x * 17)),
+        6 => [digit(0, 2), digit(2, 2), digit(4, 2)],
+        _ => return Err(error()),
+    };
+
+    let [r, g, b] = rgb;
+    Ok([
+        r.map_err(|_| error())?,
+        g.map_err(|_| error())?,
+        b.map_err(|_| error())?,
+    ])
+}
+
+/// Writes a color as a hex code like `#1e1e2e`
+pub fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Converts a color to hue in degrees, saturation and lightness from 0 to 1
+pub fn to_hsl(rgb: [u8; 3]) -> (f32, f32, f32) {
+    let [r, g, b] = rgb.map(|x| f32::from(x) / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = match max {
+        _ if max == r => ((g - b) / delta).rem_euclid(6.0),
+        _ if max == g => (b - r) / delta + 2.0,
+        _ => (r - g) / delta + 4.0,
+    };
+
+    (hue * 60.0, saturation, lightness)
+}
+
+/// Converts hue in degrees, saturation and lightness back to a color
+pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> [u8; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let m = lightness - chroma / 2.0;
+    [r, g, b].map(|x| ((x + m) * 255.0).round().clamp(0.0, 255.0) as u8)
+}
+
+/// Converts an RGB color to the closest color in the xterm 256 color palette
+pub fn to_ansi256([r, g, b]: [u8; 3]) -> u8 {
+    // Grays have their own, finer ramp
+    if r == g && g == b {
+        return match r {
+            0..=7 => 16,
+            249..=255 => 231,
+            _ => 232 + ((u16::from(r) - 8) * 24 / 241) as u8,
+        };
+    }
+
+    let level = |x: u8| ((u16::from(x) * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// The basic 16 ANSI colors with xterm's default values
+const ANSI16: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+/// Converts an RGB color to the closest of the basic 16 ANSI colors
+pub fn to_ansi16(rgb: [u8; 3]) -> u8 {
+    let distance = |color: &[u8; 3]| -> u32 {
+        color
+            .iter()
+            .zip(rgb)
+            .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+            .sum()
+    };
+
+    (0..ANSI16.len())
+        .min_by_key(|i| distance(&ANSI16[*i]))
+        .unwrap_or(0) as u8
+}
+
+/// Writes the escape code setting the foreground or background color
+pub fn write_color(
+    out: &mut impl fmt::Write,
+    rgb: [u8; 3],
+    depth: ColorDepth,
+    background: bool,
+) -> fmt::Result {
+    let [r, g, b] = rgb;
+    let layer = if background { 48 } else { 38 };
+
+    match depth {
+        ColorDepth::TrueColor => write!(out, "\x1b[{layer};2;{r};{g};{b}m"),
+        ColorDepth::Ansi256 => write!(out, "\x1b[{layer};5;{}m", to_ansi256(rgb)),
+        ColorDepth::Ansi16 => {
+            let index = to_ansi16(rgb);
+            let base = layer - 8;
+
+            // Bright colors have their own range of codes, 60 above the normal ones
+            match index {
+                0..=7 => write!(out, "\x1b[{}m", base + index),
+                _ => write!(out, "\x1b[{}m", base + 60 + index - 8),
+            }
+        }
+        ColorDepth::Monochrome => Ok(()),
+    }
+}
+
+/// Escape code setting the foreground color
+pub fn fg(rgb: [u8; 3], depth: ColorDepth) -> String {
+    let mut out = String::new();
+    // Writing into a string never fails
+    let _ = write_color(&mut out, rgb, depth, false);
+    out
+}
+
+/// Escape code setting the background color
+pub fn bg(rgb: [u8; 3], depth: ColorDepth) -> String {
+    let mut out = String::new();
+    let _ = write_color(&mut out, rgb, depth, true);
+    out
+}
@@ -0,0 +1,1064 @@
+//! Handlers for pokeget's subcommands.
+
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, IsTerminal, Write},
+    sync::Arc,
+    thread,
+    time::Instant,
+};
+
+use clap::Parser;
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use pokeget::{
+    achievements::{self, ACHIEVEMENTS},
+    cache::{CacheError, RenderCache},
+    cli::{AchievementsAction, Args, CacheAction, Command, PackAction, StateAction, StatsAction},
+    color,
+    demo::{Scene, STEPS},
+    forms::FormIndex,
+    items::{Item, ItemError},
+    labels::{label_line, Label},
+    list::{normalize_filename, List, ListError},
+    names::title_case,
+    pacing::{FramePacer, Pace},
+    pack, palette, picker,
+    pokemon::{Attributes, AttributesBuilder, Located, Pokemon, PokemonError, Region, Selection},
+    prompt::Shell,
+    quiz::Quiz,
+    render::{Backend, HalfBlock, Renderer},
+    source::Sources,
+    sprites::{
+        self, combine_grid, compose_with_text, downscale_to_fit, render_sprites, LayoutOptions,
+        Position, SpriteError, Text, GRID_SPACING,
+    },
+    state::{StateError, StateScope, StateStore},
+    sysinfo::SystemInfo,
+    terminal::{ColorDepth, HostTerminal, Protocol, Terminal, VirtualTerminal},
+    theme::{Scheme, ThemeFormat},
+    transform::Transform,
+    usage::WEEKDAYS,
+    verify::{self, Manifest, VerifyError},
+};
+use rand::Rng;
+
+/// Columns between the two sides of a battle
+const BATTLE_GAP: u32 = 8;
+
+/// Dominant colors a terminal scheme is made from
+const THEME_COLORS: usize = 16;
+
+/// Amount of pokemon in a party
+const TEAM_SIZE: usize = 6;
+
+/// Frame rate of the animations in the demo
+const DEMO_FPS: u32 = 20;
+
+/// Amount of frames in each animation of the demo
+const DEMO_FRAMES: u32 = 24;
+
+/// Error types for subcommands
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error(transparent)]
+    State(#[from] StateError),
+
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+
+    #[error("Error reading pokemon list: {0}")]
+    List(#[from] ListError),
+
+    #[error("Error creating pokemon: {0}")]
+    Pokemon(#[from] PokemonError),
+
+    #[error("Error combining sprites: {0}")]
+    Sprite(#[from] SpriteError),
+
+    #[error("Error drawing item: {0}")]
+    Item(#[from] ItemError),
+
+    #[error("Failed to read guess: {0}")]
+    Input(#[from] io::Error),
+
+    #[error("Failed to run the picker: {0}")]
+    Picker(io::Error),
+
+    #[error("No pokemon was picked")]
+    NothingPicked,
+
+    #[cfg(feature = "tui")]
+    #[error("Failed to run the browser: {0}")]
+    Browse(io::Error),
+
+    #[error(transparent)]
+    Verify(#[from] VerifyError),
+
+    #[error("{0} sprites failed verification")]
+    Unverified(usize),
+
+    #[error("Failed to normalize pack {0}: {1}")]
+    Pack(String, io::Error),
+}
+
+/// Runs a subcommand
+pub fn run(command: Command, scope: StateScope) -> Result<(), CommandError> {
+    match command {
+        Command::Team { levels } => team(levels),
+        Command::Quiz { regions } => quiz(regions),
+        Command::DebugRender { pokemon } => debug_render(pokemon),
+        Command::Item { item } => item_sprite(item),
+        Command::Pick => pick(),
+        Command::Battle { player, opponent } => battle(player, opponent),
+        #[cfg(feature = "tui")]
+        Command::Browse => pokeget::tui::browse(List::global()).map_err(CommandError::Browse),
+        Command::Prompt {
+            pokemon,
+            lines,
+            shell,
+        } => prompt(pokemon, lines, shell),
+        Command::Forms { pokemon } => forms(pokemon),
+        Command::Palette {
+            pokemon,
+            count,
+            shiny,
+            export_theme,
+        } => palette(pokemon, count as usize, shiny, export_theme),
+        Command::Demo => demo(),
+        Command::Fetch { pokemon } => fetch(pokemon),
+        Command::Cache { action } => cache(action, scope),
+        Command::Stats { action } => stats(action, scope),
+        Command::Achievements { action } => achievements(action, scope),
+        Command::Verify {
+            manifest,
+            key,
+            signature,
+            print,
+        } => verify(manifest, key, signature, print),
+        Command::State { action } => state(action, scope).map_err(Into::into),
+        Command::Pack { action } => pack(action),
+    }
+}
+
+fn team(levels: bool) -> Result<(), CommandError> {
+    const COLUMNS: usize = 2;
+
+    let list = List::global();
+    let terminal = HostTerminal;
+    let renderer = HalfBlock::new(terminal.color_depth());
+
+    // Every member rolls for shininess on its own
+    let mut pokemons: Vec<Pokemon> = (0..TEAM_SIZE)
+        .map(|_| {
+            let attributes = AttributesBuilder::new()
+                .with_shiny(Attributes::rate_is_shiny())
+                .with_source(Sources::from_env())
+                .build()?;
+
+            Pokemon::new("random".to_string(), list, attributes).map_err(CommandError::from)
+        })
+        .collect::<Result<_, _>>()?;
+
+    // Shrink sprites until both columns fit side by side
+    let cell_width = terminal.width().saturating_sub(GRID_SPACING) / COLUMNS as u32;
+    downscale_to_fit(&mut pokemons, &VirtualTerminal::new(cell_width));
+
+    let mut rng = rand::thread_rng();
+    let labels: Vec<Label> = pokemons
+        .iter()
+        .map(|x| match levels {
+            true => Label::plain(&format!("{} Lv. {}", x.name, rng.gen_range(1..=100))),
+            false => Label::plain(&x.name),
+        })
+        .collect();
+
+    let output: Vec<String> = combine_grid(&pokemons, COLUMNS)?
+        .iter()
+        .map(|row| {
+            format!(
+                "{}\n{}",
+                renderer.render(&row.image),
+                label_line(row, &labels)
+            )
+        })
+        .collect();
+
+    println!("{}", output.join("\n"));
+
+    Ok(())
+}
+
+/// Every way of drawing sprites the terminal can show, with a label for each
+fn render_variants(terminal: &dyn Terminal) -> Vec<(&'static str, Backend, ColorDepth)> {
+    let depth = terminal.color_depth();
+
+    let mut variants = vec![
+        ("halfblock", Backend::HalfBlock, depth),
+        ("quadrant", Backend::Quadrant, depth),
+        ("braille", Backend::Braille, depth),
+        ("ascii", Backend::Ascii, ColorDepth::Monochrome),
+        (
+            "halfblock, 256 colors",
+            Backend::HalfBlock,
+            ColorDepth::Ansi256,
+        ),
+        (
+            "halfblock, 16 colors",
+            Backend::HalfBlock,
+            ColorDepth::Ansi16,
+        ),
+    ];
+
+    // Graphics protocols only show up as garbage in terminals which don't support them
+    match terminal.protocol() {
+        Protocol::Kitty => variants.push(("kitty", Backend::Kitty, depth)),
+        Protocol::Sixel => variants.push(("sixel", Backend::Sixel, depth)),
+        _ => {}
+    }
+
+    variants
+}
+
+fn debug_render(pokemon: String) -> Result<(), CommandError> {
+    let list = List::global();
+    let terminal = HostTerminal;
+    let attributes = AttributesBuilder::new()
+        .with_source(Sources::from_env())
+        .build()?;
+    let pokemon = Pokemon::new(pokemon, list, attributes)?;
+
+    for (label, backend, depth) in render_variants(&terminal) {
+        let renderer = backend.renderer(depth);
+        let mut pokemons = [pokemon.clone()];
+        downscale_to_fit(&mut pokemons, &renderer.canvas(&terminal));
+
+        println!("{label}:\n{}\n", renderer.render(&pokemons[0].sprite));
+    }
+
+    Ok(())
+}
+
+fn item_sprite(item: String) -> Result<(), CommandError> {
+    let terminal = HostTerminal;
+    let renderer = Backend::Auto
+        .resolve(&terminal, io::stdout().is_terminal())
+        .renderer(terminal.color_depth());
+
+    let item = Item::new(&item, &Sources::from_env())?;
+
+    eprintln!("{}", item.name);
+    println!("{}", renderer.render(&item.sprite));
+
+    Ok(())
+}
+
+fn battle(player: String, opponent: String) -> Result<(), CommandError> {
+    let list = List::global();
+    let terminal = HostTerminal;
+    let renderer = Backend::Auto
+        .resolve(&terminal, io::stdout().is_terminal())
+        .renderer(terminal.color_depth());
+    let builder = || AttributesBuilder::new().with_source(Sources::from_env());
+
+    // Picked once so falling back to the front sprite shows the same pokemon
+    let name = Selection::parse(player).eval(list, &[])?;
+    let player = match Pokemon::new(name.clone(), list, builder().with_back(true).build()?) {
+        Ok(pokemon) => pokemon,
+        // The embedded sprites only face forward, so the front one is turned towards the opponent
+        Err(_) => Pokemon::new(name, list, builder().with_flip(true).build()?)?,
+    };
+    let opponent = Pokemon::new(opponent, list, builder().build()?)?;
+
+    // Each side gets half of the screen
+    let mut pokemons = [player, opponent];
+    let half = renderer
+        .canvas(&terminal)
+        .width()
+        .saturating_sub(BATTLE_GAP)
+        / 2;
+    downscale_to_fit(&mut pokemons, &VirtualTerminal::new(half.max(1)));
+    let [player, opponent] = &pokemons;
+
+    // The opponent stands further away in the top right, the player closer in the bottom left
+    let (player_w, player_h) = player.sprite.dimensions();
+    let (opponent_w, opponent_h) = opponent.sprite.dimensions();
+    let width = player_w + BATTLE_GAP + opponent_w;
+    let height = opponent_h.max(opponent_h / 2 + player_h);
+
+    let mut scene = sprites::Scene::new(width, height);
+    scene
+        .place(&opponent.sprite, i64::from(width - opponent_w), 0, 0)
+        // Closer to the screen, so in front of the opponent where they overlap
+        .place(&player.sprite, 0, i64::from(height - player_h), 1);
+    let image = scene.render();
+
+    eprintln!("A wild {} appeared! Go, {}!", opponent.name, player.name);
+    println!("{}", renderer.render(&image));
+
+    Ok(())
+}
+
+fn pick() -> Result<(), CommandError> {
+    let list = List::global();
+    let name = picker::pick(list)
+        .map_err(CommandError::Picker)?
+        .ok_or(CommandError::NothingPicked)?;
+
+    let terminal = HostTerminal;
+    let renderer = Backend::Auto
+        .resolve(&terminal, io::stderr().is_terminal())
+        .renderer(terminal.color_depth());
+
+    let attributes = AttributesBuilder::new()
+        .with_source(Sources::from_env())
+        .build()?;
+    let mut pokemon = Pokemon::new(name.clone(), list, attributes)?;
+    downscale_to_fit(
+        std::slice::from_mut(&mut pokemon),
+        &renderer.canvas(&terminal),
+    );
+
+    // The sprite is for whoever picked it, the name for whatever runs pokeget
+    eprintln!("{}", renderer.render(&pokemon.sprite));
+    println!("{name}");
+
+    Ok(())
+}
+
+fn prompt(pokemon: String, lines: u32, shell: Shell) -> Result<(), CommandError> {
+    let list = List::global();
+    let renderer = HalfBlock::new(HostTerminal.color_depth());
+
+    // Picked once so falling back to the full sprites shows the same pokemon
+    let name = Selection::parse(pokemon).eval(list, &[])?;
+    let shiny = Attributes::rate_is_shiny();
+    let attributes = |mini| {
+        AttributesBuilder::new()
+            .with_shiny(shiny)
+            .with_mini(mini)
+            .with_source(Sources::from_env())
+            .build()
+    };
+
+    // The menu icons are made for this size, but not every build has them
+    let pokemon = match Pokemon::new(name.clone(), list, attributes(true)?) {
+        Ok(pokemon) => pokemon,
+        Err(_) => Pokemon::new(name, list, attributes(false)?)?,
+    };
+
+    // Every line holds two pixels, and shrinking this much needs pixels blended rather than dropped
+    let sprite = pokemon
+        .sprite
+        .resize(u32::MAX, lines * 2, FilterType::Triangle);
+    let mut segment = renderer.render(&sprite).trim_end_matches('\n').to_string();
+
+    segment.push(' ');
+    segment.push_str(&pokemon.name);
+    if pokemon.shiny {
+        segment.push_str(" ★");
+    }
+
+    print!("{}", shell.escape(&segment));
+    io::stdout().flush()?;
+
+    Ok(())
+}
+
+fn palette(
+    pokemon: String,
+    count: usize,
+    shiny: bool,
+    export_theme: Option<ThemeFormat>,
+) -> Result<(), CommandError> {
+    let list = List::global();
+    let attributes = AttributesBuilder::new()
+        .with_shiny(shiny)
+        .with_source(Sources::from_env())
+        .build()?;
+    let pokemon = Pokemon::new(pokemon, list, attributes)?;
+
+    if let Some(format) = export_theme {
+        // More colors than usual give every slot of the scheme a better match
+        let swatches = palette::dominant_colors(&pokemon.sprite, count.max(THEME_COLORS));
+        print!("{}", Scheme::from_swatches(&swatches).export(format));
+        return Ok(());
+    }
+
+    let swatches = palette::dominant_colors(&pokemon.sprite, count);
+
+    // Only the hex codes are printed for scripts
+    if !io::stdout().is_terminal() {
+        for swatch in &swatches {
+            println!("{}", swatch.hex());
+        }
+        return Ok(());
+    }
+
+    let depth = HostTerminal.color_depth();
+    let total: usize = swatches.iter().map(|x| x.population).sum();
+
+    println!("{}", pokemon.name);
+    for swatch in &swatches {
+        let share = swatch.population * 100 / total.max(1);
+        println!(
+            "{}██████{} {} {share:>3}%",
+            color::fg(swatch.rgb, depth),
+            color::RESET,
+            swatch.hex()
+        );
+    }
+
+    Ok(())
+}
+
+fn forms(pokemon: String) -> Result<(), CommandError> {
+    let list = List::global();
+    let name = Selection::parse(pokemon).eval(list, &[])?;
+    let species = normalize_filename(&name);
+    if !list.contains(&species) {
+        return Err(PokemonError::PokemonNotFound(name).into());
+    }
+
+    let index = FormIndex::embedded();
+    let display = list.format_name(&species);
+
+    // Sets of sprites for each form, in order with the default form first
+    let mut forms: Vec<(&str, Vec<&'static str>)> = Vec::new();
+    for variant in index.variants(&species) {
+        let set = match (variant.shiny, variant.female) {
+            (false, false) => "regular",
+            (true, false) => "shiny",
+            (false, true) => "female",
+            (true, true) => "shiny female",
+        };
+
+        match forms.last_mut() {
+            Some((form, sets)) if *form == variant.form => sets.push(set),
+            _ => forms.push((&variant.form, vec![set])),
+        }
+    }
+
+    if forms.is_empty() {
+        println!("{display} has no embedded sprites");
+        return Ok(());
+    }
+
+    let width = forms
+        .iter()
+        .map(|(form, _)| form.len().max("default".len()))
+        .max()
+        .unwrap_or_default();
+
+    println!("Forms of {display}:");
+    for (form, sets) in forms {
+        let form = if form.is_empty() { "default" } else { form };
+        println!("  {form:<width$}  {}", sets.join(", "));
+    }
+
+    Ok(())
+}
+
+fn demo() -> Result<(), CommandError> {
+    let list = List::global();
+    let terminal = HostTerminal;
+    let interactive = io::stdout().is_terminal();
+    let prompt = interactive && io::stdin().is_terminal();
+
+    for (i, step) in STEPS.iter().enumerate() {
+        println!("{}/{}: {}", i + 1, STEPS.len(), step.title);
+        if let Some(command) = step.command {
+            println!("$ {command}");
+        }
+        println!();
+
+        match demo_scene(step.scene, list, &terminal, interactive) {
+            Ok(()) => {}
+            // Builds and sprite packs missing some sprites skip their steps instead of ending the demo
+            Err(CommandError::Pokemon(err)) => println!("Skipped: {err}"),
+            Err(err) => return Err(err),
+        }
+
+        if prompt && i + 1 < STEPS.len() {
+            eprint!("\nPress enter for the next step, or type q to quit ");
+            io::stderr().flush()?;
+
+            let mut answer = String::new();
+            if io::stdin().read_line(&mut answer)? == 0 || answer.trim().eq_ignore_ascii_case("q") {
+                break;
+            }
+        }
+
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Draws a scene of the demo
+fn demo_scene(
+    scene: Scene,
+    list: &List,
+    terminal: &dyn Terminal,
+    interactive: bool,
+) -> Result<(), CommandError> {
+    let depth = terminal.color_depth();
+    let backend = Backend::Auto.resolve(terminal, interactive);
+    let builder = AttributesBuilder::new().with_source(Sources::from_env());
+
+    match scene {
+        Scene::Renderers(pokemon) => {
+            let attributes = builder.build()?;
+            let pokemon = Pokemon::new(pokemon.to_string(), list, attributes)?;
+
+            for (label, backend, depth) in render_variants(terminal) {
+                let renderer = backend.renderer(depth);
+                let mut pokemons = [pokemon.clone()];
+                downscale_to_fit(&mut pokemons, &renderer.canvas(terminal));
+
+                println!("{label}:\n{}\n", renderer.render(&pokemons[0].sprite));
+            }
+        }
+        Scene::Pokemon {
+            pokemon,
+            attributes,
+        } => {
+            let attributes = Arc::new(attributes(builder).build()?);
+            let renderer = backend.renderer(depth);
+            let canvas = renderer.canvas(terminal);
+
+            let mut pokemons = pokemon
+                .iter()
+                .map(|x| Pokemon::new(x.to_string(), list, Arc::clone(&attributes)))
+                .collect::<Result<Vec<_>, _>>()?;
+            downscale_to_fit(&mut pokemons, &canvas);
+
+            println!(
+                "{}",
+                render_sprites(
+                    &pokemons,
+                    &canvas,
+                    LayoutOptions::default(),
+                    renderer.as_ref()
+                )?
+            );
+        }
+        Scene::Transition {
+            from,
+            to,
+            transition,
+        } => {
+            let attributes = Arc::new(builder.build()?);
+            // Frames are drawn over each other, which only works with text
+            let renderer = backend.for_text().renderer(depth);
+            let canvas = renderer.canvas(terminal);
+
+            let mut pokemons = [
+                Pokemon::new(from.to_string(), list, Arc::clone(&attributes))?,
+                Pokemon::new(to.to_string(), list, attributes)?,
+            ];
+            for pokemon in pokemons.chunks_mut(1) {
+                downscale_to_fit(pokemon, &canvas);
+            }
+
+            let frames = transition.frames(&pokemons[0].sprite, &pokemons[1].sprite, DEMO_FRAMES);
+            animate(frames.collect(), renderer.as_ref(), interactive)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws frames over each other, or just the last one when not writing to a terminal
+fn animate(
+    frames: Vec<DynamicImage>,
+    renderer: &dyn Renderer,
+    interactive: bool,
+) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    let mut pacer = FramePacer::for_host(DEMO_FPS);
+    let mut drawn_lines = 0;
+    let mut i = if interactive {
+        0
+    } else {
+        frames.len().saturating_sub(1)
+    };
+
+    while let Some(frame) = frames.get(i) {
+        let start = Instant::now();
+        let text = renderer.render(frame);
+
+        if drawn_lines > 0 {
+            write!(stdout, "\x1b[{drawn_lines}A")?;
+        }
+        writeln!(stdout, "{text}")?;
+        stdout.flush()?;
+        drawn_lines = text.lines().count();
+
+        match pacer.frame_written(start.elapsed()) {
+            Pace::Wait(wait) => {
+                thread::sleep(wait);
+                i += 1;
+            }
+            // Too slow to animate, skip to the end
+            Pace::Static => i = (i + 1).max(frames.len() - 1),
+        }
+    }
+
+    Ok(())
+}
+
+fn fetch(pokemon: String) -> Result<(), CommandError> {
+    let list = List::global();
+    let terminal = HostTerminal;
+
+    // Graphics can't be placed next to text
+    let backend = Backend::Auto
+        .resolve(&terminal, io::stdout().is_terminal())
+        .for_text();
+    let depth = match backend {
+        Backend::Ascii => ColorDepth::Monochrome,
+        _ => terminal.color_depth(),
+    };
+    let renderer = backend.renderer(depth);
+
+    let attributes = AttributesBuilder::new()
+        .with_shiny(Attributes::rate_is_shiny())
+        .with_source(Sources::from_env())
+        .build()?;
+    let mut pokemons = [Pokemon::new(pokemon, list, attributes)?];
+    downscale_to_fit(&mut pokemons, &renderer.canvas(&terminal));
+    let pokemon = &pokemons[0];
+
+    let paint = |text: &str| match pokemon.accent() {
+        Some(rgb) if depth != ColorDepth::Monochrome => {
+            format!("{}{text}{}", color::fg(rgb, depth), color::RESET)
+        }
+        _ => text.to_string(),
+    };
+    let line =
+        |label: &str, value: &str| Text::Line(Label::styled(&format!("{}: {value}", paint(label))));
+
+    let info = SystemInfo::gather();
+    let mut text = Vec::new();
+
+    if let Some(title) = info.title() {
+        text.push(Text::Line(Label::styled(&paint(&title))));
+        text.push(Text::Line(Label::plain(&"-".repeat(title.chars().count()))));
+    }
+
+    text.extend(
+        info.fields()
+            .iter()
+            .map(|(label, value)| line(label, value)),
+    );
+    text.push(line("Pokemon", &pokemon.name));
+
+    println!(
+        "{}",
+        compose_with_text(
+            &pokemon.sprite,
+            renderer.as_ref(),
+            &text,
+            Position::Right,
+            &terminal
+        )
+    );
+
+    Ok(())
+}
+
+fn quiz(regions: Vec<Region>) -> Result<(), CommandError> {
+    let list = List::global();
+    let terminal = HostTerminal;
+    let renderer = HalfBlock::new(terminal.color_depth());
+    let attributes = Arc::new(
+        AttributesBuilder::new()
+            .with_regions(&regions)
+            .with_source(Sources::from_env())
+            .build()?,
+    );
+    let mut quiz = Quiz::new();
+
+    loop {
+        let mut pokemons = vec![Pokemon::new(
+            "random".to_string(),
+            list,
+            Arc::clone(&attributes),
+        )?];
+        downscale_to_fit(&mut pokemons, &terminal);
+
+        let revealed = pokemons[0].sprite.clone();
+        pokemons[0].sprite = Transform::Silhouette.apply(&revealed);
+        println!(
+            "{}",
+            render_sprites(&pokemons, &terminal, LayoutOptions::default(), &renderer)?
+        );
+
+        eprint!("Who's that Pokémon? ");
+        io::stderr().flush()?;
+
+        let mut guess = String::new();
+        if io::stdin().read_line(&mut guess)? == 0 || guess.trim().eq_ignore_ascii_case("quit") {
+            break;
+        }
+
+        let pokemon = &mut pokemons[0];
+        let correct = quiz.answer(&pokemon.species, &pokemon.name, &guess);
+
+        pokemon.sprite = revealed;
+        println!(
+            "{}",
+            render_sprites(&pokemons, &terminal, LayoutOptions::default(), &renderer)?
+        );
+
+        match correct {
+            true => eprintln!("It's {}! Streak: {}\n", pokemons[0].name, quiz.streak),
+            false => eprintln!("It's {}! Streak lost\n", pokemons[0].name),
+        }
+    }
+
+    if quiz.rounds > 0 {
+        eprintln!(
+            "\nGuessed {} of {}, best streak: {}",
+            quiz.correct, quiz.rounds, quiz.best_streak
+        );
+    }
+
+    Ok(())
+}
+
+fn cache(action: CacheAction, scope: StateScope) -> Result<(), CommandError> {
+    let cache = RenderCache::open_default()?;
+
+    match action {
+        CacheAction::Warm {
+            selection,
+            width,
+            shiny,
+            no_downscale,
+        } => {
+            let list = List::global();
+            let names = expand_selection(&selection, list, scope)?;
+            // Planned like `pokeget <pokemon>` without other flags, so it finds the renders
+            let mut args = Args::parse_from(
+                ["pokeget"]
+                    .into_iter()
+                    .chain(names.iter().map(String::as_str)),
+            );
+            args.shiny = shiny;
+            args.no_downscale = no_downscale;
+            let attributes = Arc::new(Attributes::try_from(&args)?);
+            let mut terminal = VirtualTerminal::probe(&HostTerminal);
+            if let Some(width) = width {
+                terminal.width = width;
+            }
+
+            let layout = args.layout();
+            let plan = crate::Plan::new(&args, terminal);
+            let options = plan.render_options(layout);
+
+            for name in &names {
+                let located = Located::from_selection(
+                    Selection::parse(name.clone()),
+                    &[],
+                    list,
+                    Arc::clone(&attributes),
+                )?;
+                let key = RenderCache::key(std::slice::from_ref(&located), options);
+
+                let pokemons =
+                    crate::prepare(vec![located], plan.fit, &plan.canvas, layout.spacing)?;
+                cache.insert(
+                    &key,
+                    &render_sprites(&pokemons, &plan.canvas, layout, plan.renderer.as_ref())?,
+                )?;
+            }
+
+            eprintln!(
+                "Rendered {} sprites into {}",
+                names.len(),
+                cache.dir().display()
+            );
+        }
+        CacheAction::Clear => {
+            let removed = cache.clear()?;
+            eprintln!("Removed {removed} cached renders");
+        }
+        CacheAction::Stats => {
+            let stats = cache.stats()?;
+            println!("Location: {}", cache.dir().display());
+            println!("Entries: {}", stats.entries);
+            println!("Size: {:.1} KiB", stats.bytes as f64 / 1024.0);
+        }
+    }
+
+    Ok(())
+}
+
+/// Expands the arguments of `cache warm` into pokemon filenames.
+///
+/// Regions and "all" expand to every pokemon they contain rather than a random one.
+fn expand_selection(
+    selection: &[String],
+    list: &List,
+    scope: StateScope,
+) -> Result<Vec<String>, CommandError> {
+    let favorites = || -> Result<Vec<String>, CommandError> {
+        let state = StateStore::open(scope)?.load()?;
+        Ok(state.favorites.into_iter().collect())
+    };
+
+    if selection.is_empty() {
+        return favorites();
+    }
+
+    let by_regions = |regions: &[Region]| -> Result<Vec<String>, CommandError> {
+        let mut names = Vec::new();
+
+        for region in regions {
+            for id in region.range() {
+                names.push(list.get_by_id(id)?.clone());
+            }
+        }
+
+        Ok(names)
+    };
+
+    let mut names = Vec::new();
+
+    for arg in selection {
+        if arg.eq_ignore_ascii_case("all") {
+            names.extend(by_regions(&Region::ALL)?);
+            continue;
+        }
+
+        if arg.eq_ignore_ascii_case("favorites") {
+            names.extend(favorites()?);
+            continue;
+        }
+
+        match Selection::parse(arg.clone()) {
+            Selection::Region(region) => names.extend(by_regions(&[region])?),
+            Selection::Regions(regions) => names.extend(by_regions(&regions)?),
+            Selection::DexId(id) => names.push(list.get_by_id(id)?.clone()),
+            Selection::Range(range) => names.extend_from_slice(list.get_range(&range)?),
+            Selection::Name(name) | Selection::Picked(name) => names.push(name),
+            Selection::Random => names.push(list.random()?),
+        }
+    }
+
+    let mut seen = HashSet::new();
+    names.retain(|x| seen.insert(x.clone()));
+
+    Ok(names)
+}
+
+fn stats(action: Option<StatsAction>, scope: StateScope) -> Result<(), CommandError> {
+    const TOP: usize = 10;
+    const BAR_WIDTH: u64 = 30;
+
+    let store = StateStore::open(scope)?;
+
+    let Some(action) = action else {
+        let usage = store.load()?.usage;
+        let list = List::global();
+
+        if !usage.enabled && usage.total() == 0 {
+            println!("Usage statistics are disabled, enable them with `pokeget stats enable`.");
+            println!(
+                "They are only ever stored locally in {}",
+                store.path().display()
+            );
+            return Ok(());
+        }
+
+        let status = if usage.enabled { "enabled" } else { "disabled" };
+        println!("Recording is {status}");
+        println!(
+            "Displayed {} pokemon, {} shiny",
+            usage.total(),
+            usage.shinies
+        );
+
+        println!("\nMost displayed:");
+        for (i, (name, count)) in usage.top(TOP).into_iter().enumerate() {
+            println!("{:>3}. {:<16} {count}", i + 1, list.format_name(name));
+        }
+
+        println!("\nBy weekday (UTC):");
+        let most = usage.weekdays.iter().copied().max().unwrap_or(0).max(1);
+        for (day, count) in WEEKDAYS.iter().zip(usage.weekdays) {
+            let bar = "█".repeat((count * BAR_WIDTH / most) as usize);
+            println!("  {:<10} {bar} {count}", title_case(day));
+        }
+
+        return Ok(());
+    };
+
+    match action {
+        StatsAction::Enable => {
+            store.update(|state| state.usage.enabled = true)?;
+            eprintln!("Recording usage statistics to {}", store.path().display());
+        }
+        StatsAction::Disable => {
+            store.update(|state| state.usage.enabled = false)?;
+            eprintln!("Stopped recording usage statistics");
+        }
+        StatsAction::Reset => {
+            store.update(|state| state.usage.reset())?;
+            eprintln!("Cleared usage statistics");
+        }
+    }
+
+    Ok(())
+}
+
+fn achievements(action: Option<AchievementsAction>, scope: StateScope) -> Result<(), CommandError> {
+    let store = StateStore::open(scope)?;
+
+    if let Some(AchievementsAction::Notify { enabled }) = action {
+        store.update(|state| state.usage.notify = enabled)?;
+        let status = if enabled { "on" } else { "off" };
+        eprintln!("Achievement notifications turned {status}");
+        return Ok(());
+    }
+
+    let list = List::global();
+
+    // Pick up achievements unlocked by other means, like editing favorites
+    let state = store.update(|state| {
+        achievements::unlock_new(state, list);
+        state.clone()
+    })?;
+
+    for achievement in ACHIEVEMENTS {
+        let mark = if state.achievements.contains(achievement.id) {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+
+        println!(
+            "{mark} {:<20} {}",
+            achievement.name, achievement.description
+        );
+    }
+
+    println!(
+        "\n{} of {} unlocked",
+        state.achievements.len(),
+        ACHIEVEMENTS.len()
+    );
+
+    if !state.usage.enabled {
+        println!("Progress is only recorded with usage statistics, enable them with `pokeget stats enable`.");
+    }
+
+    Ok(())
+}
+
+fn verify(
+    manifest: Option<String>,
+    key: Option<String>,
+    signature: Option<String>,
+    print: bool,
+) -> Result<(), CommandError> {
+    if print {
+        print!("{}", Manifest::of_embedded().serialize());
+        return Ok(());
+    }
+
+    let Some(source) = manifest else {
+        return Ok(());
+    };
+
+    let contents = verify::fetch(&source)?;
+
+    match key {
+        Some(key) => {
+            let signature = signature.unwrap_or_else(|| format!("{source}.sig"));
+            verify::check_signature(&contents, &verify::fetch(&signature)?, &key)?;
+        }
+        None => eprintln!("The manifest's signature wasn't checked, pass --key to check it"),
+    }
+
+    let report = Manifest::parse(&String::from_utf8_lossy(&contents))?.verify_embedded();
+
+    for path in &report.mismatched {
+        println!("MODIFIED  {path}");
+    }
+    for path in &report.missing {
+        println!("MISSING   {path}");
+    }
+    for path in &report.unlisted {
+        println!("UNLISTED  {path}");
+    }
+
+    eprintln!("Verified {} sprites", report.verified);
+
+    match report.is_clean() {
+        true => Ok(()),
+        false => Err(CommandError::Unverified(
+            report.mismatched.len() + report.missing.len() + report.unlisted.len(),
+        )),
+    }
+}
+
+fn pack(action: PackAction) -> Result<(), CommandError> {
+    match action {
+        PackAction::Normalize { dir, dry_run } => {
+            let list = List::global();
+            let error = |err| CommandError::Pack(dir.display().to_string(), err);
+            let plan = pack::plan(&dir, list).map_err(error)?;
+
+            for rename in &plan.renames {
+                println!("{} -> {}", rename.from.display(), rename.to.display());
+            }
+            for rename in &plan.conflicts {
+                println!(
+                    "CONFLICT   {} -> {} (already taken)",
+                    rename.from.display(),
+                    rename.to.display()
+                );
+            }
+            for path in &plan.unmatched {
+                println!("UNMATCHED  {}", path.display());
+            }
+
+            if !dry_run {
+                pack::apply(&dir, &plan).map_err(error)?;
+            }
+
+            eprintln!(
+                "{} {} sprites, {} already in place, {} conflicting, {} unmatched",
+                if dry_run { "Would rename" } else { "Renamed" },
+                plan.renames.len(),
+                plan.unchanged,
+                plan.conflicts.len(),
+                plan.unmatched.len()
+            );
+
+            Ok(())
+        }
+    }
+}
+
+fn state(action: StateAction, scope: StateScope) -> Result<(), StateError> {
+    let store = StateStore::open(scope)?;
+
+    match action {
+        StateAction::Export { file } => {
+            let state = store.load()?;
+            fs::write(&file, state.export_json())?;
+            eprintln!("Exported state to {}", file.display());
+        }
+        StateAction::Import { file } => {
+            let json = fs::read_to_string(&file)?;
+            store.update(|state| state.import_json(&json))??;
+            eprintln!("Imported state from {}", file.display());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! The guided tour shown by `pokeget demo`.
+//!
+//! Each step shows off one thing pokeget can do along with the command doing it,
+//! so trying the demo in a new terminal shows which options are worth putting in a config.
+
+use crate::{pokemon::AttributesBuilder, transition::Transition};
+
+/// What a step of the demo draws
+#[derive(Debug, Copy, Clone)]
+pub enum Scene {
+    /// The same pokemon drawn with every renderer the terminal can show
+    Renderers(&'static str),
+    /// Pokemon side by side, with attributes set up by `attributes`
+    Pokemon {
+        pokemon: &'static [&'static str],
+        attributes: fn(AttributesBuilder) -> AttributesBuilder,
+    },
+    /// An animated transition from one pokemon to another
+    Transition {
+        from: &'static str,
+        to: &'static str,
+        transition: Transition,
+    },
+}
+
+/// A step of the demo
+#[derive(Debug, Copy, Clone)]
+pub struct Step {
+    pub title: &'static str,
+    /// The command showing the same thing for users to copy, if there is one
+    pub command: Option<&'static str>,
+    pub scene: Scene,
+}
+
+/// The steps of the demo, in order
+pub const STEPS: &[Step] = &[
+    Step {
+        title: "Renderers",
+        command: Some("pokeget pikachu --renderer quadrant"),
+        scene: Scene::Renderers("pikachu"),
+    },
+    Step {
+        title: "Several pokemon",
+        command: Some("pokeget bulbasaur charmander squirtle"),
+        scene: Scene::Pokemon {
+            pokemon: &["bulbasaur", "charmander", "squirtle"],
+            attributes: |x| x,
+        },
+    },
+    Step {
+        title: "Shiny pokemon",
+        command: Some("pokeget gyarados --shiny"),
+        scene: Scene::Pokemon {
+            pokemon: &["gyarados"],
+            attributes: |x| x.with_shiny(true),
+        },
+    },
+    Step {
+        title: "Alternative forms",
+        command: Some("pokeget raichu sandslash --alolan"),
+        scene: Scene::Pokemon {
+            pokemon: &["raichu", "sandslash"],
+            attributes: |x| x.with_alolan(true),
+        },
+    },
+    Step {
+        title: "Facing each other",
+        command: Some("pokeget charizard blastoise:flip"),
+        scene: Scene::Pokemon {
+            pokemon: &["charizard", "blastoise:flip"],
+            attributes: |x| x,
+        },
+    },
+    Step {
+        title: "Who's that Pokémon?",
+        command: Some("pokeget random --silhouette"),
+        scene: Scene::Pokemon {
+            pokemon: &["random"],
+            attributes: |x| x.with_silhouette(true),
+        },
+    },
+    Step {
+        title: "Fading",
+        command: None,
+        scene: Scene::Transition {
+            from: "pichu",
+            to: "pikachu",
+            transition: Transition::Fade,
+        },
+    },
+    Step {
+        title: "Pokeball",
+        command: None,
+        scene: Scene::Transition {
+            from: "pikachu",
+            to: "random",
+            transition: Transition::Pokeball,
+        },
+    },
+];
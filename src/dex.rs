@@ -0,0 +1,69 @@
+use crate::{names::title_case, pokemon::Pokemon};
+
+/// A pokedex entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct DexEntry {
+    /// The category, eg. "Seed" for the "Seed Pokémon"
+    pub category: &'static str,
+    /// Height in metres
+    pub height: f32,
+    /// Weight in kilograms
+    pub weight: f32,
+    /// The pokedex description
+    pub flavor_text: &'static str,
+}
+
+// Generated by `build.rs` from `dex.csv`
+include!(concat!(env!("OUT_DIR"), "/dex.rs"));
+
+/// The entries of `dex.csv`, backed by a table generated at compile time.
+pub struct Dex {
+    entries: &'static [(&'static str, DexEntry)],
+}
+
+impl Dex {
+    /// Creates a new [`Dex`] from the embedded table
+    pub fn read() -> Self {
+        Self { entries: &ENTRIES }
+    }
+
+    /// Gets the entry of a pokemon by filename
+    pub fn get(&self, filename: &str) -> Option<&DexEntry> {
+        self.entries
+            .binary_search_by(|(x, _)| (*x).cmp(filename))
+            .ok()
+            .map(|i| &self.entries[i].1)
+    }
+
+    /// Builds the paragraphs of text describing a pokemon
+    pub fn info(&self, pokemon: &Pokemon) -> Vec<String> {
+        let mut paragraphs = Vec::new();
+        let entry = self.get(&pokemon.species);
+
+        match entry {
+            Some(entry) => {
+                paragraphs.push(format!("{}, the {} Pokémon", pokemon.name, entry.category))
+            }
+            None => paragraphs.push(pokemon.name.clone()),
+        }
+
+        if !pokemon.types.is_empty() {
+            let types: Vec<String> = pokemon.types.iter().map(|x| title_case(x.name())).collect();
+            paragraphs.push(format!("Type: {}", types.join(" / ")));
+        }
+
+        match entry {
+            Some(entry) => {
+                paragraphs.push(format!(
+                    "Height: {:.1} m, Weight: {:.1} kg",
+                    entry.height, entry.weight
+                ));
+                paragraphs.push(String::new());
+                paragraphs.push(entry.flavor_text.to_string());
+            }
+            None => paragraphs.push("No pokedex entry available.".to_string()),
+        }
+
+        paragraphs
+    }
+}
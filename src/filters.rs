@@ -0,0 +1,177 @@
+//! Color filters which recolor sprites before they're rendered,
+//! and effects drawn around sprites from their shape after that.
+
+use std::str::FromStr;
+
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+
+use crate::color;
+
+/// The four shades of the original Game Boy screen, darkest first
+pub const GAMEBOY_PALETTE: [[u8; 3]; 4] =
+    [[15, 56, 15], [48, 98, 48], [139, 172, 15], [155, 188, 15]];
+
+/// Error returned when a filter can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown filter: {0}, expected grayscale, sepia, gameboy or invert")]
+pub struct ParseFilterError(String);
+
+/// A recoloring of every pixel in a sprite
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// Shades of gray
+    Grayscale,
+    /// Brownish tones, like an old photograph
+    Sepia,
+    /// The four green shades of the Game Boy
+    Gameboy,
+    /// Inverted colors
+    Invert,
+    /// Colors turned around the color wheel by some degrees
+    Hue(i32),
+    /// Colors made brighter or darker, in percent of the original brightness
+    Brightness(u32),
+    /// Colors made more or less vivid, in percent of the original saturation
+    Saturation(u32),
+}
+
+/// Perceived brightness of a color, from 0 to 255
+fn luma([r, g, b]: [u8; 3]) -> u8 {
+    ((299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000) as u8
+}
+
+impl Filter {
+    /// Recolors a single pixel
+    pub fn map(self, rgb: [u8; 3]) -> [u8; 3] {
+        match self {
+            Filter::Grayscale => [luma(rgb); 3],
+            Filter::Sepia => {
+                let [r, g, b] = rgb.map(f32::from);
+                [
+                    0.393 * r + 0.769 * g + 0.189 * b,
+                    0.349 * r + 0.686 * g + 0.168 * b,
+                    0.272 * r + 0.534 * g + 0.131 * b,
+                ]
+                .map(|x| x.min(255.0) as u8)
+            }
+            Filter::Gameboy => GAMEBOY_PALETTE[usize::from(luma(rgb)) * 4 / 256],
+            Filter::Invert => rgb.map(|x| 255 - x),
+            Filter::Hue(degrees) => {
+                let (hue, saturation, lightness) = color::to_hsl(rgb);
+                color::from_hsl(hue + degrees as f32, saturation, lightness)
+            }
+            Filter::Brightness(percent) => {
+                rgb.map(|x| (u32::from(x) * percent / 100).min(255) as u8)
+            }
+            Filter::Saturation(percent) => {
+                // Moves each channel away from or towards the gray of the same brightness
+                let gray = f32::from(luma(rgb));
+                rgb.map(|x| {
+                    (gray + (f32::from(x) - gray) * percent as f32 / 100.0).clamp(0.0, 255.0) as u8
+                })
+            }
+        }
+    }
+
+    /// Recolors every pixel of an image, keeping its transparency
+    pub fn apply(self, image: &mut RgbaImage) {
+        for pixel in image.pixels_mut() {
+            let [r, g, b] = self.map([pixel[0], pixel[1], pixel[2]]);
+            pixel.0 = [r, g, b, pixel[3]];
+        }
+    }
+}
+
+/// Applies filters one after another to a sprite
+pub fn apply_all(sprite: DynamicImage, filters: &[Filter]) -> DynamicImage {
+    if filters.is_empty() {
+        return sprite;
+    }
+
+    let mut rgba = sprite.into_rgba8();
+    for filter in filters {
+        filter.apply(&mut rgba);
+    }
+
+    DynamicImage::ImageRgba8(rgba)
+}
+
+/// Opacity of a shadow beneath fully opaque pixels
+const SHADOW_ALPHA: u8 = 128;
+
+/// An effect drawn around a sprite from its alpha mask, which grows the sprite to make room
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Effect {
+    /// A line of one color a pixel wide around the sprite
+    Outline([u8; 3]),
+    /// A translucent black copy of the sprite beneath it, a pixel down and to the right
+    Shadow,
+}
+
+impl Effect {
+    /// Draws the effect around an image
+    pub fn apply(self, image: &RgbaImage) -> RgbaImage {
+        let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            return image.clone();
+        }
+
+        match self {
+            Effect::Outline([r, g, b]) => {
+                // Every pixel next to an opaque one, diagonally too, is part of the outline
+                let mut canvas = RgbaImage::from_fn(width + 2, height + 2, |x, y| {
+                    let opaque = (x.saturating_sub(2)..=x.min(width - 1))
+                        .flat_map(|x| {
+                            (y.saturating_sub(2)..=y.min(height - 1)).map(move |y| (x, y))
+                        })
+                        .any(|(x, y)| image.get_pixel(x, y)[3] > 0);
+
+                    match opaque {
+                        true => Rgba([r, g, b, 255]),
+                        false => Rgba([0; 4]),
+                    }
+                });
+                imageops::overlay(&mut canvas, image, 1, 1);
+
+                canvas
+            }
+            Effect::Shadow => {
+                let mut canvas = RgbaImage::new(width + 1, height + 1);
+                for (x, y, pixel) in image.enumerate_pixels() {
+                    let alpha = (u16::from(pixel[3]) * u16::from(SHADOW_ALPHA) / 255) as u8;
+                    canvas.put_pixel(x + 1, y + 1, Rgba([0, 0, 0, alpha]));
+                }
+                imageops::overlay(&mut canvas, image, 0, 0);
+
+                canvas
+            }
+        }
+    }
+}
+
+/// Draws effects one after another around a sprite
+pub fn apply_effects(sprite: DynamicImage, effects: &[Effect]) -> DynamicImage {
+    if effects.is_empty() {
+        return sprite;
+    }
+
+    let rgba = effects
+        .iter()
+        .fold(sprite.into_rgba8(), |image, x| x.apply(&image));
+
+    DynamicImage::ImageRgba8(rgba)
+}
+
+impl FromStr for Filter {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "grayscale" | "greyscale" | "gray" | "grey" => Ok(Filter::Grayscale),
+            "sepia" => Ok(Filter::Sepia),
+            "gameboy" | "gb" => Ok(Filter::Gameboy),
+            "invert" => Ok(Filter::Invert),
+            _ => Err(ParseFilterError(s.to_string())),
+        }
+    }
+}
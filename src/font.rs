@@ -0,0 +1,184 @@
+//! Tiny bitmap font for drawing captions onto images.
+//!
+//! Text in the terminal is printed as is, but exported images
+//! need their captions drawn into the pixels themselves.
+
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+
+/// A monospaced font with every glyph stored as rows of bits
+#[derive(Debug, Copy, Clone)]
+pub struct BitmapFont {
+    /// Width of a glyph in pixels, at most 8
+    pub width: u32,
+    /// Height of a glyph in pixels
+    pub height: u32,
+    /// Looks up the rows of a glyph from top to bottom,
+    /// with the leftmost pixel in the highest of the `width` bits
+    pub glyph: fn(char) -> Option<&'static [u8]>,
+}
+
+/// The built in 5x7 font, covering uppercase letters, digits and the punctuation in names.
+/// Lowercase letters are drawn in uppercase.
+pub const SMALL: BitmapFont = BitmapFont {
+    width: 5,
+    height: 7,
+    glyph: small_glyph,
+};
+
+/// Pixels between a caption and the image above it
+const CAPTION_GAP: u32 = 2;
+
+#[rustfmt::skip]
+fn small_glyph(c: char) -> Option<&'static [u8]> {
+    let c = match c {
+        'à' | 'á' | 'â' | 'ä' => 'A',
+        'è' | 'é' | 'ê' | 'ë' => 'E',
+        'ì' | 'í' | 'î' | 'ï' => 'I',
+        'ò' | 'ó' | 'ô' | 'ö' => 'O',
+        'ù' | 'ú' | 'û' | 'ü' => 'U',
+        c => c.to_ascii_uppercase(),
+    };
+
+    Some(match c {
+        'A' => &[0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => &[0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => &[0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => &[0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110],
+        'E' => &[0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => &[0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => &[0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => &[0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => &[0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => &[0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => &[0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => &[0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => &[0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => &[0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => &[0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => &[0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => &[0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => &[0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => &[0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => &[0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => &[0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => &[0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => &[0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => &[0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => &[0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => &[0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        '0' => &[0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => &[0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => &[0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => &[0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => &[0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => &[0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => &[0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => &[0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => &[0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => &[0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        ' ' => &[0; 7],
+        '.' => &[0, 0, 0, 0, 0, 0b01100, 0b01100],
+        ',' => &[0, 0, 0, 0, 0b01100, 0b00100, 0b01000],
+        '-' => &[0, 0, 0, 0b01110, 0, 0, 0],
+        '\'' => &[0b00100, 0b00100, 0b01000, 0, 0, 0, 0],
+        ':' => &[0, 0b01100, 0b01100, 0, 0b01100, 0b01100, 0],
+        '!' => &[0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0, 0b00100],
+        '?' => &[0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0, 0b00100],
+        '#' => &[0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010],
+        '/' => &[0b00001, 0b00010, 0b00010, 0b00100, 0b01000, 0b01000, 0b10000],
+        '(' => &[0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+        ')' => &[0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+        '♀' => &[0b01110, 0b10001, 0b10001, 0b01110, 0b00100, 0b01110, 0b00100],
+        '♂' => &[0b00111, 0b00011, 0b00101, 0b01110, 0b10010, 0b10010, 0b01100],
+        _ => return None,
+    })
+}
+
+impl BitmapFont {
+    /// Horizontal distance between the start of two glyphs, leaving a pixel between them
+    fn advance(&self) -> u32 {
+        self.width + 1
+    }
+
+    /// Width of `text` in pixels when drawn at `scale`
+    pub fn text_width(&self, text: &str, scale: u32) -> u32 {
+        let glyphs = text.chars().count() as u32;
+        (glyphs * self.advance()).saturating_sub(1) * scale
+    }
+
+    /// Height of a line of text in pixels when drawn at `scale`
+    pub fn text_height(&self, scale: u32) -> u32 {
+        self.height * scale
+    }
+
+    /// Draws `text` with its top left corner at `(x, y)`, every font pixel `scale` pixels big.
+    ///
+    /// Characters missing from the font are drawn as "?", and text beyond the edges is cut off.
+    pub fn draw(
+        &self,
+        image: &mut RgbaImage,
+        x: u32,
+        y: u32,
+        text: &str,
+        color: Rgba<u8>,
+        scale: u32,
+    ) {
+        let fallback = (self.glyph)('?').unwrap_or(&[]);
+
+        for (i, c) in text.chars().enumerate() {
+            let rows = (self.glyph)(c).unwrap_or(fallback);
+            let left = x + i as u32 * self.advance() * scale;
+
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..self.width {
+                    if bits >> (self.width - 1 - column) & 1 == 0 {
+                        continue;
+                    }
+
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            let px = left + column * scale + dx;
+                            let py = y + row as u32 * scale + dy;
+
+                            if px < image.width() && py < image.height() {
+                                image.put_pixel(px, py, color);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Places `text` centered beneath an image, widening it if the text is wider
+    pub fn caption(
+        &self,
+        image: &DynamicImage,
+        text: &str,
+        color: Rgba<u8>,
+        scale: u32,
+    ) -> DynamicImage {
+        let text_width = self.text_width(text, scale);
+        let width = image.width().max(text_width);
+        let height = image.height() + CAPTION_GAP * scale + self.text_height(scale);
+
+        let mut canvas = RgbaImage::new(width, height);
+        imageops::overlay(
+            &mut canvas,
+            &image.to_rgba8(),
+            i64::from((width - image.width()) / 2),
+            0,
+        );
+
+        self.draw(
+            &mut canvas,
+            (width - text_width) / 2,
+            image.height() + CAPTION_GAP * scale,
+            text,
+            color,
+            scale,
+        );
+
+        DynamicImage::ImageRgba8(canvas)
+    }
+}
@@ -0,0 +1,115 @@
+//! An index of the forms each species has sprites for.
+//!
+//! Sprite filenames are the species followed by the form, eg. `regular/raichu-alola.png`,
+//! with female sprites in a `female/` directory. The index splits them back apart
+//! using the pokemon list to tell where the species ends.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::OnceLock,
+};
+
+use crate::{list::List, Data};
+
+/// A sprite of a species
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Variant {
+    /// The form, empty for the default one
+    pub form: String,
+    pub shiny: bool,
+    pub female: bool,
+}
+
+impl Variant {
+    /// Parses a sprite path like `shiny/female/pikachu.png` into its species and variant,
+    /// `None` if the path isn't a sprite of a species in `list`
+    pub fn parse(path: &str, list: &List) -> Option<(String, Self)> {
+        let mut parts: Vec<&str> = path.split('/').collect();
+        let stem = parts.pop()?.strip_suffix(".png")?;
+
+        let shiny = match parts.first() {
+            Some(&"shiny") => true,
+            Some(&"regular") => false,
+            _ => return None,
+        };
+        let female = match parts.get(1..) {
+            Some([]) => false,
+            Some(["female"]) => true,
+            _ => return None,
+        };
+
+        // The longest prefix which is a species, as species can contain dashes like "mr-mime"
+        let split = stem
+            .match_indices('-')
+            .map(|(i, _)| i)
+            .chain([stem.len()])
+            .rev()
+            .find(|&i| list.contains(&stem[..i]))?;
+        let form = stem[split..].trim_start_matches('-').to_string();
+
+        Some((
+            stem[..split].to_string(),
+            Self {
+                form,
+                shiny,
+                female,
+            },
+        ))
+    }
+}
+
+/// The variants of every species, keyed by species filename
+#[derive(Debug, Default, Clone)]
+pub struct FormIndex {
+    species: BTreeMap<String, BTreeSet<Variant>>,
+}
+
+impl FormIndex {
+    /// Builds an index of the sprites at `paths`, skipping any which aren't of a species in `list`
+    pub fn from_paths<'a>(paths: impl IntoIterator<Item = &'a str>, list: &List) -> Self {
+        let mut species: BTreeMap<String, BTreeSet<Variant>> = BTreeMap::new();
+
+        for (name, variant) in paths.into_iter().filter_map(|x| Variant::parse(x, list)) {
+            species.entry(name).or_default().insert(variant);
+        }
+
+        Self { species }
+    }
+
+    /// The index of the embedded sprites, built on first use
+    pub fn embedded() -> &'static Self {
+        static INDEX: OnceLock<FormIndex> = OnceLock::new();
+
+        INDEX.get_or_init(|| {
+            let paths: Vec<_> = Data::iter().collect();
+            Self::from_paths(paths.iter().map(AsRef::as_ref), List::global())
+        })
+    }
+
+    /// Every variant of a species, sorted by form
+    pub fn variants(&self, species: &str) -> impl Iterator<Item = &Variant> {
+        self.species.get(species).into_iter().flatten()
+    }
+
+    /// The forms a species has sprites for, without the default one
+    pub fn forms(&self, species: &str) -> Vec<&str> {
+        let mut forms: Vec<&str> = self
+            .variants(species)
+            .map(|x| x.form.as_str())
+            .filter(|x| !x.is_empty())
+            .collect();
+        forms.dedup();
+
+        forms
+    }
+
+    /// Whether a species has a sprite of `form`, the empty form being the default one
+    pub fn has_form(&self, species: &str, form: &str) -> bool {
+        self.variants(species).any(|x| x.form == form)
+    }
+
+    /// Species in the index, sorted by filename
+    pub fn species(&self) -> impl Iterator<Item = &str> {
+        self.species.keys().map(String::as_str)
+    }
+}
@@ -0,0 +1,38 @@
+//! Fortunes for pokemon to say with `--fortune`.
+//!
+//! The built in fortunes use the format of the `fortune` program,
+//! entries separated by lines holding a single `%`.
+
+use rand::seq::SliceRandom;
+
+/// The built in fortunes
+const FORTUNES: &str = include_str!("../data/fortunes.txt");
+
+/// Splits a file in `fortune` format into its entries, skipping empty ones
+pub fn parse(contents: &str) -> Vec<String> {
+    let mut fortunes = Vec::new();
+    let mut current = Vec::new();
+
+    for line in contents.lines().chain(["%"]) {
+        if line.trim_end() == "%" {
+            let fortune = current.join("\n").trim().to_string();
+            if !fortune.is_empty() {
+                fortunes.push(fortune);
+            }
+
+            current.clear();
+        } else {
+            current.push(line);
+        }
+    }
+
+    fortunes
+}
+
+/// Picks a random built in fortune
+pub fn random() -> String {
+    parse(FORTUNES)
+        .choose(&mut rand::thread_rng())
+        .cloned()
+        .unwrap_or_default()
+}
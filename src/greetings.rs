@@ -0,0 +1,89 @@
+//! Templated greetings which can replace the plain line of names.
+
+use std::str::FromStr;
+
+use rand::Rng;
+
+/// Built in greetings for one and for several pokemon,
+/// `{name}` is replaced with the displayed pokemon
+pub const GREETINGS: &[(&str, &str)] = &[
+    ("A wild {name} appeared!", "Wild {name} appeared!"),
+    ("{name} wants to battle!", "{name} want to battle!"),
+    ("Go! {name}!", "Go! {name}!"),
+    (
+        "{name} is looking at you curiously.",
+        "{name} are looking at you curiously.",
+    ),
+    (
+        "You feel {name} is happy to see you.",
+        "You feel {name} are happy to see you.",
+    ),
+    (
+        "Welcome back! {name} was waiting for you.",
+        "Welcome back! {name} were waiting for you.",
+    ),
+    (
+        "What? {name} is evolving! ...Just kidding.",
+        "What? {name} are evolving! ...Just kidding.",
+    ),
+    (
+        "{name} used Hello! It's super effective!",
+        "{name} used Hello! It's super effective!",
+    ),
+];
+
+/// Error returned when a greeting selection can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown greeting: {0}, expected random, off or an index below {len}", len = GREETINGS.len())]
+pub struct ParseGreetingError(String);
+
+/// Which greeting to show above the sprites
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Greeting {
+    /// Just the names of the pokemon
+    #[default]
+    Off,
+    /// A different greeting every time
+    Random,
+    /// A specific greeting from [`GREETINGS`]
+    Index(usize),
+}
+
+impl FromStr for Greeting {
+    type Err = ParseGreetingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Ok(Greeting::Off),
+            "random" => Ok(Greeting::Random),
+            index => match index.parse() {
+                Ok(i) if i < GREETINGS.len() => Ok(Greeting::Index(i)),
+                _ => Err(ParseGreetingError(s.to_string())),
+            },
+        }
+    }
+}
+
+/// Joins names into a list like "Pikachu, Eevee and Mew"
+fn join_names(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => name.to_string(),
+        [init @ .., last] => format!("{} and {last}", init.join(", ")),
+    }
+}
+
+impl Greeting {
+    /// Builds the line shown above the sprites
+    pub fn format(self, names: &[&str]) -> String {
+        let (one, several) = match self {
+            Greeting::Off => return names.join(", "),
+            Greeting::Random => GREETINGS[rand::thread_rng().gen_range(0..GREETINGS.len())],
+            Greeting::Index(i) => GREETINGS[i],
+        };
+
+        let template = if names.len() > 1 { several } else { one };
+
+        template.replace("{name}", &join_names(names))
+    }
+}
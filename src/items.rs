@@ -0,0 +1,143 @@
+//! Item sprites, like poké balls and held items, drawn by `pokeget item`.
+//!
+//! Items are laid out like in pokesprite's `items` directory, eg. `items/ball/master.png`
+//! for the master ball. They're embedded with the `items` feature,
+//! otherwise they're only found in sprite directories laid out the same way.
+
+use image::DynamicImage;
+use rand::Rng;
+
+use crate::{list::normalize_filename, names::title_case, source::SpriteSource};
+
+/// Directories of the items in pokesprite, searched in order for items without a known suffix
+const CATEGORIES: &[&str] = &[
+    "ball",
+    "hold-item",
+    "berry",
+    "medicine",
+    "evo-item",
+    "battle-item",
+    "mega-stone",
+    "z-crystals",
+    "valuable-item",
+    "key-item",
+    "fossil",
+    "mail",
+    "plate",
+    "memory",
+    "mint",
+    "exp-candy",
+    "ev-item",
+    "flute",
+    "other-item",
+];
+
+/// Suffixes of item names which are left out of the filename, and the directory they're in
+const SUFFIXES: &[(&str, &str)] = &[("-ball", "ball"), ("-berry", "berry")];
+
+/// Well known items, which random items are picked from
+pub const ITEMS: &[&str] = &[
+    "poke-ball",
+    "great-ball",
+    "ultra-ball",
+    "master-ball",
+    "premier-ball",
+    "luxury-ball",
+    "dusk-ball",
+    "quick-ball",
+    "leftovers",
+    "life-orb",
+    "choice-band",
+    "choice-scarf",
+    "focus-sash",
+    "lucky-egg",
+    "exp-share",
+    "rare-candy",
+    "oran-berry",
+    "sitrus-berry",
+    "potion",
+    "revive",
+    "fire-stone",
+    "water-stone",
+    "thunder-stone",
+    "moon-stone",
+];
+
+/// Error types for items
+#[derive(Debug, thiserror::Error)]
+pub enum ItemError {
+    #[error("Item '{0}' not found, item sprites are embedded with the `items` feature or read from $POKEGET_SPRITE_DIR")]
+    NotFound(String),
+
+    #[error("Failed to load item sprite: {0}")]
+    Image(#[from] image::ImageError),
+}
+
+/// Which item to draw
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemSelection {
+    Random,
+    Name(String),
+}
+
+impl ItemSelection {
+    /// Parses an item as given on the command line, "random" picking one of [`ITEMS`]
+    pub fn parse(arg: &str) -> Self {
+        match arg.trim().to_lowercase().as_str() {
+            "random" => ItemSelection::Random,
+            _ => ItemSelection::Name(normalize_filename(arg)),
+        }
+    }
+
+    /// Evaluates the selection to an item name like "master-ball"
+    pub fn eval(self) -> String {
+        match self {
+            ItemSelection::Random => {
+                ITEMS[rand::thread_rng().gen_range(0..ITEMS.len())].to_string()
+            }
+            ItemSelection::Name(name) => name,
+        }
+    }
+}
+
+/// Paths the sprite of an item could be at, in the order they're tried
+pub fn paths(name: &str) -> Vec<String> {
+    let known = SUFFIXES.iter().find_map(|(suffix, category)| {
+        let stem = name.strip_suffix(suffix)?;
+        Some(format!("items/{category}/{stem}.png"))
+    });
+
+    known
+        .into_iter()
+        .chain(
+            CATEGORIES
+                .iter()
+                .map(|category| format!("items/{category}/{name}.png")),
+        )
+        .collect()
+}
+
+/// An item and its sprite
+#[derive(Clone)]
+pub struct Item {
+    /// Display name, eg. "Master Ball"
+    pub name: String,
+    pub sprite: DynamicImage,
+}
+
+impl Item {
+    /// Loads the trimmed sprite of the item selected by `arg` from `source`
+    pub fn new(arg: &str, source: &dyn SpriteSource) -> Result<Self, ItemError> {
+        let name = ItemSelection::parse(arg).eval();
+        let bytes = paths(&name)
+            .iter()
+            .find_map(|x| source.get(x))
+            .ok_or_else(|| ItemError::NotFound(name.clone()))?;
+        let sprite = showie::trim(&image::load_from_memory(&bytes)?);
+
+        Ok(Self {
+            name: title_case(&name),
+            sprite,
+        })
+    }
+}
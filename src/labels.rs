@@ -0,0 +1,97 @@
+//! Text labels placed beneath composed sprites.
+
+use crate::{
+    color,
+    sprites::{visible_width, SpriteRow},
+    terminal::ColorDepth,
+    types::Type,
+};
+
+/// A piece of text which may contain escape codes
+pub struct Label {
+    /// The text, including any escape codes
+    pub text: String,
+    /// Amount of columns the text takes up when printed
+    pub width: u32,
+}
+
+impl Label {
+    /// Creates a label from plain text without escape codes
+    pub fn plain(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            width: text.chars().count() as u32,
+        }
+    }
+
+    /// Creates a label from text which may contain escape codes, measuring only what's printed
+    pub fn styled(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            width: visible_width(text) as u32,
+        }
+    }
+}
+
+/// Builds a line with each label centered beneath its sprite in the row.
+///
+/// `labels` is indexed the same way as the pokemon the row was composed from.
+/// Labels wider than their sprite are pushed right so they never overlap.
+pub fn label_line(row: &SpriteRow, labels: &[Label]) -> String {
+    let mut line = String::new();
+    let mut cursor = 0;
+
+    for position in &row.positions {
+        let Some(label) = labels.get(position.index) else {
+            continue;
+        };
+
+        let centered = position.x + position.width.saturating_sub(label.width) / 2;
+        let start = if cursor == 0 {
+            centered
+        } else {
+            centered.max(cursor + 1)
+        };
+
+        line.push_str(&" ".repeat((start - cursor) as usize));
+        line.push_str(&label.text);
+        cursor = start + label.width;
+    }
+
+    line
+}
+
+/// Builds colored badges for a pokemon's types, eg. ` FIRE  FLYING `
+pub fn type_badges(types: &[Type], depth: ColorDepth) -> Label {
+    let mut text = String::new();
+    let mut width = 0;
+
+    for (i, kind) in types.iter().enumerate() {
+        if i > 0 {
+            text.push(' ');
+            width += 1;
+        }
+
+        let name = kind.name().to_uppercase();
+        let [r, g, b] = kind.color();
+        let luma = (u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114) / 1000;
+        let foreground = if luma > 140 {
+            [0, 0, 0]
+        } else {
+            [255, 255, 255]
+        };
+
+        if depth == ColorDepth::Monochrome {
+            text.push_str(&name);
+            width += name.len() as u32;
+        } else {
+            text.push_str(&color::bg(kind.color(), depth));
+            text.push_str(&color::fg(foreground, depth));
+            text.push_str(&format!(" {name} "));
+            text.push_str(color::RESET);
+            width += name.len() as u32 + 2;
+        }
+    }
+
+    Label { text, width }
+}
@@ -1,10 +1,275 @@
+use std::{borrow::Cow, fmt};
+
+use image::{
+    imageops::{self, FilterType},
+    DynamicImage, RgbaImage,
+};
+
+use list::{List, ListError};
+use pokemon::{Attributes, Pokemon, PokemonError};
+use render::Backend;
+#[cfg(feature = "items")]
 use rust_embed::RustEmbed;
+use sprites::{
+    downscale_to_fit, fill_background, write_sprites, LayoutOptions, ScaleFilter, SpriteError,
+};
+use terminal::{HostTerminal, Terminal, VirtualTerminal};
 
+pub mod achievements;
+pub mod archive;
+pub mod backdrop;
+pub mod border;
+pub mod bubble;
+pub mod cache;
 pub mod cli;
+pub mod clipboard;
+pub mod color;
+pub mod demo;
+pub mod dex;
+pub mod filters;
+pub mod font;
+pub mod forms;
+pub mod fortune;
+pub mod greetings;
+pub mod items;
+mod json;
+pub mod labels;
+pub mod limits;
 pub mod list;
+pub mod names;
+pub mod pacing;
+pub mod pack;
+pub mod pager;
+pub mod palette;
+pub mod paths;
+pub mod patterns;
+pub mod picker;
 pub mod pokemon;
+pub mod presets;
+pub mod prompt;
+pub mod quiz;
+pub mod render;
+pub mod scene;
+pub mod source;
+pub mod specs;
 pub mod sprites;
+pub mod stamp;
+pub mod state;
+pub mod stats;
+pub mod sysinfo;
+pub mod terminal;
+pub mod theme;
+#[cfg(feature = "threaded")]
+pub mod threaded;
+pub mod tmux;
+pub mod transform;
+pub mod transition;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod types;
+pub mod usage;
+pub mod verify;
+pub mod watch;
 
-#[derive(RustEmbed)]
-#[folder = "data/pokesprite/pokemon-gen8"]
+/// The embedded sprites, by paths like `regular/pikachu.png`.
+///
+/// Shiny sprites make up half of the binary, so they're only embedded with the `shiny` feature.
+/// Female sprites like `regular/female/pikachu.png` are only embedded with the `female` feature,
+/// and sprites of forms like `regular/raichu-alola.png` with the `forms` feature.
+/// Item sprites like `items/ball/master.png` are only embedded with the `items` feature,
+/// The `mini` feature serves menu icons like `mini/regular/pikachu.png`, which are the sprites
+/// themselves since pokesprite's gen 8 sprites are the games' menu icons.
 pub struct Data;
+
+include!(concat!(env!("OUT_DIR"), "/sprites.rs"));
+
+#[cfg(feature = "items")]
+#[derive(RustEmbed)]
+#[folder = "data/pokesprite/items"]
+#[prefix = "items/"]
+struct ItemData;
+
+impl Data {
+    /// Gets an embedded sprite by its path
+    pub fn get(path: &str) -> Option<Cow<'static, [u8]>> {
+        #[cfg(feature = "items")]
+        if let Some(file) = ItemData::get(path) {
+            return Some(file.data);
+        }
+
+        #[cfg(feature = "mini")]
+        if let Some(path) = path.strip_prefix("mini/") {
+            return Self::get(path);
+        }
+
+        sprite_sets()
+            .into_iter()
+            .find_map(|x| x.get(path))
+            .map(Cow::Owned)
+    }
+
+    /// Iterates over the paths of every embedded sprite
+    pub fn iter() -> impl Iterator<Item = Cow<'static, str>> {
+        let sprites = sprite_sets()
+            .into_iter()
+            .flat_map(archive::Archive::paths)
+            .map(Cow::Borrowed);
+
+        #[cfg(feature = "items")]
+        let items = ItemData::iter();
+        #[cfg(not(feature = "items"))]
+        let items = std::iter::empty();
+
+        sprites.chain(items)
+    }
+}
+
+/// Error types for [`render`]
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    #[error("Error reading pokemon list: {0}")]
+    List(#[from] ListError),
+
+    #[error("Error creating pokemon: {0}")]
+    Pokemon(#[from] PokemonError),
+
+    #[error("Error combining sprites: {0}")]
+    Sprite(#[from] SpriteError),
+}
+
+/// Options for [`render`]
+pub struct Options {
+    /// Form, gender and shininess of the pokemon
+    pub attributes: Attributes,
+    /// Width to render for, the host terminal's width is used if unset
+    pub width: Option<u32>,
+    /// Whether to shrink sprites wider than `width`
+    pub downscale: bool,
+    /// Spacing and alignment of the sprites
+    pub layout: LayoutOptions,
+    /// How the sprites are drawn, [`Backend::Auto`] draws half blocks
+    pub renderer: Backend,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            attributes: Attributes::default(),
+            width: None,
+            downscale: true,
+            layout: LayoutOptions::default(),
+            renderer: Backend::default(),
+        }
+    }
+}
+
+/// Renders a pokemon to a string of ANSI colored text.
+///
+/// `pokemon` is anything accepted on the command line:
+/// a name, a dex ID, a region or "random".
+///
+/// # Errors
+///
+/// Returns [`RenderError`] if the pokemon can't be found or its sprite can't be drawn
+pub fn render(pokemon: &str, options: Options) -> Result<String, RenderError> {
+    let mut out = String::new();
+    render_into(&mut out, pokemon, options)?;
+
+    Ok(out)
+}
+
+/// Like [`render`], but streams the escape codes into `out`
+/// without building intermediate strings.
+///
+/// # Errors
+///
+/// Returns [`RenderError`] if the pokemon can't be found, its sprite can't be drawn
+/// or writing to `out` fails
+pub fn render_into(
+    out: &mut impl fmt::Write,
+    pokemon: &str,
+    options: Options,
+) -> Result<(), RenderError> {
+    let list = List::global();
+    let mut pokemons = vec![Pokemon::new(pokemon.to_string(), list, options.attributes)?];
+
+    let terminal: Box<dyn Terminal> = match options.width {
+        Some(width) => Box::new(VirtualTerminal::new(width)),
+        None => Box::new(HostTerminal),
+    };
+    let renderer = options.renderer.renderer(terminal.color_depth());
+    let canvas = renderer.canvas(terminal.as_ref());
+
+    if options.downscale {
+        downscale_to_fit(&mut pokemons, &canvas);
+    }
+
+    write_sprites(out, &pokemons, &canvas, options.layout, renderer.as_ref())?;
+    Ok(())
+}
+
+/// Options for [`thumbnail_with`]
+#[derive(Default)]
+pub struct ThumbnailOptions {
+    /// Form, gender and shininess of the pokemon
+    pub attributes: Attributes,
+    /// Color filling the thumbnail behind the sprite, transparent if unset
+    pub background: Option<[u8; 3]>,
+    /// How sprites larger than the thumbnail are shrunk
+    pub filter: ScaleFilter,
+}
+
+/// Draws a pokemon as a square image of `size` by `size` pixels, eg. for avatars or tray icons.
+///
+/// See [`thumbnail_with`] for details.
+///
+/// # Errors
+///
+/// Returns [`RenderError`] if the pokemon can't be found
+pub fn thumbnail(pokemon: &str, size: u32) -> Result<DynamicImage, RenderError> {
+    thumbnail_with(pokemon, size, ThumbnailOptions::default())
+}
+
+/// Draws a pokemon as a square image of `size` by `size` pixels, without a terminal involved.
+///
+/// The trimmed sprite is centered in the image, enlarged by whole multiples to keep
+/// the pixels crisp or shrunk with the filter in `options` if it doesn't fit.
+///
+/// # Errors
+///
+/// Returns [`RenderError`] if the pokemon can't be found
+pub fn thumbnail_with(
+    pokemon: &str,
+    size: u32,
+    options: ThumbnailOptions,
+) -> Result<DynamicImage, RenderError> {
+    let size = size.max(1);
+    let list = List::global();
+    let sprite = Pokemon::new(pokemon.to_string(), list, options.attributes)?.sprite;
+
+    let longest = sprite.width().max(sprite.height());
+    let sprite = match size / longest {
+        0 => sprite.resize(size, size, options.filter.filter_type()),
+        1 => sprite,
+        scale => sprite.resize(
+            sprite.width() * scale,
+            sprite.height() * scale,
+            FilterType::Nearest,
+        ),
+    };
+
+    let mut image = RgbaImage::new(size, size);
+    imageops::overlay(
+        &mut image,
+        &sprite.to_rgba8(),
+        i64::from((size - sprite.width()) / 2),
+        i64::from((size - sprite.height()) / 2),
+    );
+
+    let mut image = DynamicImage::ImageRgba8(image);
+    if let Some(rgb) = options.background {
+        fill_background(&mut image, rgb);
+    }
+
+    Ok(image)
+}
@@ -0,0 +1,218 @@
+//! Resource caps for output that keeps running, like animations.
+//!
+//! A decorative toy shouldn't be able to slow down a shared server, so long running output
+//! sleeps between frames to stay under a frame rate and a share of the CPU,
+//! keeps its buffers under a memory budget, and slows down while the terminal isn't focused.
+//!
+//! Limits are read from `limits.conf` in the config directory, with flags taking precedence.
+
+use std::{fs, str::FromStr, time::Duration};
+
+use crate::{
+    pacing::{FramePacer, Pace},
+    paths,
+};
+
+/// Name of the limits file in the config directory
+pub const LIMITS_FILE: &str = "limits.conf";
+
+/// Escape code asking the terminal to report focus changes
+pub const FOCUS_REPORTING_ON: &str = "\x1b[?1004h";
+
+/// Escape code turning focus reports off again
+pub const FOCUS_REPORTING_OFF: &str = "\x1b[?1004l";
+
+/// Frame rate while the terminal isn't focused, unless configured otherwise
+const DEFAULT_IDLE_FPS: u32 = 1;
+
+/// Error returned when a size can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid size: {0}, expected a number of bytes like 512K, 64M or 1G")]
+pub struct ParseSizeError(String);
+
+/// An amount of memory in bytes, parsed from sizes like `64M`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Size(pub u64);
+
+impl FromStr for Size {
+    type Err = ParseSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim().to_uppercase();
+        let number = trimmed.trim_end_matches(['B', 'I']);
+
+        let (digits, unit) = match number.char_indices().last() {
+            Some((i, 'K')) => (&number[..i], 1 << 10),
+            Some((i, 'M')) => (&number[..i], 1 << 20),
+            Some((i, 'G')) => (&number[..i], 1 << 30),
+            _ => (number, 1),
+        };
+
+        digits
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .and_then(|x| x.checked_mul(unit))
+            .map(Size)
+            .ok_or_else(|| ParseSizeError(s.to_string()))
+    }
+}
+
+/// Caps on the resources used by long running output, `None` meaning no cap
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Limits {
+    /// Highest frame rate
+    pub fps: Option<u32>,
+    /// Highest share of a single CPU core spent drawing, in percent
+    pub cpu: Option<u8>,
+    /// Most memory kept in frame buffers and caches
+    pub memory: Option<Size>,
+    /// Frame rate while the terminal isn't focused, 0 pausing entirely
+    pub idle_fps: Option<u32>,
+}
+
+impl Limits {
+    /// Parses limits from `key = value` lines, with the keys
+    /// `fps`, `cpu`, `memory` and `idle-fps`.
+    ///
+    /// Lines starting with `#` and lines which can't be parsed are skipped,
+    /// since a typo shouldn't keep sprites from being shown.
+    pub fn parse(contents: &str) -> Self {
+        let mut limits = Self::default();
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_lowercase().replace('_', "-").as_str() {
+                "fps" => limits.fps = value.parse().ok().or(limits.fps),
+                "cpu" => {
+                    let percent = value.trim_end_matches('%').trim().parse().ok();
+                    limits.cpu = percent.filter(|x| (1..=100).contains(x)).or(limits.cpu);
+                }
+                "memory" => limits.memory = value.parse().ok().or(limits.memory),
+                "idle-fps" => limits.idle_fps = value.parse().ok().or(limits.idle_fps),
+                _ => {}
+            }
+        }
+
+        limits
+    }
+
+    /// Reads the user's limits file, without limits if it doesn't exist
+    pub fn read() -> Self {
+        paths::config_dir()
+            .ok()
+            .and_then(|dir| fs::read_to_string(dir.join(LIMITS_FILE)).ok())
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Fills the limits which aren't set from `fallback`
+    pub fn or(self, fallback: Limits) -> Self {
+        Self {
+            fps: self.fps.or(fallback.fps),
+            cpu: self.cpu.or(fallback.cpu),
+            memory: self.memory.or(fallback.memory),
+            idle_fps: self.idle_fps.or(fallback.idle_fps),
+        }
+    }
+
+    /// Whether `bytes` fit in the memory budget
+    pub fn allows(&self, bytes: u64) -> bool {
+        self.memory.is_none_or(|Size(max)| bytes <= max)
+    }
+
+    /// Amount of items of `item_bytes` each fitting in the memory budget, at least one
+    pub fn capacity(&self, item_bytes: u64) -> Option<usize> {
+        let Size(max) = self.memory?;
+        Some((max / item_bytes.max(1)).max(1) as usize)
+    }
+
+    /// Creates a throttle enforcing the limits on output aiming for `fps` frames per second
+    pub fn throttle(self, fps: u32) -> Throttle {
+        let fps = self.fps.map_or(fps, |max| fps.min(max));
+
+        Throttle {
+            limits: self,
+            pacer: FramePacer::for_host(fps),
+            focused: true,
+        }
+    }
+}
+
+/// Whether the terminal is focused, as reported after [`FOCUS_REPORTING_ON`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Focus {
+    Gained,
+    Lost,
+}
+
+impl Focus {
+    /// Finds the last focus report in input read from the terminal
+    pub fn last_in(input: &[u8]) -> Option<Self> {
+        input.windows(3).rev().find_map(|x| match x {
+            b"\x1b[I" => Some(Focus::Gained),
+            b"\x1b[O" => Some(Focus::Lost),
+            _ => None,
+        })
+    }
+}
+
+/// Decides how long to sleep between frames so [`Limits`] are kept
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    limits: Limits,
+    pacer: FramePacer,
+    focused: bool,
+}
+
+impl Throttle {
+    /// The limits being enforced
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Records a focus report from the terminal.
+    ///
+    /// Terminals which don't report focus are always treated as focused.
+    pub fn set_focus(&mut self, focus: Focus) {
+        self.focused = focus == Focus::Gained;
+    }
+
+    /// Records that drawing a frame took `busy` and returns what to do next
+    pub fn frame_drawn(&mut self, busy: Duration) -> Pace {
+        let Pace::Wait(mut wait) = self.pacer.frame_written(busy) else {
+            return Pace::Static;
+        };
+
+        // Sleeping `busy * (100 - cpu) / cpu` keeps the time spent drawing at `cpu` percent
+        if let Some(cpu) = self.limits.cpu {
+            let cpu = u32::from(cpu.clamp(1, 100));
+            wait = wait.max(busy * (100 - cpu) / cpu);
+        }
+
+        if !self.focused {
+            let idle = match self.limits.idle_fps.unwrap_or(DEFAULT_IDLE_FPS) {
+                // Paused until focus comes back, checked every second
+                0 => Duration::from_secs(1),
+                fps => Duration::from_secs(1) / fps,
+            };
+            wait = wait.max(idle);
+        }
+
+        Pace::Wait(wait)
+    }
+
+    /// Whether frames should be drawn at all, false while unfocused with an idle rate of 0
+    pub fn is_paused(&self) -> bool {
+        !self.focused && self.limits.idle_fps == Some(0)
+    }
+}
@@ -1,10 +1,10 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 
-use std::io::Cursor;
+use std::{collections::HashSet, ops::RangeInclusive, sync::OnceLock};
 
+use crate::names::{display_name, replace_gender_signs};
 use crate::pokemon::Region;
-use bimap::BiHashMap;
-use inflector::Inflector;
+use crate::types::{ParseTypeError, Type};
 use rand::Rng;
 use sanitize_filename::sanitize_with_options;
 use sanitize_filename::Options as SanitizeOptions;
@@ -12,68 +12,94 @@ use sanitize_filename::Options as SanitizeOptions;
 /// Error types for list operations.
 #[derive(Debug, thiserror::Error)]
 pub enum ListError {
-    /// Failed to parse CSV record.
-    #[error("Failed to parse CSV record: {0}")]
-    CsvParseError(#[from] csv::Error),
-
     /// Invalid Pokemon ID provided.
     #[error("Pokemon ID {0} is not valid (must be between 1 and {1})")]
     InvalidPokemonId(usize, usize),
 
+    /// A range of Pokemon IDs ends before it starts.
+    #[error("Range {0}-{1} is reversed, the first Pokemon ID must not be greater than the last")]
+    ReversedRange(usize, usize),
+
+    /// A range of Pokemon IDs reaches past the list.
+    #[error("Range {0}-{1} is not valid (Pokemon IDs are between 1 and {2})")]
+    RangeOutOfBounds(usize, usize, usize),
+
     /// No Pokémon found in region
     #[error("No Pokémon found in region: {0:?}")]
     EmptyRegion(Region),
+
+    /// Invalid type in the types dataset.
+    #[error("Failed to parse types: {0}")]
+    TypeParseError(#[from] ParseTypeError),
+
+    /// No Pokémon matches the requested types
+    #[error("No Pokémon found with type(s): {0}")]
+    NoMatchingType(String),
+
+    /// Every Pokémon to pick from was already picked
+    #[error("Not enough different Pokémon to pick from")]
+    Exhausted,
 }
 
-/// A parsed representation of `names.csv`.
-pub struct List {
-    /// Pokedex IDs and corresponding filenames
-    ids: BiHashMap<usize, String>,
+// Generated by `build.rs` from `names.csv` and `types.csv`
+include!(concat!(env!("OUT_DIR"), "/list.rs"));
 
-    /// Formatted names in order of Pokedex ID
-    names: Vec<String>,
+/// The pokemon in `names.csv`, backed by a table generated at compile time.
+///
+/// [`List::global`] shares a single list instead of building a new one each time.
+#[derive(Debug, Clone)]
+pub struct List {
+    /// Filenames in order of Pokedex ID
+    filenames: Vec<String>,
 }
 
 impl List {
-    /// Reads a new [`List`] from embedded CSV data
+    /// Creates a new [`List`] from the embedded table
     ///
     /// # Errors
     ///
-    /// Returns `ListError` if it fails to parse the CSV file
+    /// Never fails since the table is checked at compile time,
+    /// the `Result` is kept for compatibility
     pub fn read() -> Result<Self, ListError> {
-        const FILE: &str = include_str!("../data/names.csv");
-        const CAPACITY: usize = 1000;
+        Ok(Self::build())
+    }
 
-        let mut reader = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .from_reader(Cursor::new(FILE));
+    /// The list shared by the whole program, created on first use
+    #[must_use]
+    pub fn global() -> &'static Self {
+        static LIST: OnceLock<List> = OnceLock::new();
 
-        let mut ids = BiHashMap::with_capacity(CAPACITY);
-        let mut names = Vec::with_capacity(CAPACITY);
+        LIST.get_or_init(Self::build)
+    }
 
-        for (i, entry) in reader.deserialize().enumerate() {
-            let record: (String, String) = entry?;
-            let id = i + 1;
-            ids.insert(id, record.1);
-            names.push(record.0);
-        }
+    fn build() -> Self {
+        let filenames = POKEMON
+            .iter()
+            .map(|(_, filename, _)| (*filename).to_string())
+            .collect();
+
+        Self { filenames }
+    }
+
+    /// Gets the Dex ID of a filename with a binary search through the sorted table
+    fn lookup(filename: &str) -> Option<usize> {
+        BY_FILENAME
+            .binary_search_by_key(&filename, |(x, _)| x)
+            .ok()
+            .map(|i| BY_FILENAME[i].1)
+    }
 
-        Ok(Self { ids, names })
+    /// Gets the entry of a pokemon by Dex ID
+    fn entry(id: usize) -> Option<&'static (&'static str, &'static str, &'static [Type])> {
+        POKEMON.get(id.checked_sub(1)?)
     }
 
     /// Formats a filename into a display name
     #[must_use]
     pub fn format_name(&self, filename: &str) -> String {
-        let raw_fmt = |x: &str| x.replace('-', " ").replace('\'', "").to_title_case();
-
-        let Some(id) = self.ids.get_by_right(filename) else {
-            return raw_fmt(filename);
-        };
-
-        self.names
-            .get(*id - 1)
-            .cloned()
-            .unwrap_or_else(|| raw_fmt(filename))
+        display_name(&normalize_filename(filename), |name| {
+            Self::entry(Self::lookup(name)?).map(|(name, _, _)| *name)
+        })
     }
 
     /// Gets a pokemon filename by Dex ID
@@ -82,9 +108,27 @@ impl List {
     ///
     /// Returns `ListError::InvalidPokemon` if it fails to find the pokemon by id
     pub fn get_by_id(&self, id: usize) -> Result<&String, ListError> {
-        self.ids
-            .get_by_left(&id)
-            .ok_or_else(|| ListError::InvalidPokemonId(id, self.ids.len()))
+        id.checked_sub(1)
+            .and_then(|i| self.filenames.get(i))
+            .ok_or(ListError::InvalidPokemonId(id, POKEMON.len()))
+    }
+
+    /// Gets the pokemon filenames of a range of Dex IDs
+    ///
+    /// # Errors
+    ///
+    /// Returns `ListError::ReversedRange` if the range ends before it starts
+    /// Returns `ListError::RangeOutOfBounds` if it reaches past the first or last Pokemon ID
+    pub fn get_range(&self, range: &RangeInclusive<usize>) -> Result<&[String], ListError> {
+        let (start, end) = (*range.start(), *range.end());
+        if start > end {
+            return Err(ListError::ReversedRange(start, end));
+        }
+
+        start
+            .checked_sub(1)
+            .and_then(|i| self.filenames.get(i..end))
+            .ok_or(ListError::RangeOutOfBounds(start, end, POKEMON.len()))
     }
 
     /// Gets a random pokemon by region
@@ -102,10 +146,7 @@ impl List {
         let mut rng = rand::thread_rng();
         let idx = rng.gen_range(range);
 
-        self.ids
-            .get_by_left(&idx)
-            .ok_or_else(|| ListError::InvalidPokemonId(idx, self.ids.len()))
-            .cloned()
+        self.get_by_id(idx).cloned()
     }
 
     /// Gets a random pokemon filename
@@ -115,13 +156,154 @@ impl List {
     /// Returns `ListError::InvalidPokemonId` if the Pokemon ID does not exist
     pub fn random(&self) -> Result<String, ListError> {
         let mut rng = rand::thread_rng();
-        let idx = rng.gen_range(1..=self.ids.len());
+        let idx = rng.gen_range(1..=POKEMON.len());
+
+        self.get_by_id(idx).cloned()
+    }
+
+    /// Gets the Dex ID of a pokemon by filename
+    #[must_use]
+    pub fn id_of(&self, filename: &str) -> Option<usize> {
+        Self::lookup(filename)
+    }
+
+    /// Gets the Dex ID of a pokemon by name, either a display name like "Mr. Mime" or a filename
+    #[must_use]
+    pub fn get_id_by_name(&self, name: &str) -> Option<usize> {
+        Self::lookup(&normalize_filename(name))
+    }
+
+    /// Whether a filename belongs to a pokemon in the list
+    #[must_use]
+    pub fn contains(&self, filename: &str) -> bool {
+        Self::lookup(filename).is_some()
+    }
+
+    /// Filenames of every pokemon in order of Pokedex ID
+    pub fn filenames(&self) -> impl Iterator<Item = &str> {
+        self.filenames.iter().map(String::as_str)
+    }
+
+    /// Gets the types of a pokemon by filename
+    #[must_use]
+    pub fn types_of(&self, filename: &str) -> Option<&[Type]> {
+        Self::entry(Self::lookup(filename)?).map(|(_, _, types)| *types)
+    }
 
-        self.ids
-            .get_by_left(&idx)
-            .ok_or_else(|| ListError::InvalidPokemonId(idx, self.ids.len()))
-            .cloned()
+    /// Gets a random pokemon from the union of several regions,
+    /// or from every region if `regions` is empty
+    ///
+    /// # Errors
+    ///
+    /// Returns `ListError::InvalidPokemonId` if the Pokemon ID does not exist
+    pub fn get_by_regions(&self, regions: &[Region]) -> Result<String, ListError> {
+        let ranges = Self::unique_ranges(regions);
+        if ranges.is_empty() {
+            return self.random();
+        }
+
+        let total: usize = ranges.iter().map(|x| x.end() - x.start() + 1).sum();
+        let mut idx = rand::thread_rng().gen_range(0..total);
+
+        for range in ranges {
+            let len = range.end() - range.start() + 1;
+            if idx < len {
+                let id = range.start() + idx;
+
+                return self.get_by_id(id).cloned();
+            }
+
+            idx -= len;
+        }
+
+        unreachable!("index is always within the total length of the ranges")
     }
+
+    /// Dex ranges of the given regions, skipping duplicates and empty regions
+    fn unique_ranges(regions: &[Region]) -> Vec<std::ops::RangeInclusive<usize>> {
+        let mut ranges = Vec::with_capacity(regions.len());
+
+        for range in regions.iter().map(Region::range) {
+            if !range.is_empty() && !ranges.contains(&range) {
+                ranges.push(range);
+            }
+        }
+
+        ranges
+    }
+
+    /// Gets a random pokemon which has all of the given types,
+    /// restricted to the union of `regions` unless it's empty
+    ///
+    /// # Errors
+    ///
+    /// Returns `ListError::NoMatchingType` if no pokemon has the given types
+    pub fn random_by_types(&self, regions: &[Region], types: &[Type]) -> Result<String, ListError> {
+        let candidates = Self::candidates(regions, types)?;
+        let idx = candidates[rand::thread_rng().gen_range(0..candidates.len())];
+
+        self.get_by_id(idx).cloned()
+    }
+
+    /// Gets a random pokemon like [`List::random_by_types`], never picking a filename in `exclude`
+    ///
+    /// # Errors
+    ///
+    /// Returns `ListError::NoMatchingType` if no pokemon has the given types
+    /// Returns `ListError::Exhausted` if every pokemon which could be picked is in `exclude`
+    pub fn random_excluding(
+        &self,
+        regions: &[Region],
+        types: &[Type],
+        exclude: &HashSet<String>,
+    ) -> Result<String, ListError> {
+        let candidates: Vec<usize> = Self::candidates(regions, types)?
+            .into_iter()
+            .filter(|id| !exclude.contains(&self.filenames[id - 1]))
+            .collect();
+
+        if candidates.is_empty() {
+            return Err(ListError::Exhausted);
+        }
+
+        let idx = candidates[rand::thread_rng().gen_range(0..candidates.len())];
+
+        self.get_by_id(idx).cloned()
+    }
+
+    /// Dex IDs of the pokemon having all of `types` in the union of `regions`,
+    /// or in every region if `regions` is empty
+    fn candidates(regions: &[Region], types: &[Type]) -> Result<Vec<usize>, ListError> {
+        let mut ranges = Self::unique_ranges(regions);
+        if ranges.is_empty() {
+            ranges.push(1..=POKEMON.len());
+        }
+
+        let candidates: Vec<usize> = ranges
+            .into_iter()
+            .flatten()
+            .filter(|id| {
+                Self::entry(*id).is_some_and(|(_, _, x)| types.iter().all(|t| x.contains(t)))
+            })
+            .collect();
+
+        if candidates.is_empty() {
+            let names: Vec<&str> = types.iter().map(Type::name).collect();
+            return Err(ListError::NoMatchingType(names.join(", ")));
+        }
+
+        Ok(candidates)
+    }
+}
+
+/// Normalizes a user supplied name into the filename format of the dataset
+#[must_use]
+pub fn normalize_filename(name: &str) -> String {
+    sanitize_filename(&replace_gender_signs(name).replace([' ', '_'], "-"))
+        .replace(['.', '\'', ':'], "")
+        .to_lowercase()
+        .trim()
+        .to_string()
 }
 
 /// Sanitize filename to prevent path traversal
@@ -1,51 +1,1255 @@
 //! Display pokemon sprites in your terminal.
 
 use clap::Parser;
+use image::{DynamicImage, ImageError, Rgba, RgbaImage};
 use pokeget::{
+    achievements, backdrop, bubble,
+    cache::{RenderCache, RenderOptions},
     cli::Args,
+    clipboard, color,
+    dex::Dex,
+    font, fortune,
+    labels::{label_line, type_badges, Label},
+    limits::{Focus, Limits, Throttle, FOCUS_REPORTING_OFF, FOCUS_REPORTING_ON},
     list::List,
-    pokemon::{Attributes, Pokemon},
-    sprites::combine_sprites,
+    names::{title_case, NameOutput},
+    pacing::Pace,
+    pager,
+    pokemon::{Attributes, Located, Pokemon, PokemonError, Region, Selection},
+    render::{write_png, write_svg, Backend, Format, Html, Renderer},
+    scene::SceneFile,
+    specs,
+    sprites::{
+        combine_labeled_rows, combine_sprites, compose_with_text, crop_lines, downscale_to,
+        fill_background, render_sprites, Fit, LayoutOptions, Position, Spacing, Text,
+    },
+    stamp::{stamp, STAMP_COLOR},
+    state::{StateScope, StateStore},
+    stats::StatTable,
+    terminal::{
+        read_keys, ColorDepth, HostTerminal, Key, RawMode, Restore, Terminal, VirtualTerminal,
+        SHOW_CURSOR,
+    },
+    tmux,
+    transform::split_transforms,
+    transition::Transition,
+    watch::Watcher,
 };
-use std::process::exit;
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, IsTerminal, Read, Write},
+    path::Path,
+    process::{exit, Child},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+mod commands;
+
+use commands::CommandError;
+
+/// Frame rate of the transitions between slides
+const SLIDESHOW_FPS: u32 = 20;
+
+/// Amount of frames in a transition between slides
+const SLIDESHOW_FRAMES: u32 = 20;
+
+/// How often sprite directories are checked for edited sprites during a slideshow
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
 
 fn main() {
     let args = Args::parse();
 
-    let list = List::read().unwrap_or_else(|err| {
-        eprintln!("Error reading pokemon list: {err}");
-        exit(1);
-    });
+    if let Some(command) = args.command {
+        commands::run(command, args.state_scope).unwrap_or_else(|err| {
+            eprintln!("Error: {err}");
+            exit(1);
+        });
+
+        return;
+    }
+
+    let list = List::global();
+
+    if let Some(path) = &args.scene {
+        draw_scene(path, &args, list);
+        return;
+    }
+
+    let selections = args
+        .collect_pokemon(io::stdin().lock())
+        .unwrap_or_else(|err| {
+            eprintln!("Error reading pokemon from stdin: {err}");
+            exit(1);
+        });
+
+    let specs = match &args.file {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|x| specs::parse(&x).map_err(|err| err.to_string()))
+            .unwrap_or_else(|err| {
+                eprintln!("Error reading {}: {err}", path.display());
+                exit(1);
+            }),
+        None => Vec::new(),
+    };
 
-    if args.pokemon.is_empty() {
+    if selections.is_empty() && specs.is_empty() {
         eprintln!("You must specify at least one Pokémon");
         exit(1);
     }
 
-    let attributes = Attributes::new(&args).unwrap_or_else(|err| {
-        eprintln!("Error creating attributes: {err}");
+    let attributes = Attributes::try_from(&args)
+        .map(Arc::new)
+        .unwrap_or_else(|err| {
+            eprintln!("Error creating attributes: {err}");
+            exit(1);
+        });
+
+    // Every line of --file gets its own attributes on top of the flags
+    let spec_attributes: Vec<Arc<Attributes>> = specs
+        .iter()
+        .map(|x| x.apply(args.attributes_builder()).build().map(Arc::new))
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|err| {
+            eprintln!("Error creating attributes: {err}");
+            exit(1);
+        });
+
+    let layout = args.layout();
+
+    let requested: Vec<(&String, &Arc<Attributes>)> = selections
+        .iter()
+        .zip(std::iter::repeat(&attributes))
+        .chain(specs.iter().map(|x| &x.pokemon).zip(&spec_attributes))
+        .collect();
+
+    let plan = Plan::new(&args, VirtualTerminal::probe(&HostTerminal));
+    let render_options = plan.render_options(layout);
+    let Plan {
+        terminal,
+        backend,
+        renderer,
+        canvas,
+        name_output,
+        show_name,
+        said,
+        output_lines,
+        max_lines,
+        backdrop,
+        fit,
+    } = plan;
+
+    if let Some(seconds) = args.slideshow {
+        // Frames are drawn over each other, which only works with text
+        let transition = match backend.is_graphics() {
+            true => Transition::None,
+            false => args.transition,
+        };
+
+        let mut pokemons = Vec::new();
+        let next = |reload: bool| {
+            match reload {
+                true => pokemons
+                    .iter_mut()
+                    .try_for_each(Pokemon::reload)
+                    .map_err(|err| format!("Error reloading pokemon: {err}"))?,
+                false => {
+                    pokemons = create_pokemon(&requested, &args, list)
+                        .map_err(|err| format!("Error creating pokemon: {err}"))?;
+                }
+            }
+            fit.apply(&mut pokemons, &canvas, layout.spacing)
+                .map_err(|err| format!("Error combining sprites: {err}"))?;
+
+            let image = combine_sprites(&pokemons, &canvas, layout)
+                .map_err(|err| format!("Error combining sprites: {err}"))?;
+            let image = match &backdrop {
+                Some(tile) => backdrop::compose(&image, tile, canvas.width()),
+                None => image,
+            };
+            let names: Vec<String> = pokemons
+                .iter()
+                .map(|x| caption(x, &args, list, terminal.color_depth))
+                .collect();
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            let line = show_name.then(|| args.greeting.format(&names));
+
+            if !reload {
+                record_usage(&pokemons, list, args.state_scope);
+            }
+            Ok((image, line))
+        };
+
+        // Sprites edited in a sprite directory are redrawn right away
+        let dirs = args.sprite_dirs();
+        let watcher = (!dirs.is_empty()).then(|| Watcher::new(dirs));
+
+        let interval = Duration::from_secs(seconds);
+        slideshow(
+            interval,
+            transition,
+            args.limits.limits(),
+            renderer.as_ref(),
+            watcher,
+            next,
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("{err}");
+            exit(1);
+        });
+
+        return;
+    }
+
+    if args.stream {
+        let mut shown = HashSet::new();
+        let result = for_each_pokemon(&requested, &args, list, &mut shown, |pokemon| {
+            let mut pokemon = pokemon.load()?;
+            downscale_to(
+                std::slice::from_mut(&mut pokemon),
+                fit.max_width,
+                fit.max_height,
+                args.scale_filter,
+            );
+            stream(&pokemon, &args, list, &canvas, renderer.as_ref(), backend);
+            record_usage(&[pokemon], list, args.state_scope);
+            Ok(())
+        });
+
+        if let Err(err) = result {
+            eprintln!("Error creating pokemon: {err}");
+            exit(1);
+        }
+
+        return;
+    }
+
+    let located = locate_pokemon(&requested, &args, list).unwrap_or_else(|err| {
+        eprintln!("Error creating pokemon: {err}");
         exit(1);
     });
 
-    let pokemons: Vec<Pokemon> = args
-        .pokemon
+    // Only the plain sprites are cached, keyed by the files they're drawn from
+    let cacheable = args.format == Format::Text
+        && !(args.info || args.stats || args.show_types || args.labels_names())
+        && backdrop.is_none();
+    let cache = cacheable
+        .then(RenderCache::open_default)
+        .and_then(Result::ok)
+        .map(|cache| {
+            let key = RenderCache::key(&located, render_options);
+            (cache, key)
+        });
+
+    let cached = cache.as_ref().and_then(|(cache, key)| cache.get(key));
+
+    if let Some((_, key)) = cache.as_ref().filter(|_| args.verbose) {
+        let status = if cached.is_some() { "hit" } else { "miss" };
+        eprintln!("Render cache {status}: {key}");
+    }
+
+    // A cached render stands in for the sprites, which are only decoded if something else draws them
+    let decode = cached.is_none() || said.is_some() || args.copy;
+    let pokemons: Vec<Pokemon> = match decode {
+        true => prepare(located, fit, &canvas, layout.spacing).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            exit(1);
+        }),
+        false => located.into_iter().map(Located::without_sprite).collect(),
+    };
+
+    if args.format != Format::Text {
+        let image = compose_image(&pokemons, &canvas, &args, list, backdrop.as_ref());
+        write_image(&image, args.format);
+        if args.copy {
+            copy_image(&image);
+        }
+
+        record_usage(&pokemons, list, args.state_scope);
+        return;
+    }
+
+    let output = if args.info || args.stats {
+        let dex = args.info.then(Dex::read);
+        let stats = args.stats.then(StatTable::read);
+
+        pokemons
+            .iter()
+            .map(|x| {
+                let mut text = Vec::new();
+
+                if let Some(dex) = &dex {
+                    text.extend(dex.info(x).into_iter().map(Text::Paragraph));
+                }
+
+                if let Some(stats) = &stats {
+                    if !text.is_empty() {
+                        text.push(Text::Paragraph(String::new()));
+                    }
+
+                    match stats.get(&x.species) {
+                        Some(base) => text.extend(
+                            base.bars(
+                                terminal.color_depth(),
+                                x.accent().filter(|_| args.theme_by_type),
+                            )
+                            .into_iter()
+                            .map(Text::Line),
+                        ),
+                        None => text.push(Text::Paragraph("No base stats available.".to_string())),
+                    }
+                }
+
+                let mut sprite = x.sprite.clone();
+                if let Some(rgb) = layout.background {
+                    fill_background(&mut sprite, rgb);
+                }
+
+                compose_with_text(
+                    &sprite,
+                    renderer.as_ref(),
+                    &text,
+                    Position::Right,
+                    &terminal,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else if args.show_types || args.labels_names() {
+        let names: Vec<Label> = match args.labels_names() {
+            true => pokemons
+                .iter()
+                .map(|x| Label::styled(&caption(x, &args, list, terminal.color_depth)))
+                .collect(),
+            false => Vec::new(),
+        };
+        let badges: Vec<Label> = match args.show_types {
+            true => pokemons
+                .iter()
+                .map(|x| type_badges(&x.types, terminal.color_depth()))
+                .collect(),
+            false => Vec::new(),
+        };
+
+        let rows = combine_labeled_rows(&pokemons, &[&names, &badges], &terminal, layout)
+            .unwrap_or_else(|err| {
+                eprintln!("Error combining sprites: {err}");
+                exit(1);
+            });
+
+        rows.iter()
+            .map(|row| {
+                let mut lines = vec![renderer.render(&row.image)];
+                lines.extend(
+                    [&names, &badges]
+                        .into_iter()
+                        .filter(|x| !x.is_empty())
+                        .map(|x| label_line(row, x)),
+                );
+                lines.join("\n")
+            })
+            .collect::<Vec<_>>()
+            // Every blank line between rows makes up two pixels of vertical spacing
+            .join(&"\n".repeat(1 + layout.spacing.y as usize / 2))
+    } else if let Some(tile) = &backdrop {
+        let image = combine_sprites(&pokemons, &canvas, layout).unwrap_or_else(|err| {
+            eprintln!("Error combining sprites: {err}");
+            exit(1);
+        });
+
+        renderer.render(&backdrop::compose(&image, tile, canvas.width()))
+    } else {
+        match cached {
+            Some(output) => output,
+            None => {
+                let output = render_sprites(&pokemons, &canvas, layout, renderer.as_ref())
+                    .unwrap_or_else(|err| {
+                        eprintln!("Error combining sprites: {err}");
+                        exit(1);
+                    });
+
+                // The cache is only an optimization, so failing to write to it is fine
+                if let Some((cache, key)) = &cache {
+                    let _ = cache.insert(key, &output);
+                }
+
+                output
+            }
+        }
+    };
+
+    let output = match max_lines {
+        Some(lines) => crop_lines(&output, lines as usize),
+        None => output,
+    };
+
+    // Names in the frame would give silhouettes away
+    let output = match args.border {
+        Some(style) => {
+            let names: Vec<&str> = pokemons.iter().map(|x| x.name.as_str()).collect();
+            let title = (!args.silhouette).then(|| names.join(", "));
+            // Themed by the first pokemon, like the bubble pointing at it
+            let color = match pokemons[0].accent() {
+                Some(rgb)
+                    if args.theme_by_type && terminal.color_depth != ColorDepth::Monochrome =>
+                {
+                    Some(color::fg(rgb, terminal.color_depth))
+                }
+                _ => None,
+            };
+            style.frame(&output, title.as_deref(), color.as_deref())
+        }
+        None => output,
+    };
+
+    // The bubble points at the first sprite, which starts at the left edge
+    let output = match &said {
+        Some(text) => {
+            let target = renderer.columns(pokemons[0].sprite.width()) as usize / 2;
+            bubble::say(&output, text, target, terminal.width() as usize)
+        }
+        None => output,
+    };
+
+    let output = match output_lines {
+        Some(lines) => crop_lines(&output, lines as usize),
+        None => output,
+    };
+
+    let mut text = Vec::new();
+
+    if show_name {
+        let names: Vec<String> = pokemons
+            .iter()
+            .map(|x| caption(x, &args, list, terminal.color_depth))
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let line = args.greeting.format(&names);
+
+        match name_output {
+            NameOutput::Stdout => text.push(line),
+            _ => eprintln!("{line}"),
+        }
+    }
+
+    if max_lines != Some(0) {
+        match backend.is_graphics() && args.tmux.unwrap_or_else(tmux::detected) {
+            true => text.push(tmux::passthrough(&output)),
+            false => text.push(output),
+        }
+    }
+
+    if !text.is_empty() {
+        let text = text.join("\n");
+        // Pagers can't show graphics
+        let paged = args.paged && !backend.is_graphics();
+
+        Sink::open(paged, text.lines().count(), terminal.height())
+            .write(&text)
+            .unwrap_or_else(|err| write_failed(&err));
+    }
+
+    if args.silhouette && name_output != NameOutput::None {
+        reveal(&pokemons, args.reveal);
+    }
+
+    if args.copy {
+        copy_image(&compose_image(
+            &pokemons,
+            &canvas,
+            &args,
+            list,
+            backdrop.as_ref(),
+        ));
+    }
+
+    record_usage(&pokemons, list, args.state_scope);
+}
+
+/// How the sprites are laid out and drawn, worked out from the flags and the terminal.
+///
+/// `pokeget cache warm` plans its renders the same way, so they're found under the keys
+/// the sprites are later looked up by.
+struct Plan {
+    terminal: VirtualTerminal,
+    backend: Backend,
+    renderer: Box<dyn Renderer>,
+    /// The terminal measured in the renderer's pixels
+    canvas: VirtualTerminal,
+    name_output: NameOutput,
+    /// Whether the names are written on a line above the sprites
+    show_name: bool,
+    /// What the speech bubble says, from --say or --fortune
+    said: Option<String>,
+    /// Lines left for everything below the name
+    output_lines: Option<u32>,
+    /// Lines left for the sprites
+    max_lines: Option<u32>,
+    backdrop: Option<RgbaImage>,
+    fit: Fit,
+}
+
+impl Plan {
+    fn new(args: &Args, terminal: VirtualTerminal) -> Self {
+        let mut terminal = terminal;
+        if let Some(depth) = args.color_depth {
+            terminal.color_depth = depth;
+        }
+        if let Some(width) = args.max_width {
+            terminal.width = terminal.width.min(width);
+        }
+
+        // A frame takes up a column on each side and a line above and below the sprites
+        let frame = match args.border {
+            Some(_) => 2,
+            None => 0,
+        };
+        terminal.width = terminal.width.saturating_sub(frame);
+
+        let mut backend = args.renderer.resolve(&terminal, io::stdout().is_terminal());
+
+        // Graphics can't be placed next to text
+        if args.info
+            || args.stats
+            || args.show_types
+            || args.labels_names()
+            || args.border.is_some()
+        {
+            backend = backend.for_text();
+        }
+
+        // Labels are placed by pixel, so they need a character for every pixel of width
+        if (args.show_types || args.labels_names())
+            && backend.renderer(terminal.color_depth).cell_size().0 != 1
+        {
+            backend = Backend::HalfBlock;
+        }
+
+        let renderer = backend.renderer(terminal.color_depth);
+        let canvas = renderer.canvas(&terminal);
+
+        let name_output = args.name_output();
+
+        // The line of names above the sprites, as opposed to names drawn beneath them
+        let show_name = matches!(name_output, NameOutput::Stdout | NameOutput::Stderr)
+            && !args.silhouette
+            && args.max_lines != Some(0);
+
+        let said = match args.fortune {
+            true => Some(fortune(args)),
+            false => args.say.clone(),
+        };
+
+        // The bubble can only wrap onto more lines when it starts further right,
+        // so the output is cropped again once it's drawn
+        let bubble_lines = said.as_deref().map_or(0, |x| {
+            let lines = bubble::say("", x, 0, terminal.width() as usize)
+                .lines()
+                .count();
+            u32::try_from(lines).unwrap_or(u32::MAX)
+        });
+
+        // Lines left for everything below the name, and for the sprites once the frame and bubble are in
+        let output_lines = args
+            .max_lines
+            .map(|x| x.saturating_sub(u32::from(show_name)));
+        let max_lines = output_lines.map(|x| x.saturating_sub(frame + bubble_lines));
+
+        // Lines the sprites can take up without scrolling the name off screen,
+        // leaving one for the prompt after them
+        let fit_lines = match args.no_fit || args.paged {
+            true => None,
+            false => canvas
+                .height()
+                .map(|x| x.saturating_sub(1 + u32::from(show_name) + frame + bubble_lines)),
+        };
+
+        // --max-width and --max-height apply even when sprites otherwise keep their size
+        let (max_width, lines) = match args.no_downscale {
+            true => (args.max_width.map(|_| canvas.width()), args.max_height),
+            false => (
+                Some(canvas.width()),
+                [max_lines, fit_lines, args.max_height]
+                    .into_iter()
+                    .flatten()
+                    .min(),
+            ),
+        };
+
+        // Names and type badges each take up a line beneath the sprites
+        let label_lines = u32::from(args.labels_names()) + u32::from(args.show_types);
+        let max_height = lines.map(|x| {
+            renderer
+                .half_block_lines(x.saturating_sub(label_lines))
+                .saturating_mul(2)
+        });
+
+        let backdrop = args.background.as_ref().map(|x| {
+            x.tile().unwrap_or_else(|err| {
+                eprintln!("Error reading background: {err}");
+                exit(1);
+            })
+        });
+
+        // The canvas grows to whole tiles of the backdrop, which takes up to a tile less than that
+        let max_height = match &backdrop {
+            Some(tile) => max_height.map(|x| x.saturating_sub(tile.height().saturating_sub(1))),
+            None => max_height,
+        };
+
+        let (max_width, max_height) = (
+            max_width.unwrap_or(u32::MAX),
+            max_height.unwrap_or(u32::MAX),
+        );
+
+        // --max-height limits all rows of sprites together, not only each sprite on its own
+        let rows_height = args.max_height.map(|x| {
+            let pixels = renderer.half_block_lines(x).saturating_mul(2);
+            match &backdrop {
+                Some(tile) => pixels.saturating_sub(tile.height().saturating_sub(1)),
+                None => pixels,
+            }
+        });
+
+        let fit = Fit {
+            max_width,
+            max_height,
+            rows_height,
+            row_padding: label_lines.saturating_mul(2),
+            filter: args.scale_filter,
+        };
+
+        Self {
+            terminal,
+            backend,
+            renderer,
+            canvas,
+            name_output,
+            show_name,
+            said,
+            output_lines,
+            max_lines,
+            backdrop,
+            fit,
+        }
+    }
+
+    /// Options the renders of sprites laid out with `layout` are cached under,
+    /// with the sprites shrunk by [`prepare`]
+    fn render_options(&self, layout: LayoutOptions) -> RenderOptions {
+        RenderOptions {
+            width: self.terminal.width(),
+            fit: self.fit,
+            layout,
+            renderer: self.backend,
+            color_depth: self.terminal.color_depth,
+        }
+    }
+}
+
+/// Shows a new slide every `interval` until q or Ctrl-C is pressed,
+/// going from one to the next with `transition`.
+///
+/// `next` creates the image of the next slide and the line to write beneath it, if any.
+fn slideshow(
+    interval: Duration,
+    transition: Transition,
+    limits: Limits,
+    renderer: &dyn Renderer,
+    mut watcher: Option<Watcher>,
+    mut next: impl FnMut(bool) -> Result<(DynamicImage, Option<String>), String>,
+) -> Result<(), String> {
+    let input = read_input();
+    // Ctrl-C is read as a key, so the terminal is always restored
+    let _raw = input.is_some().then(RawMode::enable);
+    let mut throttle = limits.throttle(SLIDESHOW_FPS);
+    let mut stdout = io::stdout().lock();
+
+    let error = |err: io::Error| format!("Error writing output: {err}");
+    write!(stdout, "\x1b[?25l{FOCUS_REPORTING_ON}\x1b[2J").map_err(error)?;
+    // Shows the cursor again however the slideshow ends, even on errors and kill signals
+    let _restore = Restore::new(&[FOCUS_REPORTING_OFF, SHOW_CURSOR]);
+
+    let mut previous: Option<DynamicImage> = None;
+    let mut reload = false;
+    let result = loop {
+        let (image, line) = match next(reload) {
+            Ok(slide) => slide,
+            Err(err) => break Err(err),
+        };
+
+        let mut draw = |frame: &DynamicImage| {
+            let start = Instant::now();
+            let mut text = renderer.render(frame);
+            if let Some(line) = &line {
+                text.push('\n');
+                text.push_str(line);
+            }
+
+            // Drawn over the last frame, clearing whatever it left beneath
+            write!(stdout, "\x1b[H{text}\x1b[J")?;
+            stdout.flush()?;
+
+            Ok::<_, io::Error>(start.elapsed())
+        };
+
+        // A transition keeps both slides and the current frame, each as large as both slides
+        let frame_bytes = |from: &DynamicImage| {
+            let (width, height) = (
+                from.width().max(image.width()),
+                from.height().max(image.height()),
+            );
+            u64::from(width) * u64::from(height) * 4
+        };
+        let frames: Box<dyn Iterator<Item = DynamicImage>> = match previous.take() {
+            Some(from) if limits.allows(frame_bytes(&from) * 3) => {
+                Box::new(transition.frames(&from, &image, SLIDESHOW_FRAMES))
+            }
+            _ => Box::new(std::iter::once(image.clone())),
+        };
+
+        let mut quit = false;
+        for frame in frames {
+            let busy = match draw(&frame) {
+                Ok(busy) => busy,
+                Err(err) => return Err(error(err)),
+            };
+
+            match throttle.frame_drawn(busy) {
+                Pace::Wait(wait) => quit = wait_for_quit(input.as_ref(), &mut throttle, wait),
+                // Too slow to animate, skip to the next slide
+                Pace::Static => {
+                    draw(&image).map_err(error)?;
+                    break;
+                }
+            }
+
+            if quit {
+                break;
+            }
+        }
+
+        // The slide stays up for as long as drawing is paused while the terminal isn't focused,
+        // and is drawn again without a transition as soon as one of the watched sprites changes
+        let step = match watcher {
+            Some(_) => WATCH_INTERVAL.min(interval),
+            None => interval,
+        };
+        let mut shown = Duration::ZERO;
+        reload = false;
+        while !quit {
+            quit = wait_for_quit(input.as_ref(), &mut throttle, step);
+            shown += step;
+
+            reload = watcher.as_mut().is_some_and(|x| !x.poll().is_empty());
+            if reload || (shown >= interval && !throttle.is_paused()) {
+                break;
+            }
+        }
+
+        if quit {
+            break Ok(());
+        }
+
+        previous = (!reload).then_some(image);
+    };
+
+    writeln!(stdout).map_err(error)?;
+    result
+}
+
+/// Reads input from the terminal on a separate thread, `None` if there's no terminal
+fn read_input() -> Option<Receiver<Vec<u8>>> {
+    let mut tty = File::open("/dev/tty").ok()?;
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut buffer = [0; 64];
+        while let Ok(read @ 1..) = tty.read(&mut buffer) {
+            if sender.send(buffer[..read].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    Some(receiver)
+}
+
+/// Waits for `duration`, returning early with true if q, Escape or Ctrl-C is pressed.
+/// Focus reports are passed on to `throttle`.
+fn wait_for_quit(
+    input: Option<&Receiver<Vec<u8>>>,
+    throttle: &mut Throttle,
+    duration: Duration,
+) -> bool {
+    let deadline = Instant::now() + duration;
+
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let bytes = match input.map(|x| x.recv_timeout(left)) {
+            Some(Ok(bytes)) => bytes,
+            Some(Err(RecvTimeoutError::Timeout)) => return false,
+            None | Some(Err(RecvTimeoutError::Disconnected)) => {
+                thread::sleep(left);
+                return false;
+            }
+        };
+
+        if let Some(focus) = Focus::last_in(&bytes) {
+            throttle.set_focus(focus);
+        }
+
+        let keys = read_keys(&mut bytes.as_slice()).unwrap_or_default();
+        if keys
+            .iter()
+            .any(|x| matches!(x, Key::Char('q') | Key::Escape | Key::Ctrl('c' | 'd')))
+        {
+            return true;
+        }
+    }
+}
+
+/// Where the output is written
+enum Sink {
+    Stdout,
+    /// Piped into `$PAGER`
+    Pager(Child),
+    /// Shown by pokeget a page of this many lines at a time
+    Paged(usize),
+}
+
+impl Sink {
+    /// A pager for `lines` lines of output if `paged` is set and they don't fit in `height`,
+    /// stdout otherwise
+    fn open(paged: bool, lines: usize, height: Option<u32>) -> Self {
+        let height = match height {
+            Some(height) if paged && io::stdout().is_terminal() && lines > height as usize => {
+                height as usize
+            }
+            _ => return Sink::Stdout,
+        };
+
+        match pager::spawn() {
+            Some(Ok(child)) => Sink::Pager(child),
+            Some(Err(err)) => {
+                eprintln!("Error starting $PAGER: {err}");
+                Sink::Paged(height)
+            }
+            None => Sink::Paged(height),
+        }
+    }
+
+    /// Writes `text` followed by a newline, waiting for the pager to be closed
+    fn write(self, text: &str) -> io::Result<()> {
+        match self {
+            Sink::Stdout => writeln!(io::stdout().lock(), "{text}"),
+            Sink::Pager(mut child) => {
+                if let Some(mut stdin) = child.stdin.take() {
+                    // Quitting the pager before reading everything isn't an error
+                    let _ = writeln!(stdin, "{text}");
+                }
+
+                child.wait().map(|_| ())
+            }
+            Sink::Paged(height) => pager::page(text, height),
+        }
+    }
+}
+
+/// Creates the requested pokemon, each one --count times.
+///
+/// Selections are expanded before creating any pokemon, so with --unique
+/// random ones are picked from the pokemon which weren't shown yet.
+fn create_pokemon(
+    requested: &[(&String, &Arc<Attributes>)],
+    args: &Args,
+    list: &List,
+) -> Result<Vec<Pokemon>, PokemonError> {
+    locate_pokemon(requested, args, list)?
+        .into_iter()
+        .map(Located::load)
+        .collect()
+}
+
+/// Decodes and draws the sprites, then shrinks them by `fit` to the space they're drawn in on `canvas`
+fn prepare(
+    located: Vec<Located>,
+    fit: Fit,
+    canvas: &VirtualTerminal,
+    spacing: Spacing,
+) -> Result<Vec<Pokemon>, CommandError> {
+    let mut pokemons = located
         .into_iter()
-        .map(|x| Pokemon::new(x, &list, &attributes))
-        .collect::<Result<Vec<_>, _>>()
+        .map(Located::load)
+        .collect::<Result<Vec<_>, _>>()?;
+    fit.apply(&mut pokemons, canvas, spacing)?;
+
+    Ok(pokemons)
+}
+
+/// Picks the requested pokemon and reads their sprite files like [`create_pokemon`],
+/// without decoding them yet
+fn locate_pokemon(
+    requested: &[(&String, &Arc<Attributes>)],
+    args: &Args,
+    list: &List,
+) -> Result<Vec<Located>, PokemonError> {
+    let mut pokemons = Vec::with_capacity(requested.len() * args.count as usize);
+    for_each_pokemon(requested, args, list, &mut HashSet::new(), |x| {
+        pokemons.push(x);
+        Ok(())
+    })?;
+
+    Ok(pokemons)
+}
+
+/// Picks the requested pokemon one at a time like [`locate_pokemon`],
+/// handing each one to `f` before picking the next.
+/// The species of every pokemon picked is added to `shown`.
+fn for_each_pokemon(
+    requested: &[(&String, &Arc<Attributes>)],
+    args: &Args,
+    list: &List,
+    shown: &mut HashSet<String>,
+    mut f: impl FnMut(Located) -> Result<(), PokemonError>,
+) -> Result<(), PokemonError> {
+    for &(arg, attributes) in requested {
+        let (arg, inline) = split_transforms(arg);
+
+        for _ in 0..args.count {
+            let selections = Selection::parse(arg.to_string())
+                .within(&attributes.regions)
+                .expand(args.all);
+
+            for mut selection in selections {
+                if args.unique {
+                    selection = selection.pick(list, &attributes.types, shown)?;
+                }
+
+                let pokemon =
+                    Located::from_selection(selection, &inline, list, Arc::clone(attributes))?;
+                shown.insert(pokemon.species.clone());
+                f(pokemon)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints a single pokemon for --stream, its name first unless names are hidden
+fn stream(
+    pokemon: &Pokemon,
+    args: &Args,
+    list: &List,
+    canvas: &VirtualTerminal,
+    renderer: &dyn Renderer,
+    backend: Backend,
+) {
+    let output = render_sprites(
+        std::slice::from_ref(pokemon),
+        canvas,
+        args.layout(),
+        renderer,
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Error combining sprites: {err}");
+        exit(1);
+    });
+    let output = match backend.is_graphics() && args.tmux.unwrap_or_else(tmux::detected) {
+        true => tmux::passthrough(&output),
+        false => output,
+    };
+
+    let name = caption(pokemon, args, list, canvas.color_depth);
+    let mut stdout = io::stdout().lock();
+
+    let written = match args.name_output() {
+        _ if args.silhouette => Ok(()),
+        NameOutput::Stdout => writeln!(stdout, "{name}"),
+        NameOutput::Stderr => {
+            eprintln!("{name}");
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+    .and_then(|_| writeln!(stdout, "{output}"))
+    .and_then(|_| stdout.flush());
+
+    written.unwrap_or_else(|err| write_failed(&err));
+}
+
+/// Reports an error writing the output and exits.
+///
+/// Whatever reads the output going away early, eg. `head`, isn't an error,
+/// so a broken pipe exits quietly and successfully.
+fn write_failed(err: &io::Error) -> ! {
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        exit(0);
+    }
+
+    eprintln!("Error writing output: {err}");
+    exit(1);
+}
+
+/// A fortune piped into stdin, unless it's used for pokemon, or a built in one
+fn fortune(args: &Args) -> String {
+    let stdin = io::stdin();
+
+    if !stdin.is_terminal() && !args.reads_stdin() {
+        let mut text = String::new();
+        if stdin.lock().read_to_string(&mut text).is_ok() && !text.trim().is_empty() {
+            return text;
+        }
+    }
+
+    fortune::random()
+}
+
+/// The name of a pokemon as shown above the sprites, themed and captioned according to `args`
+fn caption(pokemon: &Pokemon, args: &Args, list: &List, depth: ColorDepth) -> String {
+    let mut caption = match pokemon.accent() {
+        Some(rgb) if args.theme_by_type && depth != ColorDepth::Monochrome => {
+            format!("{}{}{}", color::fg(rgb, depth), pokemon.name, color::RESET)
+        }
+        _ => pokemon.name.clone(),
+    };
+
+    if !args.no_indicators {
+        if pokemon.shiny {
+            caption.push_str(" ★");
+        }
+        if pokemon.female {
+            caption.push_str(" ♀");
+        }
+    }
+
+    if args.show_id {
+        if let Some(id) = list.get_id_by_name(&pokemon.species) {
+            caption.insert_str(0, &format!("#{id:03} "));
+        }
+    }
+
+    if args.caption_region {
+        if let Some(region) = list.id_of(&pokemon.species).and_then(Region::of) {
+            caption.push_str(&format!(
+                " ({}, Gen {})",
+                title_case(region.name()),
+                region.generation()
+            ));
+        }
+    }
+
+    caption
+}
+
+/// Draws the scene in the file at `path`, as text or as a PNG image with `--format png`
+fn draw_scene(path: &Path, args: &Args, list: &List) {
+    let image = fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|x| SceneFile::parse(&x).map_err(|err| err.to_string()))
+        .and_then(|x| {
+            x.render(list, || args.attributes_builder())
+                .map_err(|err| err.to_string())
+        })
         .unwrap_or_else(|err| {
-            eprintln!("Error creating pokemon: {err}");
+            eprintln!("Error reading {}: {err}", path.display());
             exit(1);
         });
 
-    let combined = combine_sprites(&pokemons).unwrap_or_else(|err| {
+    let mut stdout = io::stdout();
+
+    if args.format == Format::Png {
+        if stdout.is_terminal() {
+            eprintln!(
+                "Error: Refusing to write a PNG image to the terminal, redirect it into a file"
+            );
+            exit(1);
+        }
+
+        write_png(&mut stdout, &image).unwrap_or_else(|err| {
+            eprintln!("Error writing image: {err}");
+            exit(1);
+        });
+        return;
+    }
+
+    if args.format == Format::Svg {
+        write_svg(&mut stdout, &image).unwrap_or_else(|err| {
+            eprintln!("Error writing image: {err}");
+            exit(1);
+        });
+        return;
+    }
+
+    let mut terminal = VirtualTerminal::probe(&HostTerminal);
+    if let Some(depth) = args.color_depth {
+        terminal.color_depth = depth;
+    }
+
+    // The canvas has a fixed size, so it's drawn as it is even if it doesn't fit
+    let backend = args.renderer.resolve(&terminal, stdout.is_terminal());
+    let output = match args.format {
+        Format::Html => Html.render(&image),
+        _ => backend.renderer(terminal.color_depth).render(&image),
+    };
+    let output = match backend.is_graphics() && args.tmux.unwrap_or_else(tmux::detected) {
+        true if args.format == Format::Text => tmux::passthrough(&output),
+        _ => output,
+    };
+
+    writeln!(stdout, "{output}").unwrap_or_else(|err| write_failed(&err));
+}
+
+/// Combines the sprites into one image over the backdrop, with optional captions and credit drawn on
+fn compose_image(
+    pokemons: &[Pokemon],
+    terminal: &dyn Terminal,
+    args: &Args,
+    list: &List,
+    backdrop: Option<&RgbaImage>,
+) -> DynamicImage {
+    let color = |pokemon: Option<&Pokemon>| match pokemon.and_then(Pokemon::accent) {
+        Some([r, g, b]) if args.theme_by_type => Rgba([r, g, b, 255]),
+        _ => STAMP_COLOR,
+    };
+
+    // Captions are drawn beneath the sprites before they're combined, so each row makes room for them
+    let captioned: Vec<Pokemon>;
+    let pokemons = match args.captions {
+        true => {
+            captioned = pokemons
+                .iter()
+                .map(|x| {
+                    let text = caption(x, args, list, ColorDepth::Monochrome);
+                    let sprite = font::SMALL.caption(&x.sprite, &text, color(Some(x)), 1);
+                    Pokemon {
+                        sprite,
+                        ..x.clone()
+                    }
+                })
+                .collect();
+            &captioned
+        }
+        false => pokemons,
+    };
+
+    let mut image = combine_sprites(pokemons, terminal, args.layout()).unwrap_or_else(|err| {
         eprintln!("Error combining sprites: {err}");
-        std::process::exit(1);
+        exit(1);
     });
 
-    if !args.hide_name {
-        let names: Vec<&str> = pokemons.iter().map(|x| x.name.as_ref()).collect();
-        eprintln!("{}", names.join(", "));
+    if let Some(tile) = backdrop {
+        image = backdrop::compose(&image, tile, terminal.width());
+    }
+
+    if let Some(credit) = &args.credit {
+        let color = color(pokemons.first());
+        image = stamp(&image, credit, args.credit_corner, &font::SMALL, color);
+    }
+
+    image
+}
+
+/// Writes an image to stdout in `format`
+fn write_image(image: &DynamicImage, format: Format) {
+    let mut stdout = io::stdout();
+
+    if format == Format::Png && stdout.is_terminal() {
+        eprintln!("Error: Refusing to write a PNG image to the terminal, redirect it into a file");
+        exit(1);
     }
 
-    println!("{}", showie::to_ascii(&combined));
+    let written = match format {
+        Format::Svg => write_svg(&mut stdout, image),
+        Format::Html => writeln!(stdout, "{}", Html.render(image)),
+        _ => write_png(&mut stdout, image).map_err(|err| match err {
+            ImageError::IoError(err) => err,
+            err => {
+                eprintln!("Error encoding image: {err}");
+                exit(1);
+            }
+        }),
+    };
+
+    written.unwrap_or_else(|err| write_failed(&err));
+}
+
+/// Places an image on the clipboard as PNG.
+///
+/// Failing to is only a warning, since the sprites were shown anyway
+/// and headless systems have no clipboard to begin with.
+fn copy_image(image: &DynamicImage) {
+    let mut png = Vec::new();
+    let copied = write_png(&mut png, image)
+        .map_err(|err| err.to_string())
+        .and_then(|_| clipboard::copy_png(&png).map_err(|err| err.to_string()));
+
+    if let Err(err) = copied {
+        eprintln!("Couldn't copy the image to the clipboard: {err}");
+    }
+}
+
+/// Reveals the names of silhouetted pokemon, waiting for enter unless `now` is set.
+///
+/// Without a terminal to wait on, the names stay hidden unless `now` is set.
+fn reveal(pokemons: &[Pokemon], now: bool) {
+    let names: Vec<&str> = pokemons.iter().map(|x| x.name.as_ref()).collect();
+
+    if !now {
+        if !io::stdin().is_terminal() {
+            return;
+        }
+
+        eprint!("Who's that Pokémon? (press enter to reveal)");
+        let _ = io::stderr().flush();
+        let _ = io::stdin().read_line(&mut String::new());
+    }
+
+    eprintln!("It's {}!", names.join(", "));
+}
+
+/// Records the displayed pokemon if usage statistics are enabled,
+/// announcing any achievements this unlocked.
+///
+/// Statistics are best-effort, so any error is ignored rather than failing the display.
+fn record_usage(pokemons: &[Pokemon], list: &List, scope: StateScope) {
+    let Ok(store) = StateStore::open(scope) else {
+        return;
+    };
+
+    // Don't create a state file just to find out recording is disabled
+    if !store.path().exists() || !store.load().is_ok_and(|x| x.usage.enabled) {
+        return;
+    }
+
+    let now = SystemTime::now();
+    let unlocked = store.update(|state| {
+        for pokemon in pokemons {
+            let shiny = pokemon.shiny;
+            state
+                .usage
+                .record(&pokemon.species, shiny, pokemon.random, now);
+        }
+
+        let unlocked = achievements::unlock_new(state, list);
+        if state.usage.notify {
+            unlocked
+        } else {
+            Vec::new()
+        }
+    });
+
+    for achievement in unlocked.unwrap_or_default() {
+        eprintln!(
+            "Achievement unlocked: {} ({})",
+            achievement.name, achievement.description
+        );
+    }
 }
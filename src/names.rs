@@ -0,0 +1,145 @@
+//! Formatting of filenames into display names.
+
+use std::str::FromStr;
+
+/// Error returned when a name output can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown name output: {0}, expected stdout, stderr, none or inline")]
+pub struct ParseNameOutputError(String);
+
+/// Where the names of the pokemon are written
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum NameOutput {
+    /// A line above the sprites on standard output, so it can be captured with them
+    Stdout,
+    /// A line on standard error, so only the sprites are captured
+    #[default]
+    Stderr,
+    /// Nowhere
+    None,
+    /// Beneath each sprite, as part of the output
+    Inline,
+}
+
+impl FromStr for NameOutput {
+    type Err = ParseNameOutputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "stdout" => Ok(NameOutput::Stdout),
+            "stderr" => Ok(NameOutput::Stderr),
+            "none" => Ok(NameOutput::None),
+            "inline" => Ok(NameOutput::Inline),
+            _ => Err(ParseNameOutputError(s.to_string())),
+        }
+    }
+}
+
+/// Names which can't be derived by title casing their filename
+const EXCEPTIONS: &[(&str, &str)] = &[
+    ("nidoran-f", "Nidoran♀"),
+    ("nidoran-m", "Nidoran♂"),
+    ("farfetchd", "Farfetch'd"),
+    ("mr-mime", "Mr. Mime"),
+    ("ho-oh", "Ho-Oh"),
+    ("mime-jr", "Mime Jr."),
+    ("porygon-z", "Porygon-Z"),
+    ("flabebe", "Flabébé"),
+    ("type-null", "Type: Null"),
+    ("jangmo-o", "Jangmo-o"),
+    ("hakamo-o", "Hakamo-o"),
+    ("kommo-o", "Kommo-o"),
+    ("tapu-koko", "Tapu Koko"),
+    ("tapu-lele", "Tapu Lele"),
+    ("tapu-bulu", "Tapu Bulu"),
+    ("tapu-fini", "Tapu Fini"),
+    ("sirfetchd", "Sirfetch'd"),
+    ("mr-rime", "Mr. Rime"),
+];
+
+/// Looks up a name which doesn't follow the usual title casing
+pub fn exception(filename: &str) -> Option<&'static str> {
+    EXCEPTIONS
+        .iter()
+        .find(|(name, _)| *name == filename)
+        .map(|(_, name)| *name)
+}
+
+/// Gender signs and the filename suffixes they stand for
+const GENDER_SIGNS: [(char, &str); 2] = [('♀', "f"), ('♂', "m")];
+
+/// Species whose filename carries a gender suffix
+const GENDERED_SPECIES: [&str; 1] = ["nidoran"];
+
+/// Rewrites gender signs in user input into filename suffixes,
+/// so "Nidoran♀", "nidoran ♀" and "nidoranf" all become "nidoran-f".
+pub fn replace_gender_signs(input: &str) -> String {
+    let mut name = input.trim().to_string();
+
+    for (sign, suffix) in GENDER_SIGNS {
+        name = name.replace(sign, &format!("-{suffix}"));
+    }
+
+    for species in GENDERED_SPECIES {
+        let lower = name.to_lowercase();
+
+        if let Some(rest) = lower.strip_prefix(species) {
+            let rest = rest.trim_start_matches([' ', '_', '-']);
+
+            if GENDER_SIGNS.iter().any(|(_, suffix)| *suffix == rest) {
+                name = format!("{species}-{rest}");
+            }
+        }
+    }
+
+    name
+}
+
+/// Title cases a filename, turning dashes into spaces.
+///
+/// Unlike a generic title caser this leaves apostrophes and
+/// letters following digits (like in "porygon2") untouched.
+pub fn title_case(filename: &str) -> String {
+    filename
+        .split(['-', ' '])
+        .filter(|x| !x.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Formats a normalized filename into a display name.
+///
+/// `lookup` resolves the display name of a species filename.
+/// If the filename has a form suffix (eg. "mr-mime-galar"),
+/// the longest known species prefix is formatted on its own
+/// and the remaining words are title cased.
+pub fn display_name<'a>(filename: &str, lookup: impl Fn(&str) -> Option<&'a str>) -> String {
+    let known = |name: &str| {
+        exception(name)
+            .map(str::to_string)
+            .or_else(|| lookup(name).map(str::to_string))
+    };
+
+    if let Some(name) = known(filename) {
+        return name;
+    }
+
+    let parts: Vec<&str> = filename.split('-').collect();
+
+    for split in (1..parts.len()).rev() {
+        let (base, rest) = parts.split_at(split);
+
+        if let Some(name) = known(&base.join("-")) {
+            return format!("{name} {}", title_case(&rest.join("-")));
+        }
+    }
+
+    title_case(filename)
+}
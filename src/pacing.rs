@@ -0,0 +1,85 @@
+//! Adaptive frame pacing for animated output.
+//!
+//! Writing a frame to a slow terminal (eg. over SSH) can take longer than the
+//! frame itself is shown for. [`FramePacer`] measures how long each frame took
+//! to write and lowers the frame rate when the terminal can't keep up,
+//! giving up on animating entirely when even the lowest rate is too fast.
+
+use std::{env, time::Duration};
+
+/// Slowest frame interval before falling back to static output
+const MAX_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Amount of consecutive fast frames before the frame rate is raised again
+const RECOVERY_FRAMES: u32 = 30;
+
+/// What to do after a frame was written
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Pace {
+    /// Wait this long before writing the next frame
+    Wait(Duration),
+    /// The terminal is too slow to animate, only draw the final frame
+    Static,
+}
+
+/// Decides how long to wait between frames based on how quickly they're written
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    /// Interval of the requested frame rate, never gone below
+    target: Duration,
+    /// The current interval between frames
+    interval: Duration,
+    /// Frames written well within the interval since the last slowdown
+    fast_frames: u32,
+}
+
+impl FramePacer {
+    /// Creates a pacer aiming for `fps` frames per second
+    pub fn new(fps: u32) -> Self {
+        let target = Duration::from_secs(1) / fps.max(1);
+
+        Self {
+            target,
+            interval: target,
+            fast_frames: 0,
+        }
+    }
+
+    /// Creates a pacer for the host terminal,
+    /// starting at half the frame rate over SSH connections
+    pub fn for_host(fps: u32) -> Self {
+        let mut pacer = Self::new(fps);
+
+        if env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some() {
+            pacer.interval *= 2;
+        }
+
+        pacer
+    }
+
+    /// The current interval between frames
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Records that a frame took `elapsed` to write and returns what to do next
+    pub fn frame_written(&mut self, elapsed: Duration) -> Pace {
+        if elapsed > self.interval {
+            self.fast_frames = 0;
+            self.interval *= 2;
+
+            if self.interval > MAX_INTERVAL {
+                return Pace::Static;
+            }
+        } else if elapsed < self.interval / 4 {
+            self.fast_frames += 1;
+
+            if self.fast_frames >= RECOVERY_FRAMES {
+                self.fast_frames = 0;
+                self.interval = (self.interval / 2).max(self.target);
+            }
+        }
+
+        Pace::Wait(self.interval.saturating_sub(elapsed))
+    }
+}
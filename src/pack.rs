@@ -0,0 +1,250 @@
+//! Tools for sprite packs, directories of sprites used with `--sprite-dir`.
+//!
+//! Community packs rarely follow pokeget's layout of `regular/pikachu-alola.png`,
+//! so sprites are identified from their paths and renamed to it.
+
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::list::{normalize_filename, List};
+
+/// Words in paths marking shiny sprites
+const SHINY: &[&str] = &["shiny"];
+
+/// Words in paths marking female sprites
+const FEMALE: &[&str] = &["female"];
+
+/// Words in paths which say nothing about the sprite
+const NOISE: &[&str] = &[
+    "regular", "normal", "default", "male", "sprite", "sprites", "icon",
+];
+
+/// Other spellings of forms, and pokeget's name for them
+const FORM_ALIASES: &[(&str, &str)] = &[
+    ("alolan", "alola"),
+    ("galarian", "galar"),
+    ("hisuian", "hisui"),
+    ("gigantamax", "gmax"),
+    ("megax", "mega-x"),
+    ("megay", "mega-y"),
+];
+
+/// The sprite a file was identified as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    /// Filename of the species
+    pub species: String,
+    /// Form of the pokemon, empty for the base form
+    pub form: String,
+    pub shiny: bool,
+    pub female: bool,
+}
+
+impl Identity {
+    /// Path of the sprite in pokeget's layout, relative to the sprite directory
+    pub fn path(&self) -> String {
+        let filename = match self.form.is_empty() {
+            true => self.species.clone(),
+            false => format!("{}-{}", self.species, self.form),
+        };
+
+        format!(
+            "{}/{}{filename}.png",
+            if self.shiny { "shiny" } else { "regular" },
+            if self.female { "female/" } else { "" },
+        )
+    }
+}
+
+/// Edit distance between two strings
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, x) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, y) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(x != *y);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Finds the species a name most likely refers to, allowing a typo every four letters
+fn closest_species<'a>(name: &str, list: &'a List) -> Option<&'a str> {
+    let compact = |x: &str| x.replace('-', "");
+    let name = compact(name);
+
+    list.filenames()
+        .map(|x| (distance(&name, &compact(x)), x))
+        .filter(|(distance, _)| *distance <= name.len() / 4)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, x)| x)
+}
+
+/// Identifies the sprite at `path`, relative to the root of the pack.
+///
+/// Species are matched by Pokedex ID or by name, preferring the longest exact match
+/// and then the closest name. Whatever follows the species is taken as its form.
+pub fn identify(path: &Path, list: &List) -> Option<Identity> {
+    let words_of = |x: &Path| -> Vec<String> {
+        x.to_string_lossy()
+            .to_lowercase()
+            .split(|x: char| !x.is_alphanumeric())
+            .filter(|x| !x.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+
+    // Directories only say whether sprites are shiny or female, the rest is in the filename
+    let mut words = words_of(Path::new(path.file_stem()?));
+    let all: Vec<String> = path
+        .parent()
+        .map(words_of)
+        .unwrap_or_default()
+        .into_iter()
+        .chain(words.iter().cloned())
+        .collect();
+
+    let shiny = all.iter().any(|x| SHINY.contains(&x.as_str()));
+    let female = all.iter().any(|x| FEMALE.contains(&x.as_str()));
+    words.retain(|x| ![SHINY, FEMALE, NOISE].concat().contains(&x.as_str()));
+
+    let (species, used) = match words.first()?.parse::<usize>() {
+        Ok(id) => (list.get_by_id(id).ok()?.clone(), 1),
+        Err(_) => (1..=words.len())
+            .rev()
+            .find_map(|i| {
+                let name = words[..i].join("-");
+                list.contains(&name).then_some((name, i))
+            })
+            .or_else(|| {
+                (1..=words.len()).rev().find_map(|i| {
+                    closest_species(&words[..i].join("-"), list).map(|x| (x.to_string(), i))
+                })
+            })?,
+    };
+
+    let form: Vec<&str> = words[used..]
+        .iter()
+        .map(|x| {
+            FORM_ALIASES
+                .iter()
+                .find(|(alias, _)| alias == x)
+                .map_or(x.as_str(), |(_, form)| form)
+        })
+        .collect();
+
+    Some(Identity {
+        species,
+        form: normalize_filename(&form.join("-")),
+        shiny,
+        female,
+    })
+}
+
+/// A file to move within a pack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// What normalizing a pack does, with paths relative to the pack
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Plan {
+    /// Files to move to their place in pokeget's layout
+    pub renames: Vec<Rename>,
+    /// Files already in their place
+    pub unchanged: usize,
+    /// Files which couldn't be identified
+    pub unmatched: Vec<PathBuf>,
+    /// Files whose place is taken by another file
+    pub conflicts: Vec<Rename>,
+}
+
+/// Lists the PNG files under `dir`, relative to `root`
+fn png_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            png_files(root, &path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("png"))
+        {
+            files.push(path.strip_prefix(root).unwrap_or(&path).to_path_buf());
+        }
+    }
+
+    Ok(())
+}
+
+/// Works out how to rename the sprites in `dir` to pokeget's layout
+///
+/// # Errors
+///
+/// Returns `io::Error` if the directory can't be read
+pub fn plan(dir: &Path, list: &List) -> io::Result<Plan> {
+    let mut files = Vec::new();
+    png_files(dir, dir, &mut files)?;
+    files.sort();
+
+    let mut plan = Plan::default();
+    // Files already in place keep it, everything else queues up for what's left
+    let mut taken: HashSet<PathBuf> = HashSet::new();
+    let mut moves = Vec::new();
+
+    for file in files {
+        match identify(&file, list) {
+            Some(identity) if file == Path::new(&identity.path()) => {
+                plan.unchanged += 1;
+                taken.insert(file);
+            }
+            Some(identity) => moves.push(Rename {
+                from: file,
+                to: PathBuf::from(identity.path()),
+            }),
+            None => plan.unmatched.push(file),
+        }
+    }
+
+    for rename in moves {
+        if taken.contains(&rename.to) || dir.join(&rename.to).exists() {
+            plan.conflicts.push(rename);
+        } else {
+            taken.insert(rename.to.clone());
+            plan.renames.push(rename);
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Moves the files of a plan into place
+///
+/// # Errors
+///
+/// Returns `io::Error` if a file can't be moved
+pub fn apply(dir: &Path, plan: &Plan) -> io::Result<()> {
+    for rename in &plan.renames {
+        let to = dir.join(&rename.to);
+
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::rename(dir.join(&rename.from), to)?;
+    }
+
+    Ok(())
+}
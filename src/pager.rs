@@ -0,0 +1,75 @@
+//! Paging output taller than the terminal, for `--paged`.
+//!
+//! Output is piped through `$PAGER` when it's set, otherwise it's shown a page at a time
+//! by [`page`], which reads keys straight from the terminal.
+
+use std::{
+    env,
+    fs::File,
+    io::{self, Read, Write},
+    process::{Child, Command, Stdio},
+};
+
+use crate::terminal::RawMode;
+
+/// Shown beneath every page but the last one
+const PROMPT: &str = "\x1b[7m-- More -- (space: next page, enter: next line, q: quit)\x1b[0m";
+
+/// Erases the prompt, leaving the cursor at the start of its line
+const CLEAR_LINE: &str = "\r\x1b[2K";
+
+/// Starts `$PAGER` reading from a pipe, `None` if it isn't set.
+///
+/// Colors are kept by setting `LESS=R` for less, unless `$LESS` is already set.
+pub fn spawn() -> Option<io::Result<Child>> {
+    let pager = env::var("PAGER").ok().filter(|x| !x.trim().is_empty())?;
+
+    let mut command = Command::new("sh");
+    command.args(["-c", &pager]).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "R");
+    }
+
+    Some(command.spawn())
+}
+
+/// Shows `text` a page of `height` lines at a time, waiting for a key between pages
+pub fn page(text: &str, height: usize) -> io::Result<()> {
+    let lines: Vec<&str> = text.lines().collect();
+    // The last line of the terminal is taken up by the prompt
+    let page = height.saturating_sub(1).max(1);
+
+    let mut tty = File::open("/dev/tty")?;
+    let _raw = RawMode::enable();
+    let mut stdout = io::stdout().lock();
+
+    let mut shown = 0;
+    let mut end = page.min(lines.len());
+
+    loop {
+        for line in &lines[shown..end] {
+            writeln!(stdout, "{line}")?;
+        }
+        shown = end;
+
+        if shown == lines.len() {
+            return Ok(());
+        }
+
+        write!(stdout, "{PROMPT}")?;
+        stdout.flush()?;
+
+        let mut key = [0];
+        let read = tty.read(&mut key)?;
+        write!(stdout, "{CLEAR_LINE}")?;
+
+        end = match (read, key[0]) {
+            // Ctrl-C, Ctrl-D or the end of input
+            (0, _) | (_, b'q' | b'Q' | 0x03 | 0x04) => return Ok(()),
+            (_, b' ' | b'f') => shown + page,
+            (_, b'\n' | b'\r' | b'j') => shown + 1,
+            _ => shown,
+        }
+        .min(lines.len());
+    }
+}
@@ -0,0 +1,132 @@
+//! The dominant colors of a sprite, for `pokeget palette`.
+//!
+//! Colors are first split into groups by median cut, then every pixel is moved to the
+//! group whose average it's closest to a few times over, so each group ends up
+//! with the pixels of one color and its shades.
+
+use image::DynamicImage;
+
+use crate::color;
+
+/// Pixels less opaque than this are left out, so edges blended into nothing don't count
+const MIN_ALPHA: u8 = 128;
+
+/// Times the pixels are moved to their closest group after the median cut
+const REFINEMENTS: usize = 8;
+
+/// A dominant color and how much of the sprite it takes up
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Swatch {
+    pub rgb: [u8; 3],
+    /// Amount of pixels closest to this color
+    pub population: usize,
+}
+
+impl Swatch {
+    /// The color as a hex code like `#f8d030`
+    pub fn hex(&self) -> String {
+        color::hex(self.rgb)
+    }
+}
+
+/// Channel with the widest range of values in `pixels`, and that range
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let values = pixels.iter().map(|x| x[channel]);
+            let range = values.clone().max().unwrap_or(0) - values.min().unwrap_or(0);
+            (channel, range)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+/// Average color of `pixels`
+fn average(pixels: &[[u8; 3]]) -> [u8; 3] {
+    let len = pixels.len().max(1) as u64;
+    let mut sum = [0u64; 3];
+    for pixel in pixels {
+        for (total, channel) in sum.iter_mut().zip(pixel) {
+            *total += u64::from(*channel);
+        }
+    }
+
+    sum.map(|x| (x / len) as u8)
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+        .sum()
+}
+
+/// Finds up to `count` dominant colors of an image, the most common first.
+///
+/// Fewer colors are returned if the image doesn't have that many.
+pub fn dominant_colors(image: &DynamicImage, count: usize) -> Vec<Swatch> {
+    let pixels: Vec<[u8; 3]> = image
+        .to_rgba8()
+        .pixels()
+        .filter(|x| x[3] >= MIN_ALPHA)
+        .map(|x| [x[0], x[1], x[2]])
+        .collect();
+
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    // Median cut, splitting the group spanning the most colors with the most pixels each time
+    let mut groups = vec![pixels.clone()];
+    while groups.len() < count {
+        let Some((index, channel)) = groups
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let (channel, range) = widest_channel(x);
+                (i, channel, usize::from(range) * x.len())
+            })
+            .filter(|(_, _, score)| *score > 0)
+            .max_by_key(|(_, _, score)| *score)
+            .map(|(i, channel, _)| (i, channel))
+        else {
+            break;
+        };
+
+        let mut group = groups.swap_remove(index);
+        group.sort_unstable_by_key(|x| x[channel]);
+        let upper = group.split_off(group.len() / 2);
+        groups.push(group);
+        groups.push(upper);
+    }
+
+    let mut colors: Vec<[u8; 3]> = groups.iter().map(|x| average(x)).collect();
+    let mut populations = vec![0; colors.len()];
+
+    for _ in 0..REFINEMENTS {
+        let mut members: Vec<Vec<[u8; 3]>> = vec![Vec::new(); colors.len()];
+        for pixel in &pixels {
+            let closest = (0..colors.len())
+                .min_by_key(|&i| distance(colors[i], *pixel))
+                .unwrap_or(0);
+            members[closest].push(*pixel);
+        }
+
+        populations = members.iter().map(Vec::len).collect();
+        for (color, members) in colors.iter_mut().zip(&members) {
+            if !members.is_empty() {
+                *color = average(members);
+            }
+        }
+    }
+
+    let mut swatches: Vec<Swatch> = colors
+        .into_iter()
+        .zip(populations)
+        .filter(|(_, population)| *population > 0)
+        .map(|(rgb, population)| Swatch { rgb, population })
+        .collect();
+    swatches.sort_by_key(|x| std::cmp::Reverse(x.population));
+
+    swatches
+}
@@ -0,0 +1,157 @@
+//! Locations of pokeget's files on disk.
+//!
+//! Every directory can be overridden with a `POKEGET_*_DIR` variable,
+//! otherwise the platform's conventions are followed (XDG on Linux and other unixes).
+
+use std::{env, ffi::OsString, path::PathBuf};
+
+/// Error returned when a directory can't be resolved
+#[derive(Debug, thiserror::Error)]
+#[error("Could not determine the {0} directory, set ${1}")]
+pub struct PathError(&'static str, &'static str);
+
+/// Kinds of directories pokeget stores files in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Dir {
+    /// User configuration
+    Config,
+    /// Files which can be regenerated at any time
+    Cache,
+    /// Data installed by the user, like sprite packs
+    Data,
+    /// Data written by pokeget itself, like counters and history
+    State,
+}
+
+impl Dir {
+    fn name(self) -> &'static str {
+        match self {
+            Dir::Config => "config",
+            Dir::Cache => "cache",
+            Dir::Data => "data",
+            Dir::State => "state",
+        }
+    }
+
+    /// The variable which overrides the directory entirely
+    fn override_var(self) -> &'static str {
+        match self {
+            Dir::Config => "POKEGET_CONFIG_DIR",
+            Dir::Cache => "POKEGET_CACHE_DIR",
+            Dir::Data => "POKEGET_DATA_DIR",
+            Dir::State => "POKEGET_STATE_DIR",
+        }
+    }
+
+    /// The XDG base directory variable and its default relative to `$HOME`
+    fn xdg(self) -> (&'static str, &'static str) {
+        match self {
+            Dir::Config => ("XDG_CONFIG_HOME", ".config"),
+            Dir::Cache => ("XDG_CACHE_HOME", ".cache"),
+            Dir::Data => ("XDG_DATA_HOME", ".local/share"),
+            Dir::State => ("XDG_STATE_HOME", ".local/state"),
+        }
+    }
+}
+
+/// Resolves a directory for the operating system `os` (as in [`std::env::consts::OS`]),
+/// reading environment variables through `var`.
+pub fn resolve_with(
+    dir: Dir,
+    os: &str,
+    var: impl Fn(&str) -> Option<OsString>,
+) -> Result<PathBuf, PathError> {
+    let non_empty = |key: &str| var(key).filter(|x| !x.is_empty()).map(PathBuf::from);
+    let error = || PathError(dir.name(), dir.override_var());
+
+    if let Some(path) = non_empty(dir.override_var()) {
+        return Ok(path);
+    }
+
+    match os {
+        "windows" => {
+            let base = match dir {
+                Dir::Cache => non_empty("LOCALAPPDATA"),
+                _ => non_empty("APPDATA"),
+            };
+
+            let path = base.ok_or_else(error)?.join("pokeget");
+
+            Ok(match dir {
+                Dir::Cache => path.join("cache"),
+                _ => path,
+            })
+        }
+        "macos" => {
+            let home = non_empty("HOME").ok_or_else(error)?;
+
+            Ok(match dir {
+                Dir::Cache => home.join("Library/Caches/pokeget"),
+                _ => home.join("Library/Application Support/pokeget"),
+            })
+        }
+        _ => {
+            let (key, default) = dir.xdg();
+
+            // Relative XDG paths are invalid according to the spec and should be ignored
+            let base = non_empty(key)
+                .filter(|x| x.is_absolute())
+                .or_else(|| non_empty("HOME").map(|home| home.join(default)));
+
+            Ok(base.ok_or_else(error)?.join("pokeget"))
+        }
+    }
+}
+
+/// Resolves the directory for state shared by every user on the system,
+/// for the operating system `os` (as in [`std::env::consts::OS`]).
+///
+/// Can be overridden with `POKEGET_SYSTEM_STATE_DIR`.
+pub fn resolve_system_with(
+    os: &str,
+    var: impl Fn(&str) -> Option<OsString>,
+) -> Result<PathBuf, PathError> {
+    let non_empty = |key: &str| var(key).filter(|x| !x.is_empty()).map(PathBuf::from);
+
+    if let Some(path) = non_empty("POKEGET_SYSTEM_STATE_DIR") {
+        return Ok(path);
+    }
+
+    match os {
+        "windows" => Ok(non_empty("ProgramData")
+            .ok_or(PathError("system state", "POKEGET_SYSTEM_STATE_DIR"))?
+            .join("pokeget")),
+        "macos" => Ok(PathBuf::from("/Library/Application Support/pokeget")),
+        _ => Ok(PathBuf::from("/var/lib/pokeget")),
+    }
+}
+
+/// Resolves a directory using the real environment
+pub fn resolve(dir: Dir) -> Result<PathBuf, PathError> {
+    resolve_with(dir, env::consts::OS, |key| env::var_os(key))
+}
+
+/// Directory for configuration files
+pub fn config_dir() -> Result<PathBuf, PathError> {
+    resolve(Dir::Config)
+}
+
+/// Directory for cached files
+pub fn cache_dir() -> Result<PathBuf, PathError> {
+    resolve(Dir::Cache)
+}
+
+/// Directory for user installed data
+pub fn data_dir() -> Result<PathBuf, PathError> {
+    resolve(Dir::Data)
+}
+
+/// Directory for state written by pokeget
+pub fn state_dir() -> Result<PathBuf, PathError> {
+    resolve(Dir::State)
+}
+
+/// Directory for state shared by every user on the system
+pub fn system_state_dir() -> Result<PathBuf, PathError> {
+    resolve_system_with(env::consts::OS, |key| env::var_os(key))
+}
@@ -0,0 +1,108 @@
+//! Cosmetic patterns, like Vivillon's wings or the colors of Flabébé's flower.
+//!
+//! Several species can share a pattern's name while drawing it differently,
+//! so the form a pattern maps to is only known once the species is.
+
+use std::str::FromStr;
+
+/// The patterns of each group of species.
+/// The first pattern of a group is the default look, which has no form of its own.
+const PATTERNS: &[(&[&str], &[&str])] = &[
+    (
+        &["vivillon"],
+        &[
+            "meadow",
+            "archipelago",
+            "continental",
+            "elegant",
+            "fancy",
+            "garden",
+            "high-plains",
+            "icy-snow",
+            "jungle",
+            "marine",
+            "modern",
+            "monsoon",
+            "ocean",
+            "poke-ball",
+            "polar",
+            "river",
+            "sandstorm",
+            "savanna",
+            "sun",
+            "tundra",
+        ],
+    ),
+    (
+        &["alcremie"],
+        &[
+            "vanilla-cream",
+            "ruby-cream",
+            "matcha-cream",
+            "mint-cream",
+            "lemon-cream",
+            "salted-cream",
+            "ruby-swirl",
+            "caramel-swirl",
+            "rainbow-swirl",
+        ],
+    ),
+    (
+        &["flabebe", "floette", "florges"],
+        &["red", "yellow", "orange", "blue", "white"],
+    ),
+    (
+        &["minior"],
+        &[
+            "meteor", "red", "orange", "yellow", "green", "blue", "indigo", "violet",
+        ],
+    ),
+];
+
+/// Error returned when a pattern can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown pattern: {0}, run `pokeget forms` on vivillon, alcremie, flabebe or minior to see theirs")]
+pub struct ParsePatternError(String);
+
+/// A cosmetic pattern of one or more species
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Pattern(&'static str);
+
+impl Pattern {
+    /// The name of the pattern, eg. "icy-snow"
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+
+    /// The form of `species`'s sprite with the pattern,
+    /// empty for the default look and `None` if the species doesn't have the pattern
+    pub fn form(&self, species: &str) -> Option<&'static str> {
+        match of(species).iter().position(|x| *x == self.0)? {
+            0 => Some(""),
+            _ => Some(self.0),
+        }
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = ParsePatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace([' ', '_'], "-");
+
+        PATTERNS
+            .iter()
+            .flat_map(|(_, patterns)| patterns.iter())
+            .find(|x| **x == name)
+            .map(|x| Pattern(x))
+            .ok_or_else(|| ParsePatternError(s.to_string()))
+    }
+}
+
+/// The patterns a species has, empty if it has none
+pub fn of(species: &str) -> &'static [&'static str] {
+    PATTERNS
+        .iter()
+        .find(|(group, _)| group.contains(&species))
+        .map_or(&[], |(_, patterns)| patterns)
+}
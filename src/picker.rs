@@ -0,0 +1,241 @@
+//! A fuzzy finder over the names of every pokemon, for `pokeget pick`.
+//!
+//! It's drawn on `/dev/tty` rather than stdout, so the picked pokemon can be captured
+//! by scripts like `pokeget $(pokeget pick) --shiny`.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+};
+
+use crate::{
+    list::List,
+    terminal::{read_keys, HostTerminal, Key, RawMode, Terminal},
+};
+
+/// Lines the terminal is assumed to have when its height isn't known
+const FALLBACK_HEIGHT: u32 = 24;
+
+/// Switches to the alternate screen
+const ENTER: &str = "\x1b[?1049h";
+
+/// Switches back to the normal screen
+const LEAVE: &str = "\x1b[?1049l";
+
+/// Points at the selected match
+const POINTER: &str = "\x1b[1m>\x1b[0m";
+
+/// Scores how well `query` matches `candidate`, `None` if its characters don't all appear in order.
+///
+/// Characters matching right after each other or at the start of a word score higher,
+/// like in fuzzy finders such as skim or fzf.
+/// Also returns the positions of the matched characters in `candidate`.
+pub fn score(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let mut positions = Vec::new();
+    let mut score = 0;
+    let mut previous: Option<(usize, char)> = None;
+    let mut chars = candidate.char_indices().enumerate();
+
+    for wanted in query.chars().filter(|x| !x.is_whitespace()) {
+        let wanted = wanted.to_lowercase().next().unwrap_or(wanted);
+
+        let (i, (index, c)) = chars
+            .by_ref()
+            .find(|(_, (_, c))| c.to_lowercase().next() == Some(wanted))?;
+
+        let word_start = match candidate[..index].chars().next_back() {
+            None => true,
+            Some(before) => {
+                !before.is_alphanumeric() || (before.is_lowercase() && c.is_uppercase())
+            }
+        };
+
+        score += match previous {
+            Some((last, _)) if last + 1 == i => 8,
+            _ if word_start => 6,
+            Some((last, _)) => -((i - last) as i64).min(4),
+            None => -(i as i64).min(4),
+        };
+
+        positions.push(i);
+        previous = Some((i, c));
+    }
+
+    // Shorter names are better matches for the same characters
+    score -= candidate.chars().count() as i64 / 4;
+
+    Some((score, positions))
+}
+
+/// A name matching the query
+struct Match<'a> {
+    /// Filename of the pokemon, which is what's picked
+    filename: &'a str,
+    /// Display name, which is what's matched
+    name: String,
+    score: i64,
+    /// Positions of the matched characters in `name`
+    positions: Vec<usize>,
+}
+
+/// State of the picker
+struct Picker<'a> {
+    list: &'a List,
+    query: String,
+    matches: Vec<Match<'a>>,
+    selected: usize,
+}
+
+impl<'a> Picker<'a> {
+    fn new(list: &'a List) -> Self {
+        let mut picker = Self {
+            list,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        picker.filter(true);
+
+        picker
+    }
+
+    /// Matches the query against the names again.
+    ///
+    /// Once the query is only extended the matches can only get fewer,
+    /// so unless `all` is set only the current ones are searched.
+    fn filter(&mut self, all: bool) {
+        let candidates: Vec<(&'a str, String)> = match all {
+            true => self
+                .list
+                .filenames()
+                .map(|x| (x, self.list.format_name(x)))
+                .collect(),
+            false => self
+                .matches
+                .drain(..)
+                .map(|x| (x.filename, x.name))
+                .collect(),
+        };
+
+        self.matches = candidates
+            .into_iter()
+            .filter_map(|(filename, name)| {
+                let (score, positions) = score(&self.query, &name)?;
+                Some(Match {
+                    filename,
+                    name,
+                    score,
+                    positions,
+                })
+            })
+            .collect();
+
+        // Equally good matches are in Dex order
+        let list = self.list;
+        self.matches
+            .sort_by_key(|x| (std::cmp::Reverse(x.score), list.id_of(x.filename)));
+
+        self.selected = 0;
+    }
+
+    /// Handles a key press, returning what was picked once the picker should close
+    fn handle(&mut self, key: Key) -> Option<Option<String>> {
+        match key {
+            Key::Enter => {
+                let picked = self.matches.get(self.selected);
+                return Some(picked.map(|x| x.filename.to_string()));
+            }
+            Key::Escape | Key::Ctrl('c' | 'g') => return Some(None),
+            Key::Char(c) => {
+                self.query.push(c);
+                self.filter(false);
+            }
+            Key::Backspace => {
+                self.query.pop();
+                self.filter(true);
+            }
+            Key::Ctrl('u') => {
+                self.query.clear();
+                self.filter(true);
+            }
+            // The best match is at the bottom, so going up goes to worse ones
+            Key::Up | Key::Ctrl('p' | 'k') | Key::Tab => {
+                self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1));
+            }
+            Key::Down | Key::Ctrl('n') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    /// Draws the query beneath the matches, the best of which is closest to it
+    fn draw(&self, out: &mut impl Write, height: u32) -> io::Result<()> {
+        let rows = height.saturating_sub(2) as usize;
+        // Scrolls so the selected match is always shown
+        let skip = self.selected.saturating_sub(rows.saturating_sub(1));
+
+        write!(out, "\x1b[H\x1b[2J")?;
+
+        for (row, x) in self.matches.iter().skip(skip).take(rows).enumerate() {
+            write!(out, "\x1b[{};1H", rows - row)?;
+            match skip + row == self.selected {
+                true => write!(out, "{POINTER} ")?,
+                false => write!(out, "  ")?,
+            }
+
+            for (i, c) in x.name.chars().enumerate() {
+                match x.positions.contains(&i) {
+                    true => write!(out, "\x1b[1;32m{c}\x1b[0m")?,
+                    false => write!(out, "{c}")?,
+                }
+            }
+        }
+
+        let total = self.list.filenames().count();
+        write!(
+            out,
+            "\x1b[{};1H  \x1b[2m{}/{total}\x1b[0m",
+            rows + 1,
+            self.matches.len()
+        )?;
+        write!(out, "\x1b[{height};1H> {}", self.query)?;
+
+        out.flush()
+    }
+}
+
+/// Lets the user pick a pokemon by typing part of its name,
+/// returning its filename or `None` if the picker was closed without picking one
+pub fn pick(list: &List) -> io::Result<Option<String>> {
+    let mut input = File::open("/dev/tty")?;
+    let mut output = OpenOptions::new().write(true).open("/dev/tty")?;
+    let _raw = RawMode::enable();
+
+    write!(output, "{ENTER}")?;
+    let result = run(&mut Picker::new(list), &mut input, &mut output);
+    write!(output, "{LEAVE}")?;
+    output.flush()?;
+
+    result
+}
+
+fn run(picker: &mut Picker, input: &mut File, output: &mut File) -> io::Result<Option<String>> {
+    loop {
+        let height = HostTerminal.height().unwrap_or(FALLBACK_HEIGHT);
+        picker.draw(output, height)?;
+
+        let keys = read_keys(input)?;
+        if keys.is_empty() {
+            return Ok(None);
+        }
+
+        for key in keys {
+            if let Some(picked) = picker.handle(key) {
+                return Ok(picked);
+            }
+        }
+    }
+}
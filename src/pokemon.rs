@@ -1,10 +1,16 @@
+use std::{borrow::Cow, collections::HashSet, ops::RangeInclusive, str::FromStr, sync::Arc};
+
 use image::DynamicImage;
 use rand::Rng;
 
 use crate::{
-    cli::Args,
-    list::{sanitize_filename, List, ListError},
-    Data,
+    filters::{self, Effect, Filter},
+    forms::FormIndex,
+    list::{normalize_filename, List, ListError},
+    patterns::{self, Pattern},
+    source::{Sources, SpriteSource},
+    transform::{split_transforms, Transform},
+    types::Type,
 };
 
 /// Error types for Pokemon operations.
@@ -29,10 +35,27 @@ pub enum PokemonError {
     /// Form requires another flag to be set.
     #[error("Form requires another flag: {0}")]
     MissingRequiredFlag(String),
+
+    /// The species has no sprite of the form, the last field lists the ones it has.
+    #[error("{0} not available for {1}; available: {2}")]
+    FormNotAvailable(String, String, String),
+
+    /// The form only exists for other species.
+    #[error("Form '{0}' is only available for {1}")]
+    FormUnavailable(String, String),
+
+    /// The sprites weren't included in this build.
+    #[error("This build of pokeget doesn't include {0} sprites, rebuild it with the `{0}` feature or use --sprite-dir")]
+    MissingSpriteSet(&'static str),
 }
 
 const DEFAULT_SHINY_RATE: u32 = 8192;
 
+/// Error returned when a region name can't be parsed.
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown region: {0}")]
+pub struct ParseRegionError(String);
+
 /// Regions in the Pokémon world
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Region {
@@ -47,6 +70,18 @@ pub enum Region {
 }
 
 impl Region {
+    /// All regions, in order of introduction.
+    pub const ALL: [Region; 8] = [
+        Region::Kanto,
+        Region::Johto,
+        Region::Hoenn,
+        Region::Sinnoh,
+        Region::Unova,
+        Region::Kalos,
+        Region::Alola,
+        Region::Galar,
+    ];
+
     /// Returns the inclusive range of pokemon IDs for the region.
     pub fn range(&self) -> std::ops::RangeInclusive<usize> {
         match self {
@@ -60,6 +95,194 @@ impl Region {
             Region::Galar => 810..=905,
         }
     }
+
+    /// Returns the region a pokemon was introduced in by its dex ID.
+    pub fn of(dex_id: usize) -> Option<Region> {
+        Region::ALL
+            .into_iter()
+            .find(|x| x.range().contains(&dex_id))
+    }
+
+    /// Returns the generation the region was introduced in.
+    pub fn generation(&self) -> u8 {
+        *self as u8 + 1
+    }
+
+    /// Returns the lowercase name of the region.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Region::Kanto => "kanto",
+            Region::Johto => "johto",
+            Region::Hoenn => "hoenn",
+            Region::Sinnoh => "sinnoh",
+            Region::Unova => "unova",
+            Region::Kalos => "kalos",
+            Region::Alola => "alola",
+            Region::Galar => "galar",
+        }
+    }
+}
+
+impl FromStr for Region {
+    type Err = ParseRegionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+
+        Region::ALL
+            .into_iter()
+            .find(|r| r.name() == lower)
+            .ok_or_else(|| ParseRegionError(s.to_string()))
+    }
+}
+
+/// Forms of Unown, in the order of the letters they're parsed from
+const UNOWN_FORMS: [&str; 28] = [
+    "a",
+    "b",
+    "c",
+    "d",
+    "e",
+    "f",
+    "g",
+    "h",
+    "i",
+    "j",
+    "k",
+    "l",
+    "m",
+    "n",
+    "o",
+    "p",
+    "q",
+    "r",
+    "s",
+    "t",
+    "u",
+    "v",
+    "w",
+    "x",
+    "y",
+    "z",
+    "exclamation",
+    "question",
+];
+
+/// Error returned when an Unown letter can't be parsed.
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown letter: {0}, expected a-z, ! or ?")]
+pub struct ParseLetterError(String);
+
+/// A letter Unown can take the shape of
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Letter(usize);
+
+impl Letter {
+    /// The form of Unown's sprite for the letter, eg. "a" or "question"
+    pub fn form(&self) -> &'static str {
+        UNOWN_FORMS[self.0]
+    }
+}
+
+impl FromStr for Letter {
+    type Err = ParseLetterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let form = match lower.as_str() {
+            "!" => "exclamation",
+            "?" => "question",
+            x if x.len() == 1 => x,
+            _ => "",
+        };
+
+        UNOWN_FORMS
+            .iter()
+            .position(|x| *x == form)
+            .map(Letter)
+            .ok_or_else(|| ParseLetterError(s.to_string()))
+    }
+}
+
+/// Forms of Pikachu wearing Ash's caps, each named after the cap with "-cap"
+const CAP_FORMS: [&str; 8] = [
+    "original-cap",
+    "hoenn-cap",
+    "sinnoh-cap",
+    "unova-cap",
+    "kalos-cap",
+    "alola-cap",
+    "partner-cap",
+    "world-cap",
+];
+
+/// Error returned when a cap can't be parsed.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Unknown cap: {0}, expected original, hoenn, sinnoh, unova, kalos, alola, partner or world"
+)]
+pub struct ParseCapError(String);
+
+/// A cap Pikachu can wear
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cap(usize);
+
+impl Cap {
+    /// The form of Pikachu's sprite wearing the cap, eg. "original-cap"
+    pub fn form(&self) -> &'static str {
+        CAP_FORMS[self.0]
+    }
+}
+
+impl FromStr for Cap {
+    type Err = ParseCapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let form = format!("{}-cap", s.trim().to_lowercase().trim_end_matches("-cap"));
+
+        CAP_FORMS
+            .iter()
+            .position(|x| *x == form)
+            .map(Cap)
+            .ok_or_else(|| ParseCapError(s.to_string()))
+    }
+}
+
+/// Forms of Cosplay Pikachu
+const COSPLAY_FORMS: [&str; 6] = ["cosplay", "rock-star", "belle", "pop-star", "phd", "libre"];
+
+/// Error returned when a costume can't be parsed.
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown costume: {0}, expected cosplay, rock-star, belle, pop-star, phd or libre")]
+pub struct ParseCostumeError(String);
+
+/// A costume of Cosplay Pikachu
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Costume(usize);
+
+impl Costume {
+    /// The form of Pikachu's sprite in the costume, eg. "rock-star"
+    pub fn form(&self) -> &'static str {
+        COSPLAY_FORMS[self.0]
+    }
+}
+
+impl FromStr for Costume {
+    type Err = ParseCostumeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let form = s.trim().to_lowercase().replace([' ', '_'], "-");
+        let form = match form.as_str() {
+            "ph-d" | "ph.d" | "ph.d." => "phd",
+            x => x,
+        };
+
+        COSPLAY_FORMS
+            .iter()
+            .position(|x| *x == form)
+            .map(Costume)
+            .ok_or_else(|| ParseCostumeError(s.to_string()))
+    }
 }
 
 /// User selection type
@@ -67,8 +290,13 @@ impl Region {
 pub enum Selection {
     Random,
     Region(Region),
+    Regions(Vec<Region>),
     DexId(usize),
+    /// A range of Dex ID's like "1-151", expanded with [`Selection::expand`]
+    Range(RangeInclusive<usize>),
     Name(String),
+    /// A pokemon picked randomly ahead of time with [`Selection::pick`]
+    Picked(String),
 }
 
 impl Selection {
@@ -79,72 +307,357 @@ impl Selection {
                 0 => Selection::Random,
                 id => Selection::DexId(id),
             }
+        } else if let Some(range) = parse_range(&arg) {
+            Selection::Range(range)
+        } else if arg.eq_ignore_ascii_case("random") {
+            Selection::Random
+        } else if let Ok(region) = arg.parse() {
+            Selection::Region(region)
+        } else if let Ok(regions) = arg.split('+').map(str::parse).collect() {
+            Selection::Regions(regions)
         } else {
-            match arg.to_lowercase().as_str() {
-                "random" => Selection::Random,
-                "kanto" => Selection::Region(Region::Kanto),
-                "johto" => Selection::Region(Region::Johto),
-                "hoenn" => Selection::Region(Region::Hoenn),
-                "sinnoh" => Selection::Region(Region::Sinnoh),
-                "unova" => Selection::Region(Region::Unova),
-                "kalos" => Selection::Region(Region::Kalos),
-                "alola" => Selection::Region(Region::Alola),
-                "galar" => Selection::Region(Region::Galar),
-                _ => Selection::Name(arg),
+            Selection::Name(arg)
+        }
+    }
+
+    /// Restricts a plain random selection to `regions`, unless there are none
+    pub fn within(self, regions: &[Region]) -> Self {
+        match self {
+            Selection::Random if !regions.is_empty() => Selection::Regions(regions.to_vec()),
+            selection => selection,
+        }
+    }
+
+    /// Expands a range into a selection of each Dex ID in it,
+    /// and regions too if `regions` is set. Other selections are returned as they are,
+    /// including invalid ranges so evaluating them reports what's wrong.
+    pub fn expand(self, regions: bool) -> Vec<Self> {
+        match self {
+            Selection::Range(range) if List::global().get_range(&range).is_ok() => {
+                range.map(Selection::DexId).collect()
             }
+            Selection::Region(region) if regions => region.range().map(Selection::DexId).collect(),
+            Selection::Regions(list) if regions => list
+                .iter()
+                .flat_map(Region::range)
+                .map(Selection::DexId)
+                .collect(),
+            selection => vec![selection],
         }
     }
 
-    /// Evaluates the selection to a pokemon filename
-    pub fn eval(self, list: &List) -> Result<String, PokemonError> {
+    /// Whether the selection picks a pokemon randomly
+    pub fn is_random(&self) -> bool {
+        !matches!(self, Selection::DexId(_) | Selection::Name(_))
+    }
+
+    /// Picks the pokemon of a random selection ahead of time, never picking one in `exclude`.
+    /// Selections which aren't random are returned as they are.
+    pub fn pick(
+        self,
+        list: &List,
+        types: &[Type],
+        exclude: &HashSet<String>,
+    ) -> Result<Self, PokemonError> {
+        let regions = match self {
+            Selection::Random => Vec::new(),
+            Selection::Region(region) => vec![region],
+            Selection::Regions(regions) => regions,
+            selection => return Ok(selection),
+        };
+
+        list.random_excluding(&regions, types, exclude)
+            .map(Selection::Picked)
+            .map_err(Into::into)
+    }
+
+    /// Evaluates the selection to a pokemon filename,
+    /// random picks are restricted to pokemon having all of `types`
+    pub fn eval(self, list: &List, types: &[Type]) -> Result<String, PokemonError> {
         match self {
-            Selection::Random => list.random().map_err(Into::into),
-            Selection::Region(region) => list.get_by_region(&region).map_err(Into::into),
+            Selection::Random if types.is_empty() => list.random().map_err(Into::into),
+            Selection::Random => list.random_by_types(&[], types).map_err(Into::into),
+            Selection::Region(region) if types.is_empty() => {
+                list.get_by_region(&region).map_err(Into::into)
+            }
+            Selection::Region(region) => list.random_by_types(&[region], types).map_err(Into::into),
+            Selection::Regions(regions) if types.is_empty() => {
+                list.get_by_regions(&regions).map_err(Into::into)
+            }
+            Selection::Regions(regions) => {
+                list.random_by_types(&regions, types).map_err(Into::into)
+            }
             Selection::DexId(id) => list.get_by_id(id).cloned().map_err(Into::into),
-            Selection::Name(name) => Ok(name),
+            // A range which wasn't expanded picks one pokemon in it, like a region does
+            Selection::Range(range) => {
+                let filenames = list.get_range(&range)?;
+                let i = rand::thread_rng().gen_range(0..filenames.len());
+                Ok(filenames[i].clone())
+            }
+            Selection::Name(name) | Selection::Picked(name) => Ok(name),
         }
     }
 }
 
+/// Parses a range of Dex ID's like "150-151", `None` if `arg` isn't one.
+/// The range isn't checked, so a reversed one like "5-2" is reported as such.
+fn parse_range(arg: &str) -> Option<RangeInclusive<usize>> {
+    let (start, end) = arg.split_once('-')?;
+    let (start, end) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+
+    Some(start..=end)
+}
+
 /// Represents a Pokemon's data
-pub struct Pokemon<'a> {
+#[derive(Clone)]
+pub struct Pokemon {
     pub path: String,
     pub name: String,
+    /// Filename of the species, without any form
+    pub species: String,
+    pub types: Vec<Type>,
     pub sprite: DynamicImage,
-    pub attributes: &'a Attributes,
+    /// Shared between pokemon created with the same attributes
+    pub attributes: Arc<Attributes>,
+    /// Whether the pokemon was picked randomly rather than by name or ID
+    pub random: bool,
+    /// Whether the shiny sprite was used
+    pub shiny: bool,
+    /// Whether the female sprite was used, which random pokemon never are
+    pub female: bool,
+    /// Transforms appended to the argument, applied after the ones in `attributes`
+    pub inline: Vec<Transform>,
+}
+
+impl Pokemon {
+    /// Creates a new Pokemon instance.
+    ///
+    /// Transforms can be appended to `arg` like "pikachu:flip",
+    /// they're applied after the ones in `attributes`.
+    /// Pass an `Arc<Attributes>` to share attributes between several pokemon.
+    pub fn new(
+        arg: String,
+        list: &List,
+        attributes: impl Into<Arc<Attributes>>,
+    ) -> Result<Self, PokemonError> {
+        let attributes: Arc<Attributes> = attributes.into();
+        let (arg, inline) = split_transforms(&arg);
+        let selection = Selection::parse(arg.to_string()).within(&attributes.regions);
+
+        Self::from_selection(selection, &inline, list, attributes)
+    }
+
+    /// Creates a Pokemon from a selection which was already parsed,
+    /// applying `inline` transforms after the ones in `attributes`
+    pub fn from_selection(
+        selection: Selection,
+        inline: &[Transform],
+        list: &List,
+        attributes: impl Into<Arc<Attributes>>,
+    ) -> Result<Self, PokemonError> {
+        Located::from_selection(selection, inline, list, attributes)?.load()
+    }
+
+    /// Reads the sprite from the source again, eg. after it was edited in a sprite directory
+    pub fn reload(&mut self) -> Result<(), PokemonError> {
+        let bytes = self
+            .attributes
+            .source
+            .get(&self.path)
+            .ok_or_else(|| PokemonError::PokemonNotFound(self.name.clone()))?;
+        self.sprite = draw(
+            &image::load_from_memory(&bytes)?,
+            &self.attributes,
+            &self.inline,
+        );
+
+        Ok(())
+    }
+
+    /// Color of the pokemon's first type, used to theme what's drawn around it
+    pub fn accent(&self) -> Option<[u8; 3]> {
+        self.types.first().map(Type::color)
+    }
+}
+
+/// A pokemon whose sprite file was read but not decoded yet,
+/// so a render cached for the file can be used without decoding and drawing it
+#[derive(Clone)]
+pub struct Located {
+    pub path: String,
+    pub name: String,
+    /// Filename of the species, without any form
+    pub species: String,
+    pub types: Vec<Type>,
+    /// The sprite's PNG file
+    pub file: Cow<'static, [u8]>,
+    pub attributes: Arc<Attributes>,
+    pub random: bool,
+    pub shiny: bool,
+    pub female: bool,
+    pub inline: Vec<Transform>,
 }
 
-impl<'a> Pokemon<'a> {
-    /// Creates a new Pokemon instance
-    pub fn new(arg: String, list: &List, attributes: &'a Attributes) -> Result<Self, PokemonError> {
-        let selection = Selection::parse(arg);
-        let is_random = selection == Selection::Random;
-        let is_region = matches!(selection, Selection::Region(_));
-        let name = selection.eval(list)?;
+impl Located {
+    /// Picks the pokemon of a selection and reads its sprite file,
+    /// erroring like [`Pokemon::from_selection`]
+    pub fn from_selection(
+        selection: Selection,
+        inline: &[Transform],
+        list: &List,
+        attributes: impl Into<Arc<Attributes>>,
+    ) -> Result<Self, PokemonError> {
+        let attributes: Arc<Attributes> = attributes.into();
+        let is_random = matches!(selection, Selection::Random | Selection::Picked(_));
+        let is_region = matches!(
+            selection,
+            Selection::Region(_) | Selection::Regions(_) | Selection::Range(_)
+        );
+        let name = selection.eval(list, &attributes.types)?;
+
+        let species = normalize_filename(&name);
+        if !is_random && !is_region && !attributes.allows_species(&species) {
+            return Err(PokemonError::FormUnavailable(
+                attributes.form.clone(),
+                attributes
+                    .form_species
+                    .iter()
+                    .map(|x| list.format_name(x))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
+
+        if let Some(pattern) = attributes.pattern.filter(|_| !is_random && !is_region) {
+            if pattern.form(&species).is_none() {
+                let available = match patterns::of(&species) {
+                    [] => "none".to_string(),
+                    patterns => patterns.join(", "),
+                };
+
+                return Err(PokemonError::FormNotAvailable(
+                    format!("--pattern {}", pattern.name()),
+                    list.format_name(&species),
+                    available,
+                ));
+            }
+        }
 
         let path = attributes.path(&name, is_random, is_region);
-        let bytes = Data::get(&path)
-            .ok_or_else(|| PokemonError::PokemonNotFound(name.clone()))?
-            .data;
+        let bytes = attributes.source.get(&path).ok_or_else(|| {
+            match attributes.shiny && !cfg!(feature = "shiny") {
+                true => PokemonError::MissingSpriteSet("shiny"),
+                false if attributes.mini && !cfg!(feature = "mini") => {
+                    PokemonError::MissingSpriteSet("mini")
+                }
+                false if is_random || is_region => PokemonError::PokemonNotFound(name.clone()),
+                false if attributes.female && !cfg!(feature = "female") => {
+                    PokemonError::MissingSpriteSet("female")
+                }
+                false if !attributes.form_for(&species).is_empty() && !cfg!(feature = "forms") => {
+                    PokemonError::MissingSpriteSet("forms")
+                }
+                false => attributes
+                    .missing_variant(&species, list, FormIndex::embedded())
+                    .unwrap_or_else(|| PokemonError::PokemonNotFound(name.clone())),
+            }
+        })?;
 
-        let img = image::load_from_memory(&bytes)?;
-        let trimmed = showie::trim(&img);
+        let types = list.types_of(&species).unwrap_or_default().to_vec();
 
         Ok(Self {
             path,
             name: list.format_name(&name),
-            sprite: trimmed,
+            species,
+            types,
+            file: bytes,
+            random: is_random || is_region,
+            shiny: attributes.shiny,
+            female: attributes.female && !(is_random || is_region),
+            inline: inline.to_vec(),
             attributes,
         })
     }
+
+    /// Everything besides the file which changes how the sprite is drawn,
+    /// so renders of the same file drawn differently are told apart
+    pub fn drawing(&self) -> String {
+        format!(
+            "{:?} {:?} {:?} {:?}",
+            self.attributes.transforms,
+            self.inline,
+            self.attributes.filters,
+            self.attributes.effects
+        )
+    }
+
+    /// Decodes the sprite and draws it with the transforms, filters and effects
+    pub fn load(self) -> Result<Pokemon, PokemonError> {
+        let sprite = draw(
+            &image::load_from_memory(&self.file)?,
+            &self.attributes,
+            &self.inline,
+        );
+        Ok(self.with_sprite(sprite))
+    }
+
+    /// The pokemon with an empty sprite, for when a cached render takes the place of drawing it
+    pub fn without_sprite(self) -> Pokemon {
+        self.with_sprite(DynamicImage::new_rgba8(0, 0))
+    }
+
+    fn with_sprite(self, sprite: DynamicImage) -> Pokemon {
+        Pokemon {
+            path: self.path,
+            name: self.name,
+            species: self.species,
+            types: self.types,
+            sprite,
+            attributes: self.attributes,
+            random: self.random,
+            shiny: self.shiny,
+            female: self.female,
+            inline: self.inline,
+        }
+    }
+}
+
+/// Trims a sprite and applies the transforms, filters and effects of `attributes` to it,
+/// followed by `inline` transforms
+fn draw(img: &DynamicImage, attributes: &Attributes, inline: &[Transform]) -> DynamicImage {
+    let sprite = attributes
+        .transforms
+        .iter()
+        .chain(inline)
+        .fold(showie::trim(img), |sprite, x| x.apply(&sprite));
+    let sprite = filters::apply_all(sprite, &attributes.filters);
+
+    filters::apply_effects(sprite, &attributes.effects)
 }
 
 /// Pokemon attributes like form and gender
 #[derive(Default)]
 pub struct AttributesBuilder {
     form: String,
+    variants: Vec<&'static str>,
+    letter: Option<Letter>,
+    cap: Option<Cap>,
+    costume: Option<Costume>,
+    therian: bool,
+    origin: bool,
+    crowned: bool,
+    pattern: Option<Pattern>,
+    noble: bool,
     female: bool,
     shiny: bool,
+    mini: bool,
+    back: bool,
+    types: Vec<Type>,
+    regions: Vec<Region>,
+    transforms: Vec<Transform>,
+    filters: Vec<Filter>,
+    effects: Vec<Effect>,
+    source: Sources,
 }
 
 impl AttributesBuilder {
@@ -157,6 +670,90 @@ impl AttributesBuilder {
         self
     }
 
+    /// Enables a variant form, only one of which can be set at a time
+    fn with_variant(mut self, variant: &'static str, enabled: bool) -> Self {
+        if enabled && !self.variants.contains(&variant) {
+            self.variants.push(variant);
+        }
+        self
+    }
+
+    pub fn with_mega(self, mega: bool) -> Self {
+        self.with_variant("mega", mega)
+    }
+
+    pub fn with_mega_x(self, mega_x: bool) -> Self {
+        self.with_variant("mega-x", mega_x)
+    }
+
+    pub fn with_mega_y(self, mega_y: bool) -> Self {
+        self.with_variant("mega-y", mega_y)
+    }
+
+    pub fn with_alolan(self, alolan: bool) -> Self {
+        self.with_variant("alola", alolan)
+    }
+
+    pub fn with_gmax(self, gmax: bool) -> Self {
+        self.with_variant("gmax", gmax)
+    }
+
+    pub fn with_hisui(self, hisui: bool) -> Self {
+        self.with_variant("hisui", hisui)
+    }
+
+    pub fn with_galar(self, galar: bool) -> Self {
+        self.with_variant("galar", galar)
+    }
+
+    /// Uses the form of Unown shaped like `letter`
+    pub fn with_letter(mut self, letter: Option<Letter>) -> Self {
+        self.letter = letter;
+        self
+    }
+
+    /// Uses the form of Pikachu wearing `cap`
+    pub fn with_cap(mut self, cap: Option<Cap>) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Uses the form of Cosplay Pikachu wearing `costume`
+    pub fn with_costume(mut self, costume: Option<Costume>) -> Self {
+        self.costume = costume;
+        self
+    }
+
+    /// Uses the therian forme of the forces of nature, like Landorus
+    pub fn with_therian(mut self, therian: bool) -> Self {
+        self.therian = therian;
+        self
+    }
+
+    /// Uses the origin forme of Giratina, Dialga or Palkia
+    pub fn with_origin(mut self, origin: bool) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Uses the crowned form of Zacian or Zamazenta
+    pub fn with_crowned(mut self, crowned: bool) -> Self {
+        self.crowned = crowned;
+        self
+    }
+
+    /// Uses a cosmetic pattern, like Vivillon's wings
+    pub fn with_pattern(mut self, pattern: Option<Pattern>) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Uses the noble form, defaulting to hisui if no other variant is set
+    pub fn with_noble(mut self, noble: bool) -> Self {
+        self.noble = noble;
+        self
+    }
+
     pub fn with_female(mut self, female: bool) -> Self {
         self.female = female;
         self
@@ -167,32 +764,232 @@ impl AttributesBuilder {
         self
     }
 
+    /// Uses the small menu icons instead of the full sprites
+    pub fn with_mini(mut self, mini: bool) -> Self {
+        self.mini = mini;
+        self
+    }
+
+    /// Uses the sprites of pokemon seen from behind, which only sprite directories provide
+    pub fn with_back(mut self, back: bool) -> Self {
+        self.back = back;
+        self
+    }
+
+    pub fn with_types(mut self, types: &[Type]) -> Self {
+        self.types = types.to_vec();
+        self
+    }
+
+    pub fn with_regions(mut self, regions: &[Region]) -> Self {
+        self.regions = regions.to_vec();
+        self
+    }
+
+    /// Enables or disables a transform applied to every sprite
+    fn with_transform(mut self, transform: Transform, enabled: bool) -> Self {
+        self.transforms.retain(|x| *x != transform);
+        if enabled {
+            self.transforms.push(transform);
+        }
+        self
+    }
+
+    /// Mirrors every sprite horizontally
+    pub fn with_flip(self, flip: bool) -> Self {
+        self.with_transform(Transform::Flip, flip)
+    }
+
+    /// Paints every sprite as a dark silhouette
+    pub fn with_silhouette(self, silhouette: bool) -> Self {
+        self.with_transform(Transform::Silhouette, silhouette)
+    }
+
+    /// Recolors every sprite with `filters`, in order
+    pub fn with_filters(mut self, filters: &[Filter]) -> Self {
+        self.filters = filters.to_vec();
+        self
+    }
+
+    /// Draws a line of `outline` around every sprite
+    pub fn with_outline(mut self, outline: Option<[u8; 3]>) -> Self {
+        self.effects.retain(|x| !matches!(x, Effect::Outline(_)));
+        // Drawn before the shadow, so the outline casts a shadow too
+        if let Some(rgb) = outline {
+            self.effects.insert(0, Effect::Outline(rgb));
+        }
+        self
+    }
+
+    /// Draws a shadow beneath every sprite
+    pub fn with_shadow(mut self, shadow: bool) -> Self {
+        self.effects.retain(|x| *x != Effect::Shadow);
+        if shadow {
+            self.effects.push(Effect::Shadow);
+        }
+        self
+    }
+
+    pub fn with_source(mut self, source: Sources) -> Self {
+        self.source = source;
+        self
+    }
+
     pub fn build(self) -> Result<Attributes, PokemonError> {
+        // Forms which only one species has, and the species which has them
+        let mut variants = self.variants;
+        let mut form_species: &'static [&'static str] = &[];
+        let species_forms: [Option<(&'static str, &'static [&'static str])>; 6] = [
+            self.letter.map(|x| (x.form(), &["unown"][..])),
+            self.cap.map(|x| (x.form(), &["pikachu"][..])),
+            self.costume.map(|x| (x.form(), &["pikachu"][..])),
+            self.therian.then_some((
+                "therian",
+                &["tornadus", "thundurus", "landorus", "enamorus"][..],
+            )),
+            self.origin
+                .then_some(("origin", &["giratina", "dialga", "palkia"][..])),
+            self.crowned
+                .then_some(("crowned", &["zacian", "zamazenta"][..])),
+        ];
+        for (form, species) in species_forms.into_iter().flatten() {
+            variants.push(form);
+            form_species = species;
+        }
+
+        // Check for conflicting form flags
+        let mut form = match variants.as_slice() {
+            [] => self.form,
+            [variant] => variant.to_string(),
+            variants => {
+                return Err(PokemonError::ConflictingForms(format!(
+                    "Multiple form flags specified: {}",
+                    variants.join(", ")
+                )))
+            }
+        };
+
+        if self.noble {
+            form = if form.is_empty() {
+                "hisui-noble".to_string()
+            } else {
+                format!("{form}-noble")
+            };
+        }
+
         // Validate noble form requires hisui
-        if self.form.contains("noble") && !self.form.contains("hisui") {
+        if form.contains("noble") && !form.contains("hisui") {
             return Err(PokemonError::MissingRequiredFlag(
                 "--noble requires --hisui".to_string(),
             ));
         }
 
+        // Patterns decide the form themselves once the species is known
+        if let Some(pattern) = self.pattern.filter(|_| !form.is_empty()) {
+            return Err(PokemonError::ConflictingForms(format!(
+                "--pattern {} can't be combined with form {form}",
+                pattern.name()
+            )));
+        }
+
         Ok(Attributes {
-            form: self.form,
+            form,
+            form_species,
+            pattern: self.pattern,
             female: self.female,
             shiny: self.shiny,
+            mini: self.mini,
+            back: self.back,
+            types: self.types,
+            regions: self.regions,
+            transforms: self.transforms,
+            filters: self.filters,
+            effects: self.effects,
+            source: self.source,
         })
     }
 }
 
+/// The flags setting forms, to name them in errors
+const FORM_FLAGS: &[(&str, &str)] = &[
+    ("mega", "--mega"),
+    ("mega-x", "--mega-x"),
+    ("mega-y", "--mega-y"),
+    ("alola", "--alolan"),
+    ("gmax", "--gmax"),
+    ("hisui", "--hisui"),
+    ("galar", "--galar"),
+    ("hisui-noble", "--noble"),
+    ("therian", "--therian"),
+    ("origin", "--origin"),
+    ("crowned", "--crowned"),
+];
+
+/// Names a form by the flag setting it, eg. "--mega", or "form 'origin'" for other forms
+fn describe_form(form: &str) -> String {
+    if let Some((_, flag)) = FORM_FLAGS.iter().find(|(x, _)| *x == form) {
+        return flag.to_string();
+    }
+
+    if CAP_FORMS.contains(&form) {
+        return format!("--cap {}", form.trim_end_matches("-cap"));
+    }
+
+    if COSPLAY_FORMS.contains(&form) {
+        return format!("--cosplay {form}");
+    }
+
+    format!("form '{form}'")
+}
+
 /// Pokemon attributes
+#[derive(Default)]
 pub struct Attributes {
     pub form: String,
+
+    /// Species the form is limited to, any species if empty
+    pub form_species: &'static [&'static str],
+
+    /// Cosmetic pattern, which takes the place of the form
+    pub pattern: Option<Pattern>,
+
     pub female: bool,
     pub shiny: bool,
+
+    /// Whether the small menu icons are used, found under `mini/` like `mini/regular/pikachu.png`
+    pub mini: bool,
+
+    /// Whether sprites seen from behind are used, found under `back/` like `back/regular/pikachu.png`
+    pub back: bool,
+
+    /// Types which random selections must have
+    pub types: Vec<Type>,
+
+    /// Regions which plain random selections pick from
+    pub regions: Vec<Region>,
+
+    /// Transforms applied to every sprite
+    pub transforms: Vec<Transform>,
+
+    /// Filters recoloring every sprite, applied after the transforms
+    pub filters: Vec<Filter>,
+
+    /// Effects drawn around every sprite, after the filters
+    pub effects: Vec<Effect>,
+
+    /// Where the sprites are loaded from
+    pub source: Sources,
 }
 
 impl Attributes {
-    /// Determines shiny status based on rate
-    fn rate_is_shiny() -> bool {
+    /// Randomly decides whether a pokemon is shiny,
+    /// with a 1 in `$POKEGET_SHINY_RATE` chance (8192 by default).
+    /// Never shiny without the shiny sprites of the `shiny` feature
+    pub fn rate_is_shiny() -> bool {
+        if !cfg!(feature = "shiny") {
+            return false;
+        }
+
         let rate = std::env::var("POKEGET_SHINY_RATE")
             .map(|s| s.parse::<u32>().unwrap_or(DEFAULT_SHINY_RATE))
             .unwrap_or(DEFAULT_SHINY_RATE)
@@ -201,79 +998,98 @@ impl Attributes {
         rand::thread_rng().gen_range(0..rate) == 0
     }
 
-    /// Creates new attributes from CLI args
-    pub fn new(args: &Args) -> Result<Self, PokemonError> {
-        let mut builder = AttributesBuilder::new()
-            .with_female(args.female)
-            .with_shiny(args.shiny || Self::rate_is_shiny());
+    /// Explains why `species` has no sprite with these attributes,
+    /// if `index` has sprites of the species but not of the form or gender
+    fn missing_variant(
+        &self,
+        species: &str,
+        list: &List,
+        index: &FormIndex,
+    ) -> Option<PokemonError> {
+        index.variants(species).next()?;
+        let pokemon = list.format_name(species);
 
-        // Check for conflicting form flags
-        let form_flags = [
-            ("mega", args.mega),
-            ("mega-x", args.mega_x),
-            ("mega-y", args.mega_y),
-            ("alola", args.alolan),
-            ("gmax", args.gmax),
-            ("hisui", args.hisui),
-            ("galar", args.galar),
-        ];
+        let form = self.form_for(species);
+        if !index.has_form(species, form) {
+            let forms = index.forms(species);
+            let available = match forms.is_empty() {
+                true => "none".to_string(),
+                false => forms.join(", "),
+            };
 
-        let active_flags: Vec<&str> = form_flags
-            .iter()
-            .filter(|(_, active)| *active)
-            .map(|(name, _)| *name)
-            .collect();
-
-        match active_flags.len() {
-            0 => {
-                if !args.form.is_empty() {
-                    builder = builder.with_form(&args.form);
-                }
-            }
-            1 => builder = builder.with_form(active_flags[0]),
-            _ => {
-                return Err(PokemonError::ConflictingForms(format!(
-                    "Multiple form flags specified: {}",
-                    active_flags.join(", ")
-                )))
-            }
+            return Some(PokemonError::FormNotAvailable(
+                match self.pattern {
+                    Some(pattern) => format!("--pattern {}", pattern.name()),
+                    None => describe_form(form),
+                },
+                pokemon,
+                available,
+            ));
         }
 
-        if args.noble {
-            let current_form = if builder.form.is_empty() {
-                "hisui-noble".to_string()
-            } else {
-                format!("{}-noble", builder.form)
+        if self.female && !index.variants(species).any(|x| x.female && x.form == form) {
+            let mut forms: Vec<&str> = index
+                .variants(species)
+                .filter(|x| x.female)
+                .map(|x| match x.form.as_str() {
+                    "" => "default",
+                    form => form,
+                })
+                .collect();
+            forms.dedup();
+
+            let available = match forms.is_empty() {
+                true => "none".to_string(),
+                false => forms.join(", "),
             };
-            builder = builder.with_form(&current_form);
+
+            return Some(PokemonError::FormNotAvailable(
+                "--female".to_string(),
+                pokemon,
+                available,
+            ));
+        }
+
+        None
+    }
+
+    /// The form of `species`'s sprite, which depends on the species for patterns
+    fn form_for(&self, species: &str) -> &str {
+        match self.pattern {
+            Some(pattern) => pattern.form(species).unwrap_or_default(),
+            None => &self.form,
         }
+    }
 
-        builder.build()
+    /// Whether the form can be used with `species`
+    pub fn allows_species(&self, species: &str) -> bool {
+        self.form_species.is_empty() || self.form_species.contains(&species)
     }
 
     /// Formats the path for the pokemon sprite
     pub fn path(&self, name: &str, random: bool, region: bool) -> String {
         let mut filename = name.to_owned();
         let is_random = random || region;
+        let form = self.form_for(&normalize_filename(name));
 
-        if !self.form.is_empty() && !is_random {
-            filename.push_str(&format!("-{}", self.form));
+        if !form.is_empty() && !is_random {
+            filename.push_str(&format!("-{form}"));
         }
 
         // Sanitize filename to prevent path traversal
-        let filename = sanitize_filename(&filename.replace([' ', '_'], "-"))
-            .replace(['.', '\'', ':'], "")
-            .to_lowercase();
+        let filename = normalize_filename(&filename);
 
         format!(
-            "{}/{}{}.png",
+            "{}{}{}/{}{}.png",
+            if self.mini { "mini/" } else { "" },
+            if self.back { "back/" } else { "" },
             if self.shiny { "shiny" } else { "regular" },
             if self.female && !is_random {
                 "female/"
             } else {
                 ""
             },
-            filename.trim()
+            filename
         )
     }
 }
@@ -0,0 +1,107 @@
+//! Renderers and color depths for terminals known to pokeget.
+//!
+//! Presets are matched against environment variables set by terminals, like `TERM_PROGRAM`.
+//! Inside tmux, they're matched against the terminal tmux is running in.
+//! The built in presets can be extended or overridden with `terminals.csv` in the config directory,
+//! whose lines are checked first.
+
+use std::{fs, sync::OnceLock};
+
+use crate::{paths, render::Backend, terminal::ColorDepth, tmux};
+
+/// Name of the user's presets file in the config directory
+pub const PRESETS_FILE: &str = "terminals.csv";
+
+/// The defaults for a terminal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    /// Environment variable identifying the terminal
+    pub variable: String,
+    /// Value of the variable, "*" matching any value
+    pub value: String,
+    pub renderer: Backend,
+    pub color_depth: ColorDepth,
+}
+
+// Generated by `build.rs` from `terminals.csv`
+include!(concat!(env!("OUT_DIR"), "/presets.rs"));
+
+impl Preset {
+    /// Builds a preset from the fields of a line, `None` if the renderer or color depth is unknown
+    fn from_fields([variable, value, renderer, depth]: [&str; 4]) -> Option<Self> {
+        Some(Self {
+            variable: variable.to_string(),
+            value: value.to_string(),
+            renderer: renderer.parse().ok()?,
+            color_depth: depth.parse().ok()?,
+        })
+    }
+
+    /// Whether the preset applies to the environment described by `var`
+    pub fn matches(&self, var: &impl Fn(&str) -> Option<String>) -> bool {
+        var(&self.variable).is_some_and(|x| self.value == "*" || x == self.value)
+    }
+}
+
+/// A list of presets, checked in order
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Presets {
+    pub entries: Vec<Preset>,
+}
+
+impl Presets {
+    /// Parses presets from CSV lines of `variable,value,renderer,color depth`.
+    /// None of the fields need commas, so they can't be quoted.
+    ///
+    /// Lines starting with `#` and lines which can't be parsed are skipped,
+    /// since a typo shouldn't keep sprites from being shown.
+    pub fn parse(contents: &str) -> Self {
+        let entries = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                Preset::from_fields(fields.try_into().ok()?)
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    /// The presets shipped with pokeget
+    pub fn builtin() -> Self {
+        Self {
+            entries: BUILTIN
+                .into_iter()
+                .filter_map(Preset::from_fields)
+                .collect(),
+        }
+    }
+
+    /// The user's presets followed by the built in ones
+    pub fn read() -> Self {
+        let mut presets = paths::config_dir()
+            .ok()
+            .and_then(|x| fs::read_to_string(x.join(PRESETS_FILE)).ok())
+            .map(|x| Self::parse(&x))
+            .unwrap_or_default();
+
+        presets.entries.extend(Self::builtin().entries);
+        presets
+    }
+
+    /// Finds the first preset matching the environment described by `var`
+    pub fn find(&self, var: impl Fn(&str) -> Option<String>) -> Option<&Preset> {
+        self.entries.iter().find(|x| x.matches(&var))
+    }
+}
+
+/// The preset of the terminal pokeget is running in, looked up once
+pub fn detect() -> Option<&'static Preset> {
+    static PRESET: OnceLock<Option<Preset>> = OnceLock::new();
+
+    PRESET
+        .get_or_init(|| Presets::read().find(tmux::var).cloned())
+        .as_ref()
+}
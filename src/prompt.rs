@@ -0,0 +1,74 @@
+//! Segments for shell prompts, drawn by `pokeget prompt`.
+//!
+//! Shells count every character of the prompt towards its width unless escape codes are marked
+//! as taking up no space, which is what [`Shell`] does for each of them.
+
+use std::str::FromStr;
+
+/// Error returned when a shell can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown shell: {0}, expected plain, bash or zsh")]
+pub struct ParseShellError(String);
+
+/// The shell a prompt segment is embedded in
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Shell {
+    /// Escape codes are left as they are, eg. for starship which measures them itself
+    #[default]
+    Plain,
+    /// Escape codes are wrapped in the `\x01` and `\x02` markers `\[` and `\]` stand for,
+    /// since bash only reads `\[` in the prompt itself and not in the output of commands
+    Bash,
+    /// Escape codes are wrapped in `%{` and `%}`
+    Zsh,
+}
+
+impl Shell {
+    /// Marks every escape code in `text` as taking up no space in the prompt
+    pub fn escape(self, text: &str) -> String {
+        let (open, close) = match self {
+            Shell::Plain => return text.to_string(),
+            Shell::Bash => ("\x01", "\x02"),
+            Shell::Zsh => ("%{", "%}"),
+        };
+
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                out.push(c);
+                continue;
+            }
+
+            // A CSI sequence runs until its final byte, a character between '@' and '~'
+            out.push_str(open);
+            out.push(c);
+            if chars.next_if_eq(&'[').is_some() {
+                out.push('[');
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            out.push_str(close);
+        }
+
+        out
+    }
+}
+
+impl FromStr for Shell {
+    type Err = ParseShellError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "plain" | "starship" => Ok(Shell::Plain),
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            _ => Err(ParseShellError(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Score keeping for the "Who's that Pokémon?" quiz.
+
+use crate::list::normalize_filename;
+
+/// Score of a quiz
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Quiz {
+    /// Amount of pokemon shown so far
+    pub rounds: u32,
+    /// Amount of pokemon guessed correctly
+    pub correct: u32,
+    /// Correct guesses in a row, up to the current round
+    pub streak: u32,
+    /// Longest streak of the quiz
+    pub best_streak: u32,
+}
+
+impl Quiz {
+    /// Creates a quiz with no rounds played
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `guess` names the pokemon, ignoring case, spacing and punctuation.
+    ///
+    /// Both the species' filename (eg. "mr-mime") and the displayed name (eg. "Mr. Mime") count.
+    pub fn is_correct(species: &str, name: &str, guess: &str) -> bool {
+        let guess = normalize_filename(guess);
+
+        !guess.is_empty() && (guess == species || guess == normalize_filename(name))
+    }
+
+    /// Scores a round, returning whether the guess was correct
+    pub fn answer(&mut self, species: &str, name: &str, guess: &str) -> bool {
+        let correct = Self::is_correct(species, name, guess);
+
+        self.rounds += 1;
+
+        if correct {
+            self.correct += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.streak = 0;
+        }
+
+        correct
+    }
+}
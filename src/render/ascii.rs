@@ -0,0 +1,53 @@
+//! Plain characters without any escape codes, for terminals and files without colors.
+
+use std::fmt;
+
+use image::{DynamicImage, GenericImageView, Rgba};
+
+use super::Renderer;
+
+/// Characters from the darkest to the brightest pixels.
+/// Even the darkest pixels get a character so outlines don't disappear.
+const RAMP: &[u8] = b".:-=+*#%@";
+
+/// Characters picked by brightness, covering two pixels stacked vertically like [`super::HalfBlock`]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Ascii;
+
+/// Perceived brightness of a pixel, from 0 to 255
+fn luma(pixel: Rgba<u8>) -> u32 {
+    (299 * u32::from(pixel[0]) + 587 * u32::from(pixel[1]) + 114 * u32::from(pixel[2])) / 1000
+}
+
+impl Renderer for Ascii {
+    fn write(&self, out: &mut dyn fmt::Write, img: &DynamicImage) -> fmt::Result {
+        let (width, height) = img.dimensions();
+
+        for y in (0..height).step_by(2) {
+            if y != 0 {
+                out.write_char('\n')?;
+            }
+
+            for x in 0..width {
+                let opaque: Vec<u32> = [y, y + 1]
+                    .into_iter()
+                    .filter(|&y| y < height)
+                    .map(|y| img.get_pixel(x, y))
+                    .filter(|x| x[3] != 0)
+                    .map(luma)
+                    .collect();
+
+                if opaque.is_empty() {
+                    out.write_char(' ')?;
+                    continue;
+                }
+
+                let brightness = opaque.iter().sum::<u32>() / opaque.len() as u32;
+                let index = brightness as usize * RAMP.len() / 256;
+                out.write_char(char::from(RAMP[index]))?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//! Writer for sprites as colored braille characters.
+//!
+//! Every character covers a cell of 2x4 pixels, with a dot for each opaque one.
+//! A character can only have one color, so the dots take the average color of their pixels.
+
+use std::fmt;
+
+use image::{DynamicImage, GenericImageView, Rgba};
+
+use super::{
+    halfblock::{write_cell, write_color},
+    Renderer,
+};
+use crate::terminal::ColorDepth;
+
+/// Bit of the braille pattern for the dot at `(x, y)` within a cell
+const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+/// First character of the braille patterns block, with no dots raised
+const BLANK: u32 = 0x2800;
+
+/// Colored braille characters, for finer but single colored dots
+#[derive(Debug, Copy, Clone)]
+pub struct Braille {
+    /// Colors the dots are reduced to
+    pub depth: ColorDepth,
+}
+
+impl Braille {
+    /// Creates a renderer for terminals with colors of `depth`
+    pub fn new(depth: ColorDepth) -> Self {
+        Self { depth }
+    }
+}
+
+impl Default for Braille {
+    fn default() -> Self {
+        Self::new(ColorDepth::TrueColor)
+    }
+}
+
+impl Renderer for Braille {
+    fn write(&self, mut out: &mut dyn fmt::Write, img: &DynamicImage) -> fmt::Result {
+        write_image(&mut out, img, self.depth)
+    }
+
+    fn cell_size(&self) -> (u32, u32) {
+        (2, 4)
+    }
+}
+
+/// Writes an image as text, with every character covering 2x4 pixels
+fn write_image(out: &mut impl fmt::Write, img: &DynamicImage, depth: ColorDepth) -> fmt::Result {
+    let (width, height) = img.dimensions();
+
+    for row in 0..height.div_ceil(4) {
+        if row != 0 {
+            out.write_char('\n')?;
+        }
+
+        for column in 0..width.div_ceil(2) {
+            let mut pattern = 0;
+            let mut sum = [0u32; 3];
+            let mut count = 0;
+
+            for (dx, dots) in DOTS.iter().enumerate() {
+                for (dy, dot) in dots.iter().enumerate() {
+                    let (x, y) = (column * 2 + dx as u32, row * 4 + dy as u32);
+                    if x >= width || y >= height {
+                        continue;
+                    }
+
+                    let pixel = img.get_pixel(x, y);
+                    if pixel[3] == 0 {
+                        continue;
+                    }
+
+                    pattern |= dot;
+                    count += 1;
+                    for (total, channel) in sum.iter_mut().zip(pixel.0) {
+                        *total += u32::from(channel);
+                    }
+                }
+            }
+
+            if count == 0 {
+                out.write_char(' ')?;
+                continue;
+            }
+
+            let [r, g, b] = sum.map(|x| (x / count) as u8);
+            write_color(out, Rgba([r, g, b, 255]), depth, false)?;
+            write_cell(out, char::from_u32(BLANK + pattern).unwrap_or(' '), depth)?;
+        }
+    }
+
+    Ok(())
+}
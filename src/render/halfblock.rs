@@ -0,0 +1,122 @@
+//! Streaming writer for sprites as colored half block characters.
+//!
+//! In truecolor terminals this produces the same output as `showie::to_ascii`,
+//! but writes straight into any [`fmt::Write`] instead of building a string for every escape code.
+
+use std::fmt;
+
+use image::{DynamicImage, GenericImageView, Rgba};
+
+use super::Renderer;
+use crate::{color, terminal::ColorDepth};
+
+/// Colored half block characters, the default renderer.
+///
+/// Every character covers two pixels stacked vertically, each with their own color.
+#[derive(Debug, Copy, Clone)]
+pub struct HalfBlock {
+    /// Colors the pixels are reduced to
+    pub depth: ColorDepth,
+}
+
+impl HalfBlock {
+    /// Creates a renderer for terminals with colors of `depth`
+    pub fn new(depth: ColorDepth) -> Self {
+        Self { depth }
+    }
+}
+
+impl Default for HalfBlock {
+    fn default() -> Self {
+        Self::new(ColorDepth::TrueColor)
+    }
+}
+
+impl Renderer for HalfBlock {
+    fn write(&self, mut out: &mut dyn fmt::Write, img: &DynamicImage) -> fmt::Result {
+        write_image(&mut out, img, self.depth)
+    }
+}
+
+/// Writes the color escape code of a pixel
+pub(super) fn write_color(
+    out: &mut impl fmt::Write,
+    pixel: Rgba<u8>,
+    depth: ColorDepth,
+    background: bool,
+) -> fmt::Result {
+    let [r, g, b, _] = pixel.0;
+    color::write_color(out, [r, g, b], depth, background)
+}
+
+/// Writes a character followed by a reset of its colors, if it has any
+pub(super) fn write_cell(out: &mut impl fmt::Write, c: char, depth: ColorDepth) -> fmt::Result {
+    out.write_char(c)?;
+
+    match depth {
+        ColorDepth::Monochrome => Ok(()),
+        _ => out.write_str(color::RESET),
+    }
+}
+
+/// Writes an image as text, with every character covering two pixels stacked vertically
+fn write_image(out: &mut impl fmt::Write, img: &DynamicImage, depth: ColorDepth) -> fmt::Result {
+    let (width, height) = img.dimensions();
+
+    for y in (0..height.saturating_sub(1)).step_by(2) {
+        if y != 0 {
+            out.write_char('\n')?;
+        }
+
+        for x in 0..width {
+            let top = img.get_pixel(x, y);
+            let bottom = img.get_pixel(x, y + 1);
+
+            match (top[3] != 0, bottom[3] != 0) {
+                (false, false) => {
+                    out.write_char(' ')?;
+                    continue;
+                }
+                // Without colors both halves look the same
+                (true, true) if depth == ColorDepth::Monochrome => {
+                    out.write_char('█')?;
+                    continue;
+                }
+                (true, true) => {
+                    write_color(out, top, depth, true)?;
+                    write_color(out, bottom, depth, false)?;
+                }
+                (false, true) => write_color(out, bottom, depth, false)?,
+                (true, false) => write_color(out, top, depth, false)?,
+            }
+
+            if top[3] != 0 && bottom[3] == 0 {
+                write_cell(out, '▀', depth)?;
+            } else {
+                write_cell(out, '▄', depth)?;
+            }
+        }
+    }
+
+    // The bottom row of an image with an odd height has no pixel beneath it,
+    // and is the only row of an image one pixel tall
+    if height % 2 != 0 {
+        if height > 1 {
+            out.write_char('\n')?;
+        }
+
+        for x in 0..width {
+            let pixel = img.get_pixel(x, height - 1);
+
+            if pixel[3] == 0 {
+                out.write_char(' ')?;
+                continue;
+            }
+
+            write_color(out, pixel, depth, false)?;
+            write_cell(out, '▀', depth)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! HTML snippets for embedding sprites in web pages, for `--format html`.
+//!
+//! Sprites are drawn with half blocks like [`super::HalfBlock`], but colored with the styles
+//! of `<span>` elements instead of escape codes. Runs of characters in the same colors share
+//! a span, and the whole sprite sits in a `<pre>` with lines tall enough for the blocks to touch.
+
+use std::fmt;
+
+use image::{DynamicImage, GenericImageView, Rgba};
+
+use super::Renderer;
+use crate::color;
+
+/// Opens the block the sprite is drawn in
+const PRE_START: &str =
+    "<pre style=\"font-family:monospace;line-height:1;letter-spacing:0;margin:0\">";
+
+/// Colors of a character, as CSS declarations
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Style {
+    /// A transparent cell, written without a span
+    None,
+    Span(String),
+}
+
+/// Colored spans of half blocks inside a `<pre>` element
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Html;
+
+fn css(pixel: Rgba<u8>) -> String {
+    color::hex([pixel[0], pixel[1], pixel[2]])
+}
+
+/// The character and style of the cell covering `top` and `bottom`, either of which may be missing
+fn cell(top: Option<Rgba<u8>>, bottom: Option<Rgba<u8>>) -> (char, Style) {
+    let top = top.filter(|x| x[3] != 0);
+    let bottom = bottom.filter(|x| x[3] != 0);
+
+    match (top, bottom) {
+        (None, None) => (' ', Style::None),
+        (Some(top), None) => ('▀', Style::Span(format!("color:{}", css(top)))),
+        (None, Some(bottom)) => ('▄', Style::Span(format!("color:{}", css(bottom)))),
+        (Some(top), Some(bottom)) => (
+            '▄',
+            Style::Span(format!("color:{};background:{}", css(bottom), css(top))),
+        ),
+    }
+}
+
+impl Renderer for Html {
+    fn write(&self, out: &mut dyn fmt::Write, img: &DynamicImage) -> fmt::Result {
+        let (width, height) = img.dimensions();
+        let pixel = |x, y| (y < height).then(|| img.get_pixel(x, y));
+
+        out.write_str(PRE_START)?;
+
+        for y in (0..height).step_by(2) {
+            if y != 0 {
+                out.write_char('\n')?;
+            }
+
+            let mut current = Style::None;
+            for x in 0..width {
+                let (c, style) = cell(pixel(x, y), pixel(x, y + 1));
+
+                if style != current {
+                    if current != Style::None {
+                        out.write_str("</span>")?;
+                    }
+                    if let Style::Span(css) = &style {
+                        write!(out, "<span style=\"{css}\">")?;
+                    }
+                    current = style;
+                }
+
+                out.write_char(c)?;
+            }
+
+            if current != Style::None {
+                out.write_str("</span>")?;
+            }
+        }
+
+        out.write_str("</pre>")
+    }
+}
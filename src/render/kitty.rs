@@ -0,0 +1,65 @@
+//! The kitty graphics protocol, drawing actual pixels in terminals like kitty and ghostty.
+
+use std::fmt;
+
+use image::DynamicImage;
+
+use super::{write_png, Renderer};
+
+/// Largest amount of base64 encoded data in a single escape sequence
+const CHUNK_SIZE: usize = 4096;
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Images sent as PNGs, scaled by the terminal to the size they'd have with half blocks
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Kitty;
+
+/// Encodes bytes as padded base64
+fn base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, x)| n | u32::from(*x) << (16 - 8 * i));
+
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(char::from(BASE64[(n >> (18 - 6 * i) & 63) as usize])),
+                false => out.push('='),
+            }
+        }
+    }
+
+    out
+}
+
+impl Renderer for Kitty {
+    fn write(&self, out: &mut dyn fmt::Write, img: &DynamicImage) -> fmt::Result {
+        let mut png = Vec::new();
+        write_png(&mut png, img).map_err(|_| fmt::Error)?;
+
+        let data = base64(&png);
+        let chunks: Vec<&[u8]> = data.as_bytes().chunks(CHUNK_SIZE).collect();
+        let (columns, rows) = (img.width(), img.height().div_ceil(2));
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            let more = u8::from(i + 1 < chunks.len());
+            // Base64 is plain ASCII, so chunks never split a character
+            let chunk = std::str::from_utf8(chunk).map_err(|_| fmt::Error)?;
+
+            match i {
+                // q=2 keeps the terminal from answering on stdin
+                0 => write!(
+                    out,
+                    "\x1b_Ga=T,f=100,q=2,c={columns},r={rows},m={more};{chunk}\x1b\\"
+                )?,
+                _ => write!(out, "\x1b_Gm={more};{chunk}\x1b\\")?,
+            }
+        }
+
+        Ok(())
+    }
+}
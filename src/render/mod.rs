@@ -0,0 +1,257 @@
+//! Ways of drawing images in a terminal.
+//!
+//! Every [`Renderer`] turns an image into text, be it characters with color escape codes
+//! or the escape sequences of a terminal graphics protocol.
+//! [`Backend`] picks one by name or from the terminal's capabilities.
+
+mod ascii;
+mod braille;
+mod halfblock;
+mod html;
+mod kitty;
+mod quadrant;
+mod sixel;
+
+use std::{
+    fmt,
+    io::{self, Cursor},
+    str::FromStr,
+};
+
+use image::{DynamicImage, ImageError, ImageOutputFormat};
+
+pub use ascii::Ascii;
+pub use braille::Braille;
+pub use halfblock::HalfBlock;
+pub use html::Html;
+pub use kitty::Kitty;
+pub use quadrant::Quadrant;
+pub use sixel::Sixel;
+
+use crate::{
+    color,
+    terminal::{ColorDepth, Protocol, Terminal, VirtualTerminal},
+};
+
+/// Draws images as text
+pub trait Renderer {
+    /// Writes an image as text into `out`
+    fn write(&self, out: &mut dyn fmt::Write, img: &DynamicImage) -> fmt::Result;
+
+    /// Width and height of the pixels covered by a single character
+    fn cell_size(&self) -> (u32, u32) {
+        (1, 2)
+    }
+
+    /// Renders an image as text
+    fn render(&self, img: &DynamicImage) -> String {
+        let mut out = String::new();
+        // Writing into a string never fails
+        let _ = self.write(&mut out, img);
+
+        out
+    }
+
+    /// Amount of characters needed for `width` pixels
+    fn columns(&self, width: u32) -> u32 {
+        width.div_ceil(self.cell_size().0)
+    }
+
+    /// The terminal as sprites see it, one column per horizontal pixel.
+    ///
+    /// Sprites are laid out and downscaled for this rather than the actual terminal.
+    fn canvas(&self, terminal: &dyn Terminal) -> VirtualTerminal {
+        let mut canvas = VirtualTerminal::probe(terminal);
+        canvas.width = canvas.width.saturating_mul(self.cell_size().0);
+        canvas
+    }
+
+    /// Amount of half block lines taking up as many pixels as `lines` lines of this renderer
+    fn half_block_lines(&self, lines: u32) -> u32 {
+        lines.saturating_mul(self.cell_size().1) / 2
+    }
+}
+
+/// Error returned when an output format can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown format: {0}, expected text, png, svg or html")]
+pub struct ParseFormatError(String);
+
+/// What is written to stdout
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Sprites drawn by a [`Renderer`]
+    #[default]
+    Text,
+    /// The combined sprites as a PNG image
+    Png,
+    /// The combined sprites as an SVG image, see [`write_svg`]
+    Svg,
+    /// The combined sprites as an HTML snippet, see [`Html`]
+    Html,
+}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "png" | "png-stdout" => Ok(Format::Png),
+            "svg" => Ok(Format::Svg),
+            "html" => Ok(Format::Html),
+            _ => Err(ParseFormatError(s.to_string())),
+        }
+    }
+}
+
+/// Encodes an image as PNG into `out`
+pub fn write_png(out: &mut impl io::Write, image: &DynamicImage) -> Result<(), ImageError> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)?;
+    out.write_all(&png)?;
+
+    Ok(())
+}
+
+/// Writes an image as SVG into `out`, drawing every pixel as a square.
+///
+/// Runs of pixels of the same color in a row are merged into one rectangle,
+/// and the edges are kept crisp so the image can be scaled to any size.
+pub fn write_svg(out: &mut impl io::Write, image: &DynamicImage) -> io::Result<()> {
+    let rgba = image.to_rgba8();
+    let (width, height) = rgba.dimensions();
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {width} {height}\" \
+         width=\"{width}\" height=\"{height}\" shape-rendering=\"crispEdges\">"
+    )?;
+
+    for y in 0..height {
+        let mut x = 0;
+
+        while x < width {
+            let pixel = *rgba.get_pixel(x, y);
+            let run = (x..width)
+                .take_while(|&x| *rgba.get_pixel(x, y) == pixel)
+                .count() as u32;
+
+            if pixel[3] != 0 {
+                let fill = color::hex([pixel[0], pixel[1], pixel[2]]);
+                write!(
+                    out,
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"{run}\" height=\"1\" fill=\"{fill}\""
+                )?;
+                if pixel[3] != 255 {
+                    write!(out, " fill-opacity=\"{:.3}\"", f32::from(pixel[3]) / 255.0)?;
+                }
+                writeln!(out, "/>")?;
+            }
+
+            x += run;
+        }
+    }
+
+    writeln!(out, "</svg>")
+}
+
+/// Error returned when a renderer can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Unknown renderer: {0}, expected auto, halfblock, quadrant, braille, ascii, sixel or kitty"
+)]
+pub struct ParseBackendError(String);
+
+/// The renderers to choose from
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// Picked from the terminal's capabilities with [`Backend::detect`]
+    Auto,
+    /// Half block characters, see [`HalfBlock`]
+    #[default]
+    HalfBlock,
+    /// Quadrant blocks, see [`Quadrant`]
+    Quadrant,
+    /// Braille dots, see [`Braille`]
+    Braille,
+    /// Plain characters without colors, see [`Ascii`]
+    Ascii,
+    /// DEC sixel graphics, see [`Sixel`]
+    Sixel,
+    /// The kitty graphics protocol, see [`Kitty`]
+    Kitty,
+}
+
+impl Backend {
+    /// Picks the best backend for a terminal, preferring the one from its preset.
+    ///
+    /// Escape codes are only used when writing to the terminal itself,
+    /// since they end up as garbage in files and pipes.
+    pub fn detect(terminal: &dyn Terminal, interactive: bool) -> Self {
+        let depth = terminal.color_depth();
+
+        match (terminal.renderer(), terminal.protocol(), depth) {
+            _ if !interactive => Backend::Ascii,
+            // Characters drawn without colors look better as plain ascii, whatever the preset
+            (Some(backend), _, _) if backend.is_graphics() || depth != ColorDepth::Monochrome => {
+                backend
+            }
+            (_, Protocol::Kitty, _) => Backend::Kitty,
+            (_, Protocol::Sixel, _) => Backend::Sixel,
+            (_, _, ColorDepth::Monochrome) => Backend::Ascii,
+            _ => Backend::HalfBlock,
+        }
+    }
+
+    /// Resolves [`Backend::Auto`], leaving other backends as they are
+    pub fn resolve(self, terminal: &dyn Terminal, interactive: bool) -> Self {
+        match self {
+            Backend::Auto => Self::detect(terminal, interactive),
+            backend => backend,
+        }
+    }
+
+    /// Whether the backend draws pixels through a graphics protocol instead of characters
+    pub fn is_graphics(self) -> bool {
+        matches!(self, Backend::Sixel | Backend::Kitty)
+    }
+
+    /// The backend for output mixed with text, where graphics can't be placed next to text
+    pub fn for_text(self) -> Self {
+        match self.is_graphics() {
+            true => Backend::HalfBlock,
+            false => self,
+        }
+    }
+
+    /// The renderer of the backend for terminals with colors of `depth`,
+    /// [`Backend::Auto`] renders half blocks
+    pub fn renderer(self, depth: ColorDepth) -> Box<dyn Renderer> {
+        match self {
+            Backend::Auto | Backend::HalfBlock => Box::new(HalfBlock::new(depth)),
+            Backend::Quadrant => Box::new(Quadrant::new(depth)),
+            Backend::Braille => Box::new(Braille::new(depth)),
+            Backend::Ascii => Box::new(Ascii),
+            Backend::Sixel => Box::new(Sixel),
+            Backend::Kitty => Box::new(Kitty),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = ParseBackendError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(Backend::Auto),
+            "halfblock" | "half-block" | "blocks" => Ok(Backend::HalfBlock),
+            "quadrant" | "quadrants" => Ok(Backend::Quadrant),
+            "braille" => Ok(Backend::Braille),
+            "ascii" => Ok(Backend::Ascii),
+            "sixel" => Ok(Backend::Sixel),
+            "kitty" => Ok(Backend::Kitty),
+            _ => Err(ParseBackendError(s.to_string())),
+        }
+    }
+}
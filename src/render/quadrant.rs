@@ -0,0 +1,135 @@
+//! Writer for sprites as colored quadrant block characters.
+//!
+//! Every character covers a cell of 2x2 pixels, split between a foreground and a background color.
+//! Cells with more than two colors have their pixels grouped with the closest of the two.
+
+use std::fmt;
+
+use image::{DynamicImage, GenericImageView, Rgba};
+
+use super::{
+    halfblock::{write_cell, write_color},
+    Renderer,
+};
+use crate::terminal::ColorDepth;
+
+/// Quadrant characters by the quadrants they fill: top left, top right, bottom left, bottom right
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// Colored quadrant block characters, halving sprites in both directions
+#[derive(Debug, Copy, Clone)]
+pub struct Quadrant {
+    /// Colors the pixels are reduced to
+    pub depth: ColorDepth,
+}
+
+impl Quadrant {
+    /// Creates a renderer for terminals with colors of `depth`
+    pub fn new(depth: ColorDepth) -> Self {
+        Self { depth }
+    }
+}
+
+impl Default for Quadrant {
+    fn default() -> Self {
+        Self::new(ColorDepth::TrueColor)
+    }
+}
+
+impl Renderer for Quadrant {
+    fn write(&self, mut out: &mut dyn fmt::Write, img: &DynamicImage) -> fmt::Result {
+        write_image(&mut out, img, self.depth)
+    }
+
+    fn cell_size(&self) -> (u32, u32) {
+        (2, 2)
+    }
+}
+
+/// Squared distance between two colors
+fn distance(a: Rgba<u8>, b: Rgba<u8>) -> u32 {
+    a.0.iter()
+        .zip(b.0)
+        .take(3)
+        .map(|(x, y)| u32::from(x.abs_diff(y)).pow(2))
+        .sum()
+}
+
+/// Average color of some pixels
+fn average(pixels: impl Iterator<Item = Rgba<u8>>) -> Rgba<u8> {
+    let mut sum = [0u32; 3];
+    let mut count = 0;
+
+    for pixel in pixels {
+        count += 1;
+        for (total, channel) in sum.iter_mut().zip(pixel.0) {
+            *total += u32::from(channel);
+        }
+    }
+
+    let [r, g, b] = sum.map(|x| (x / count.max(1)) as u8);
+    Rgba([r, g, b, 255])
+}
+
+/// Writes an image as text, with every character covering 2x2 pixels
+fn write_image(out: &mut impl fmt::Write, img: &DynamicImage, depth: ColorDepth) -> fmt::Result {
+    let (width, height) = img.dimensions();
+
+    for row in 0..height.div_ceil(2) {
+        if row != 0 {
+            out.write_char('\n')?;
+        }
+
+        for column in 0..width.div_ceil(2) {
+            // Opaque pixels of the cell, with their bit in QUADRANTS
+            let pixels: Vec<(usize, Rgba<u8>)> = (0..4)
+                .filter_map(|i| {
+                    let (x, y) = (column * 2 + i % 2, row * 2 + i / 2);
+                    if x >= width || y >= height {
+                        return None;
+                    }
+
+                    let pixel = img.get_pixel(x, y);
+                    (pixel[3] != 0).then_some((1 << i, pixel))
+                })
+                .collect();
+
+            if pixels.is_empty() {
+                out.write_char(' ')?;
+                continue;
+            }
+
+            let opaque = pixels.iter().fold(0, |mask, (bit, _)| mask | bit);
+
+            // Transparent pixels already take the background, and it can't be colored without colors
+            if opaque != 0b1111 || depth == ColorDepth::Monochrome {
+                write_color(out, average(pixels.iter().map(|x| x.1)), depth, false)?;
+                write_cell(out, QUADRANTS[opaque], depth)?;
+                continue;
+            }
+
+            // Split the pixels between the two which are furthest apart
+            let (a, b) = pixels
+                .iter()
+                .flat_map(|a| pixels.iter().map(move |b| (a.1, b.1)))
+                .max_by_key(|(a, b)| distance(*a, *b))
+                .unwrap_or((pixels[0].1, pixels[0].1));
+
+            let (foreground, background): (Vec<_>, Vec<_>) = pixels
+                .iter()
+                .partition(|(_, x)| distance(*x, a) <= distance(*x, b));
+
+            let mask = foreground.iter().fold(0, |mask, (bit, _)| mask | bit);
+
+            if !background.is_empty() {
+                write_color(out, average(background.iter().map(|x| x.1)), depth, true)?;
+            }
+            write_color(out, average(foreground.iter().map(|x| x.1)), depth, false)?;
+            write_cell(out, QUADRANTS[mask], depth)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! DEC sixel graphics, drawing actual pixels in terminals like foot, mlterm and WezTerm.
+
+use std::{collections::BTreeMap, fmt};
+
+use image::{DynamicImage, GenericImageView, Rgba};
+
+use super::Renderer;
+
+/// Screen pixels drawn for every sprite pixel, so sprites are about as large
+/// as with half blocks in a terminal with 8x16 pixel characters
+const SCALE: u32 = 8;
+
+/// Sixels are six pixels tall
+const BAND_HEIGHT: u32 = 6;
+
+/// Sixel graphics, with colors reduced to a 6x6x6 color cube
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Sixel;
+
+/// Index of the color closest to a pixel in the color cube
+fn palette_index(pixel: Rgba<u8>) -> u8 {
+    let level = |x: u8| (u16::from(x) * 5 + 127) / 255;
+    (level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2])) as u8
+}
+
+/// Writes the sixels of a line, repeating runs with `!`
+fn write_run_length(out: &mut dyn fmt::Write, sixels: &[u8]) -> fmt::Result {
+    let mut i = 0;
+
+    while i < sixels.len() {
+        let run = sixels[i..].iter().take_while(|x| **x == sixels[i]).count();
+        let c = char::from(63 + sixels[i]);
+
+        match run {
+            1..=3 => (0..run).try_for_each(|_| out.write_char(c))?,
+            _ => write!(out, "!{run}{c}")?,
+        }
+
+        i += run;
+    }
+
+    Ok(())
+}
+
+impl Renderer for Sixel {
+    fn write(&self, out: &mut dyn fmt::Write, img: &DynamicImage) -> fmt::Result {
+        let (width, height) = (img.width() * SCALE, img.height() * SCALE);
+        let pixel = |x: u32, y: u32| img.get_pixel(x / SCALE, y / SCALE);
+
+        // Transparent pixels are left untouched with the second parameter set to 1
+        write!(out, "\x1bP0;1;0q\"1;1;{width};{height}")?;
+
+        let mut defined = [false; 216];
+
+        for band in 0..height.div_ceil(BAND_HEIGHT) {
+            let mut colors: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+
+            for dy in 0..BAND_HEIGHT {
+                let y = band * BAND_HEIGHT + dy;
+                if y >= height {
+                    break;
+                }
+
+                for x in 0..width {
+                    let pixel = pixel(x, y);
+                    if pixel[3] < 128 {
+                        continue;
+                    }
+
+                    let index = palette_index(pixel);
+                    colors
+                        .entry(index)
+                        .or_insert_with(|| vec![0; width as usize])[x as usize] |= 1 << dy;
+                }
+            }
+
+            for (index, sixels) in &colors {
+                if !defined[usize::from(*index)] {
+                    defined[usize::from(*index)] = true;
+
+                    let level = |x: u8| u32::from(x) * 100 / 5;
+                    let (r, g, b) = (index / 36, index / 6 % 6, index % 6);
+                    write!(out, "#{index};2;{};{};{}", level(r), level(g), level(b))?;
+                }
+
+                write!(out, "#{index}")?;
+                write_run_length(out, sixels)?;
+                // Return to the start of the band for the next color
+                out.write_char('$')?;
+            }
+
+            out.write_char('-')?;
+        }
+
+        out.write_str("\x1b\\")
+    }
+}
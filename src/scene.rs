@@ -0,0 +1,349 @@
+//! Scene files for `--scene`, placing sprites at fixed positions on a canvas.
+//!
+//! They're written in a small subset of TOML: a `[canvas]` table with its size in pixels
+//! and an optional background, followed by a `[[sprite]]` table for every sprite.
+//!
+//! ```toml
+//! [canvas]
+//! width = 96
+//! height = 48
+//! background = "#202020"
+//!
+//! [[sprite]]
+//! pokemon = "raichu"
+//! x = 4
+//! y = 8
+//! alolan = true
+//!
+//! [[sprite]]
+//! pokemon = "pikachu"
+//! x = 48
+//! z = 1        # drawn in front of sprites on lower layers
+//! flip = true
+//! scale = 0.5
+//! ```
+//!
+//! Sprites take the same attributes as lines of a spec file, set to `true`,
+//! and `form = "<form>"` for any other form.
+//!
+//! Only what's needed for that is read: tables, `key = value` pairs on one line each,
+//! basic strings, integers, floats, booleans and `#` comments. Arrays, inline tables,
+//! dotted keys, literal and multiline strings aren't supported.
+//!
+//! Canvases and scaled sprites can be at most [`MAX_SIZE`] pixels across,
+//! so a typo can't make pokeget try to allocate gigabytes.
+
+use image::{imageops::FilterType, DynamicImage};
+
+use crate::{
+    color,
+    list::List,
+    pokemon::{AttributesBuilder, Pokemon, PokemonError},
+    specs::SpecAttribute,
+    sprites::Scene,
+};
+
+/// Largest width or height of the canvas and of a scaled sprite, in pixels
+pub const MAX_SIZE: u32 = 4096;
+
+/// What the canvas size must be, for errors
+const SIZE_RANGE: &str = "a number from 1 to 4096";
+
+/// Error types for reading scene files
+#[derive(Debug, thiserror::Error)]
+pub enum SceneError {
+    #[error("Invalid line {0}: {1}")]
+    Syntax(usize, String),
+
+    #[error("Unknown key on line {0}: {1}")]
+    UnknownKey(usize, String),
+
+    #[error("Invalid value on line {0}: {1} must be {2}")]
+    InvalidValue(usize, String, &'static str),
+
+    #[error("Missing [canvas] table with a width and height")]
+    MissingCanvas,
+
+    #[error("Sprite on line {0} is missing its pokemon")]
+    MissingPokemon(usize),
+
+    #[error("Sprite on line {0} is larger than {MAX_SIZE} pixels once scaled")]
+    TooLarge(usize),
+
+    #[error("Error creating pokemon: {0}")]
+    Pokemon(#[from] PokemonError),
+}
+
+/// A value on the right of `=`
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Value {
+    fn parse(text: &str) -> Option<Self> {
+        if let Some(quoted) = text.strip_prefix('"') {
+            return unquote(quoted).map(Value::String);
+        }
+
+        match text {
+            "true" => return Some(Value::Bool(true)),
+            "false" => return Some(Value::Bool(false)),
+            _ => {}
+        }
+
+        let number = text.replace('_', "");
+        match number.parse() {
+            Ok(x) => Some(Value::Integer(x)),
+            Err(_) => number.parse().ok().map(Value::Float),
+        }
+    }
+}
+
+/// Reads a basic string after its opening quote, `None` if it isn't closed
+/// or anything follows it
+fn unquote(text: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                return chars.as_str().trim().is_empty().then_some(out);
+            }
+            '\\' => out.push(match chars.next()? {
+                'n' => '\n',
+                't' => '\t',
+                c @ ('"' | '\\') => c,
+                _ => return None,
+            }),
+            c => out.push(c),
+        }
+    }
+
+    None
+}
+
+/// Strips a trailing comment, leaving any `#` inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+/// A sprite in a scene
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneSprite {
+    /// The pokemon, in any form accepted on the command line
+    pub pokemon: String,
+    /// Position of the top left corner of the sprite in pixels, which may be off the canvas
+    pub x: i64,
+    pub y: i64,
+    /// Layer of the sprite, higher ones are drawn in front of lower ones
+    pub z: i32,
+    /// Factor the sprite is resized by, keeping its pixels crisp
+    pub scale: f64,
+    pub attributes: Vec<SpecAttribute>,
+    /// Line of the `[[sprite]]` header, for errors
+    line: usize,
+}
+
+impl SceneSprite {
+    fn new(line: usize) -> Self {
+        Self {
+            pokemon: String::new(),
+            x: 0,
+            y: 0,
+            z: 0,
+            scale: 1.0,
+            attributes: Vec::new(),
+            line,
+        }
+    }
+
+    /// Sets the attributes of the sprite on top of the ones already in `builder`
+    pub fn apply(&self, builder: AttributesBuilder) -> AttributesBuilder {
+        self.attributes
+            .iter()
+            .fold(builder, |builder, x| x.apply(builder))
+    }
+
+    fn set(&mut self, line: usize, key: &str, value: Value) -> Result<(), SceneError> {
+        let invalid = |expected| SceneError::InvalidValue(line, key.to_string(), expected);
+
+        match (key, value) {
+            ("pokemon", Value::String(x)) => self.pokemon = x,
+            ("pokemon", _) => return Err(invalid("a string")),
+            ("x", Value::Integer(x)) => self.x = x,
+            ("y", Value::Integer(y)) => self.y = y,
+            ("x" | "y", _) => return Err(invalid("a whole number")),
+            ("z", Value::Integer(z)) if i32::try_from(z).is_ok() => self.z = z as i32,
+            ("z", _) => return Err(invalid("a whole number")),
+            ("scale", Value::Integer(x)) if x > 0 => self.scale = x as f64,
+            ("scale", Value::Float(x)) if x > 0.0 && x.is_finite() => self.scale = x,
+            ("scale", _) => return Err(invalid("a positive number")),
+            ("form", Value::String(form)) => self.attributes.push(SpecAttribute::Form(form)),
+            ("form", _) => return Err(invalid("a string")),
+            (key, value) => {
+                let attribute = SpecAttribute::parse(key)
+                    .filter(|x| !matches!(x, SpecAttribute::Form(_)))
+                    .ok_or_else(|| SceneError::UnknownKey(line, key.to_string()))?;
+
+                match value {
+                    Value::Bool(true) => self.attributes.push(attribute),
+                    Value::Bool(false) => self.attributes.retain(|x| *x != attribute),
+                    _ => return Err(invalid("true or false")),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A parsed scene file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneFile {
+    /// Size of the canvas in pixels, anything outside of it is cut off
+    pub width: u32,
+    pub height: u32,
+    /// Color filling the transparent parts of the canvas
+    pub background: Option<[u8; 3]>,
+    /// The sprites, in the order they're listed
+    pub sprites: Vec<SceneSprite>,
+}
+
+/// The table the keys on a line belong to
+enum Table {
+    None,
+    Canvas,
+    Sprite,
+}
+
+impl SceneFile {
+    /// Parses a scene file
+    pub fn parse(contents: &str) -> Result<Self, SceneError> {
+        let mut width = None;
+        let mut height = None;
+        let mut background = None;
+        let mut sprites: Vec<SceneSprite> = Vec::new();
+        let mut table = Table::None;
+
+        for (i, line) in contents.lines().enumerate() {
+            let number = i + 1;
+            let line = line.trim();
+            let syntax = || SceneError::Syntax(number, line.to_string());
+
+            let code = strip_comment(line).trim_end();
+            if code.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = code.strip_prefix('[') {
+                match header.replace(' ', "").as_str() {
+                    "canvas]" => table = Table::Canvas,
+                    "[sprite]]" => {
+                        sprites.push(SceneSprite::new(number));
+                        table = Table::Sprite;
+                    }
+                    _ => return Err(syntax()),
+                }
+                continue;
+            }
+
+            let (key, value) = code.split_once('=').ok_or_else(syntax)?;
+            let key = key.trim();
+            let value = Value::parse(value.trim()).ok_or_else(syntax)?;
+
+            match (&table, sprites.last_mut()) {
+                (Table::Sprite, Some(sprite)) => sprite.set(number, key, value)?,
+                (Table::Canvas, _) => {
+                    let size = |value| match value {
+                        Value::Integer(x) if x > 0 => {
+                            u32::try_from(x).ok().filter(|x| *x <= MAX_SIZE)
+                        }
+                        _ => None,
+                    };
+                    let invalid =
+                        |expected| SceneError::InvalidValue(number, key.to_string(), expected);
+
+                    match (key, value) {
+                        ("width", x) => width = Some(size(x).ok_or(invalid(SIZE_RANGE))?),
+                        ("height", x) => height = Some(size(x).ok_or(invalid(SIZE_RANGE))?),
+                        ("background", Value::String(x)) => {
+                            background = Some(color::parse(&x).map_err(|_| invalid("a color"))?)
+                        }
+                        ("background", _) => return Err(invalid("a color")),
+                        _ => return Err(SceneError::UnknownKey(number, key.to_string())),
+                    }
+                }
+                _ => return Err(SceneError::UnknownKey(number, key.to_string())),
+            }
+        }
+
+        let (Some(width), Some(height)) = (width, height) else {
+            return Err(SceneError::MissingCanvas);
+        };
+
+        if let Some(sprite) = sprites.iter().find(|x| x.pokemon.is_empty()) {
+            return Err(SceneError::MissingPokemon(sprite.line));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            background,
+            sprites,
+        })
+    }
+
+    /// Creates every sprite with its attributes set on top of the ones from `builder`
+    /// and composes them onto the canvas
+    pub fn render(
+        &self,
+        list: &List,
+        builder: impl Fn() -> AttributesBuilder,
+    ) -> Result<DynamicImage, SceneError> {
+        let sprites = self
+            .sprites
+            .iter()
+            .map(|x| {
+                let attributes = x.apply(builder()).build()?;
+                let sprite = Pokemon::new(x.pokemon.clone(), list, attributes)?.sprite;
+
+                if x.scale == 1.0 {
+                    return Ok(sprite);
+                }
+
+                let width = (sprite.width() as f64 * x.scale).round().max(1.0);
+                let height = (sprite.height() as f64 * x.scale).round().max(1.0);
+                if width.max(height) > f64::from(MAX_SIZE) {
+                    return Err(SceneError::TooLarge(x.line));
+                }
+
+                Ok(sprite.resize_exact(width as u32, height as u32, FilterType::Nearest))
+            })
+            .collect::<Result<Vec<_>, SceneError>>()?;
+
+        let mut scene = Scene::new(self.width, self.height).with_background(self.background);
+        for (sprite, image) in self.sprites.iter().zip(&sprites) {
+            scene.place(image, sprite.x, sprite.y, sprite.z);
+        }
+
+        Ok(scene.render())
+    }
+}
@@ -0,0 +1,146 @@
+//! Where sprites are loaded from.
+//!
+//! Every [`SpriteSource`] looks sprites up by their path relative to the sprite directory,
+//! eg. `regular/pikachu.png`. [`Sources`] chains several, trying each in order.
+//!
+//! With the `remote` feature sprites can also be downloaded from a server laid out the same way,
+//! which goes through `curl` so pokeget doesn't need an HTTP client of its own.
+
+use std::{borrow::Cow, env, fs, path::PathBuf};
+
+use crate::Data;
+
+/// Variable naming directories of sprites, used when `--sprite-dir` isn't passed.
+/// Several can be separated like in `PATH`.
+pub const SPRITE_DIR_VAR: &str = "POKEGET_SPRITE_DIR";
+
+/// Variable with the URL sprites are downloaded from, used when `--sprite-url` isn't passed
+#[cfg(feature = "remote")]
+pub const SPRITE_URL_VAR: &str = "POKEGET_SPRITE_URL";
+
+/// A place sprites can be read from
+pub trait SpriteSource: Send + Sync {
+    /// Reads the PNG file of a sprite, `path` being relative to the sprite directory
+    fn get(&self, path: &str) -> Option<Cow<'static, [u8]>>;
+}
+
+/// The sprites embedded in the binary
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Embedded;
+
+impl SpriteSource for Embedded {
+    fn get(&self, path: &str) -> Option<Cow<'static, [u8]>> {
+        Data::get(path)
+    }
+}
+
+/// A directory laid out like the embedded sprites
+#[derive(Debug, Clone)]
+pub struct Directory(pub PathBuf);
+
+impl SpriteSource for Directory {
+    fn get(&self, path: &str) -> Option<Cow<'static, [u8]>> {
+        fs::read(self.0.join(path)).ok().map(Cow::Owned)
+    }
+}
+
+/// A server laid out like the embedded sprites, eg. `https://example.com/sprites`
+#[cfg(feature = "remote")]
+#[derive(Debug, Clone)]
+pub struct Remote(pub String);
+
+#[cfg(feature = "remote")]
+impl SpriteSource for Remote {
+    fn get(&self, path: &str) -> Option<Cow<'static, [u8]>> {
+        let url = format!("{}/{path}", self.0.trim_end_matches('/'));
+
+        // Missing sprites and unreachable servers fall through to the next source alike
+        let output = std::process::Command::new("curl")
+            .args(["--fail", "--silent", "--location", "--max-time", "10", &url])
+            .output()
+            .ok()?;
+
+        output.status.success().then_some(Cow::Owned(output.stdout))
+    }
+}
+
+/// The URL in `$POKEGET_SPRITE_URL`
+#[cfg(feature = "remote")]
+pub fn url_from_env() -> Option<String> {
+    env::var(SPRITE_URL_VAR).ok().filter(|x| !x.is_empty())
+}
+
+/// The directories in `$POKEGET_SPRITE_DIR`, in the order their sprites take precedence
+pub fn dirs_from_env() -> Vec<PathBuf> {
+    env::var_os(SPRITE_DIR_VAR)
+        .map(|x| env::split_paths(&x).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|x| !x.as_os_str().is_empty())
+        .collect()
+}
+
+/// Sources tried one after another until one has the sprite
+pub struct Sources {
+    sources: Vec<Box<dyn SpriteSource>>,
+}
+
+impl Sources {
+    /// A chain without any sources, which finds nothing
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+        }
+    }
+
+    /// Adds a source, tried after the ones already in the chain
+    pub fn with(mut self, source: impl SpriteSource + 'static) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+
+    /// The directories in order, falling back to the embedded sprites
+    pub fn from_dirs(dirs: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self::with_dirs(dirs).with(Embedded)
+    }
+
+    /// The directories in order, then the server at `url`, falling back to the embedded sprites
+    #[cfg(feature = "remote")]
+    pub fn from_dirs_and_url(dirs: impl IntoIterator<Item = PathBuf>, url: Option<String>) -> Self {
+        let sources = Self::with_dirs(dirs);
+        match url {
+            Some(url) => sources.with(Remote(url)),
+            None => sources,
+        }
+        .with(Embedded)
+    }
+
+    /// The directories in `$POKEGET_SPRITE_DIR`, then `$POKEGET_SPRITE_URL` with the `remote`
+    /// feature, falling back to the embedded sprites
+    pub fn from_env() -> Self {
+        #[cfg(feature = "remote")]
+        return Self::from_dirs_and_url(dirs_from_env(), url_from_env());
+
+        #[cfg(not(feature = "remote"))]
+        Self::from_dirs(dirs_from_env())
+    }
+
+    /// A chain of the directories in order
+    fn with_dirs(dirs: impl IntoIterator<Item = PathBuf>) -> Self {
+        dirs.into_iter()
+            .fold(Self::new(), |sources, dir| sources.with(Directory(dir)))
+    }
+}
+
+impl Default for Sources {
+    /// Only the embedded sprites
+    fn default() -> Self {
+        Self::new().with(Embedded)
+    }
+}
+
+impl SpriteSource for Sources {
+    fn get(&self, path: &str) -> Option<Cow<'static, [u8]>> {
+        self.sources.iter().find_map(|x| x.get(path))
+    }
+}
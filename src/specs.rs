@@ -0,0 +1,118 @@
+//! Files listing pokemon to display, like a saved team.
+//!
+//! Every line holds a pokemon as it would be passed on the command line, followed by
+//! attributes for that pokemon only, eg. `raichu alolan shiny` or `rotom form=wash`.
+//! Lines starting with `#` are ignored.
+
+use crate::pokemon::AttributesBuilder;
+
+/// Error types for reading spec files
+#[derive(Debug, thiserror::Error)]
+pub enum SpecError {
+    #[error("Unknown attribute on line {0}: {1}, expected shiny, female, mega, mega-x, mega-y, alolan, gmax, hisui, galar, noble, flip, silhouette or form=<form>")]
+    UnknownAttribute(usize, String),
+}
+
+/// An attribute given to a single pokemon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecAttribute {
+    Shiny,
+    Female,
+    Mega,
+    MegaX,
+    MegaY,
+    Alolan,
+    Gmax,
+    Hisui,
+    Galar,
+    Noble,
+    Flip,
+    Silhouette,
+    Form(String),
+}
+
+impl SpecAttribute {
+    /// Parses an attribute, `None` if it isn't known
+    pub fn parse(word: &str) -> Option<Self> {
+        if let Some(form) = word.strip_prefix("form=") {
+            return Some(SpecAttribute::Form(form.to_string()));
+        }
+
+        Some(match word.to_lowercase().replace('_', "-").as_str() {
+            "shiny" => SpecAttribute::Shiny,
+            "female" => SpecAttribute::Female,
+            "mega" => SpecAttribute::Mega,
+            "mega-x" => SpecAttribute::MegaX,
+            "mega-y" => SpecAttribute::MegaY,
+            "alolan" | "alola" => SpecAttribute::Alolan,
+            "gmax" => SpecAttribute::Gmax,
+            "hisui" | "hisuian" => SpecAttribute::Hisui,
+            "galar" | "galarian" => SpecAttribute::Galar,
+            "noble" => SpecAttribute::Noble,
+            "flip" => SpecAttribute::Flip,
+            "silhouette" => SpecAttribute::Silhouette,
+            _ => return None,
+        })
+    }
+
+    /// Sets the attribute on top of the ones already in `builder`
+    pub fn apply(&self, builder: AttributesBuilder) -> AttributesBuilder {
+        match self {
+            SpecAttribute::Shiny => builder.with_shiny(true),
+            SpecAttribute::Female => builder.with_female(true),
+            SpecAttribute::Mega => builder.with_mega(true),
+            SpecAttribute::MegaX => builder.with_mega_x(true),
+            SpecAttribute::MegaY => builder.with_mega_y(true),
+            SpecAttribute::Alolan => builder.with_alolan(true),
+            SpecAttribute::Gmax => builder.with_gmax(true),
+            SpecAttribute::Hisui => builder.with_hisui(true),
+            SpecAttribute::Galar => builder.with_galar(true),
+            SpecAttribute::Noble => builder.with_noble(true),
+            SpecAttribute::Flip => builder.with_flip(true),
+            SpecAttribute::Silhouette => builder.with_silhouette(true),
+            SpecAttribute::Form(form) => builder.with_form(form),
+        }
+    }
+}
+
+/// A line of a spec file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spec {
+    /// The pokemon, in any form accepted on the command line
+    pub pokemon: String,
+    pub attributes: Vec<SpecAttribute>,
+}
+
+impl Spec {
+    /// Sets the attributes of the spec on top of the ones already in `builder`
+    pub fn apply(&self, builder: AttributesBuilder) -> AttributesBuilder {
+        self.attributes
+            .iter()
+            .fold(builder, |builder, x| x.apply(builder))
+    }
+}
+
+/// Parses the lines of a spec file, skipping empty lines and comments
+pub fn parse(contents: &str) -> Result<Vec<Spec>, SpecError> {
+    let mut specs = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let Some(pokemon) = words.next().filter(|x| !x.starts_with('#')) else {
+            continue;
+        };
+
+        let attributes = words
+            .map(|x| {
+                SpecAttribute::parse(x).ok_or_else(|| SpecError::UnknownAttribute(i + 1, x.into()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        specs.push(Spec {
+            pokemon: pokemon.to_string(),
+            attributes,
+        });
+    }
+
+    Ok(specs)
+}
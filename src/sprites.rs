@@ -1,7 +1,11 @@
-use image::{DynamicImage, GenericImage, GenericImageView, ImageError};
-use terminal_size::{terminal_size, Width};
+use std::{fmt, str::FromStr};
 
-use crate::pokemon::Pokemon;
+use image::{
+    imageops::{self, FilterType},
+    DynamicImage, GenericImageView, ImageError,
+};
+
+use crate::{labels::Label, pokemon::Pokemon, render::Renderer, terminal::Terminal};
 
 /// Error types for sprite operations
 #[derive(Debug, thiserror::Error)]
@@ -17,6 +21,245 @@ pub enum SpriteError {
 
     #[error("Position out of bounds: {0}")]
     PositionOutOfBounds(String),
+
+    #[error("Failed to write sprites: {0}")]
+    WriteFailed(#[from] fmt::Error),
+}
+
+/// Horizontal placement of a sprite within a [`SpriteRow`]
+#[derive(Debug, Clone, Copy)]
+pub struct SpritePosition {
+    /// Index of the pokemon in the input slice
+    pub index: usize,
+    /// Column the sprite starts at
+    pub x: u32,
+    /// Width of the sprite
+    pub width: u32,
+}
+
+/// A sprite placed in a [`Scene`], at an offset in pixels from the top left corner
+#[derive(Debug, Clone, Copy)]
+pub struct Placement<'a> {
+    pub sprite: &'a DynamicImage,
+    pub x: i64,
+    pub y: i64,
+    /// Sprites with a higher z are drawn over ones with a lower z,
+    /// sprites with the same z in the order they were placed
+    pub z: i32,
+}
+
+/// Sprites composed at explicit positions, which can overlap each other.
+///
+/// Parts of sprites outside of the scene are cut off.
+#[derive(Debug, Clone)]
+pub struct Scene<'a> {
+    width: u32,
+    height: u32,
+    /// Color filling the transparent parts of the composed image
+    background: Option<[u8; 3]>,
+    /// Tile repeated across the scene beneath every sprite
+    backdrop: Option<&'a DynamicImage>,
+    placements: Vec<Placement<'a>>,
+}
+
+impl<'a> Scene<'a> {
+    /// Creates an empty scene of `width` by `height` pixels
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width: width.max(1),
+            height: height.max(1),
+            background: None,
+            backdrop: None,
+            placements: Vec::new(),
+        }
+    }
+
+    pub fn with_background(mut self, background: Option<[u8; 3]>) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn with_backdrop(mut self, backdrop: Option<&'a DynamicImage>) -> Self {
+        self.backdrop = backdrop;
+        self
+    }
+
+    /// Places `sprite` with its top left corner at `x`, `y` on layer `z`
+    pub fn place(&mut self, sprite: &'a DynamicImage, x: i64, y: i64, z: i32) -> &mut Self {
+        self.placements.push(Placement { sprite, x, y, z });
+        self
+    }
+
+    /// The sprites placed so far, in the order they were placed
+    pub fn placements(&self) -> &[Placement<'a>] {
+        &self.placements
+    }
+
+    /// Composes the sprites into one image, blending them where they're partially transparent
+    pub fn render(&self) -> DynamicImage {
+        let mut canvas = DynamicImage::new_rgba8(self.width, self.height);
+
+        if let Some(tile) = self.backdrop {
+            let (tile_w, tile_h) = (i64::from(tile.width()), i64::from(tile.height()));
+
+            for y in (0..i64::from(self.height)).step_by(tile_h.max(1) as usize) {
+                for x in (0..i64::from(self.width)).step_by(tile_w.max(1) as usize) {
+                    imageops::overlay(&mut canvas, tile, x, y);
+                }
+            }
+        }
+
+        // Stable, so sprites on the same layer stay in the order they were placed
+        let mut placements: Vec<&Placement> = self.placements.iter().collect();
+        placements.sort_by_key(|x| x.z);
+
+        for placement in placements {
+            imageops::overlay(&mut canvas, placement.sprite, placement.x, placement.y);
+        }
+
+        if let Some(rgb) = self.background {
+            fill_background(&mut canvas, rgb);
+        }
+
+        canvas
+    }
+}
+
+/// A single composed row of sprites
+pub struct SpriteRow {
+    pub image: DynamicImage,
+    pub positions: Vec<SpritePosition>,
+}
+
+/// Gap between composed sprites in pixels.
+///
+/// Each column of text is one pixel wide and each line is two pixels tall.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Spacing {
+    /// Columns between sprites in the same row
+    pub x: u32,
+    /// Pixels between rows of sprites
+    pub y: u32,
+}
+
+impl Default for Spacing {
+    fn default() -> Self {
+        Self { x: 1, y: 1 }
+    }
+}
+
+/// Error returned when an alignment can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown alignment: {0}, expected top, center or bottom")]
+pub struct ParseAlignError(String);
+
+/// Vertical alignment of sprites shorter than their row
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Align {
+    Top,
+    Center,
+    #[default]
+    Bottom,
+}
+
+impl Align {
+    /// Offset of a sprite `height` pixels tall from the top of a row
+    fn offset(self, row_height: u32, height: u32) -> u32 {
+        let free = row_height.saturating_sub(height);
+
+        match self {
+            Align::Top => 0,
+            Align::Center => free / 2,
+            Align::Bottom => free,
+        }
+    }
+}
+
+impl FromStr for Align {
+    type Err = ParseAlignError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "top" => Ok(Align::Top),
+            "center" | "centre" | "middle" => Ok(Align::Center),
+            "bottom" => Ok(Align::Bottom),
+            _ => Err(ParseAlignError(s.to_string())),
+        }
+    }
+}
+
+/// Error returned when a scaling filter can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown filter: {0}, expected nearest, linear, cubic, gaussian or lanczos")]
+pub struct ParseScaleFilterError(String);
+
+/// How pixels are sampled when shrinking sprites
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ScaleFilter {
+    /// Nearest neighbour, keeping the pixel art crisp
+    #[default]
+    Nearest,
+    Linear,
+    Cubic,
+    Gaussian,
+    /// Lanczos with a window of 3, the smoothest
+    Lanczos,
+}
+
+impl ScaleFilter {
+    /// The filter used by `image` when resizing
+    pub fn filter_type(self) -> FilterType {
+        match self {
+            ScaleFilter::Nearest => FilterType::Nearest,
+            ScaleFilter::Linear => FilterType::Triangle,
+            ScaleFilter::Cubic => FilterType::CatmullRom,
+            ScaleFilter::Gaussian => FilterType::Gaussian,
+            ScaleFilter::Lanczos => FilterType::Lanczos3,
+        }
+    }
+}
+
+impl FromStr for ScaleFilter {
+    type Err = ParseScaleFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "nearest" => Ok(ScaleFilter::Nearest),
+            "linear" | "triangle" => Ok(ScaleFilter::Linear),
+            "cubic" | "catmull-rom" => Ok(ScaleFilter::Cubic),
+            "gaussian" => Ok(ScaleFilter::Gaussian),
+            "lanczos" => Ok(ScaleFilter::Lanczos),
+            _ => Err(ParseScaleFilterError(s.to_string())),
+        }
+    }
+}
+
+/// How sprites are arranged relative to each other
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LayoutOptions {
+    pub spacing: Spacing,
+    pub align: Align,
+    /// Color filling the transparent parts of the composed image
+    pub background: Option<[u8; 3]>,
+}
+
+/// Fills the transparent pixels of an image with a solid color,
+/// blending it beneath partially transparent ones
+pub fn fill_background(image: &mut DynamicImage, rgb: [u8; 3]) {
+    let mut rgba = image.to_rgba8();
+
+    for pixel in rgba.pixels_mut() {
+        let alpha = u16::from(pixel[3]);
+
+        for (channel, background) in pixel.0.iter_mut().zip(rgb) {
+            *channel =
+                ((u16::from(*channel) * alpha + u16::from(background) * (255 - alpha)) / 255) as u8;
+        }
+
+        pixel[3] = 255;
+    }
+
+    *image = DynamicImage::ImageRgba8(rgba);
 }
 
 /// Dimensions for combined sprite canvas
@@ -26,22 +269,30 @@ struct CanvasDimensions {
 }
 
 /// Layout for arranging sprites
+#[derive(Clone)]
 struct SpriteLayout {
     rows: Vec<Vec<usize>>,
+    /// Width of the space each sprite is centered in, at least the width of the sprite
+    slots: Vec<u32>,
 }
 
-impl CanvasDimensions {
-    /// Calculate dimensions for multi-row layout
-    fn calculate_for_wrapped(pokemons: &[Pokemon]) -> Result<(Self, SpriteLayout), SpriteError> {
-        const SPRITE_SPACING: u32 = 1;
-        const MIN_TERMINAL_WIDTH: u32 = 40;
+/// Widths of the sprites, for layouts where nothing else takes up space
+fn sprite_widths(pokemons: &[Pokemon]) -> Vec<u32> {
+    pokemons.iter().map(|x| x.sprite.width()).collect()
+}
 
-        let terminal_width = terminal_size()
-            .map(|(Width(w), _)| w as u32)
-            .unwrap_or(MIN_TERMINAL_WIDTH)
-            .max(MIN_TERMINAL_WIDTH);
+impl CanvasDimensions {
+    /// Calculate dimensions for multi-row layout,
+    /// with each sprite taking up at least its width in `slots`
+    fn calculate_for_wrapped(
+        pokemons: &[Pokemon],
+        slots: Vec<u32>,
+        terminal: &dyn Terminal,
+        spacing: Spacing,
+    ) -> Result<(Self, SpriteLayout), SpriteError> {
+        let terminal_width = terminal.width();
 
-        if terminal_width < MIN_TERMINAL_WIDTH {
+        if terminal_width == 0 {
             return Err(SpriteError::TerminalTooNarrow);
         }
 
@@ -51,12 +302,12 @@ impl CanvasDimensions {
         let mut max_row_width = 0;
 
         for (i, pokemon) in pokemons.iter().enumerate() {
-            let sprite_width = pokemon.sprite.width();
+            let sprite_width = pokemon.sprite.width().max(slots[i]);
 
             let needed_width = if current_row.is_empty() {
                 sprite_width
             } else {
-                current_row_width + SPRITE_SPACING + sprite_width
+                current_row_width + spacing.x + sprite_width
             };
 
             if needed_width > terminal_width && !current_row.is_empty() {
@@ -90,7 +341,7 @@ impl CanvasDimensions {
         }
 
         if !rows.is_empty() {
-            total_height += (rows.len() - 1) as u32 * SPRITE_SPACING;
+            total_height += (rows.len() - 1) as u32 * spacing.y;
         } else {
             total_height = 1;
         }
@@ -100,87 +351,591 @@ impl CanvasDimensions {
                 width: max_row_width.max(1),
                 height: total_height.max(1),
             },
-            SpriteLayout { rows },
+            SpriteLayout { rows, slots },
         ))
     }
 }
 
-/// Handles sprite composition
-struct SpriteComposer {
-    canvas: DynamicImage,
+impl SpriteLayout {
+    /// Places the sprites of every row in a scene of `dimensions`,
+    /// each centered in its slot and aligned within its row
+    fn scene<'a>(
+        &self,
+        pokemons: &'a [Pokemon],
+        dimensions: &CanvasDimensions,
+        options: LayoutOptions,
+    ) -> Scene<'a> {
+        let spacing = options.spacing;
+        let mut scene =
+            Scene::new(dimensions.width, dimensions.height).with_background(options.background);
+        let mut y_offset = 0;
+
+        for row in &self.rows {
+            let row_height = row
+                .iter()
+                .map(|&x| pokemons[x].sprite.height())
+                .max()
+                .unwrap_or(0);
+            let mut x_offset = 0;
+
+            for &index in row {
+                let sprite = &pokemons[index].sprite;
+                let (sprite_w, sprite_h) = sprite.dimensions();
+                let slot_w = self.slots[index].max(sprite_w);
+
+                let sprite_x = x_offset + (slot_w - sprite_w) / 2;
+                let sprite_y = y_offset + options.align.offset(row_height, sprite_h);
+                scene.place(sprite, i64::from(sprite_x), i64::from(sprite_y), 0);
+
+                x_offset += slot_w + spacing.x;
+            }
+
+            y_offset += row_height + spacing.y;
+        }
+
+        scene
+    }
 }
 
-impl SpriteComposer {
-    fn new(dimensions: &CanvasDimensions) -> Self {
-        let canvas = DynamicImage::new_rgba8(dimensions.width, dimensions.height);
-        Self { canvas }
+/// Shrinks sprites larger than `max_width` by `max_height` pixels, preserving the aspect ratio
+pub fn downscale_to(
+    pokemons: &mut [Pokemon],
+    max_width: u32,
+    max_height: u32,
+    filter: ScaleFilter,
+) {
+    let (max_width, max_height) = (max_width.max(1), max_height.max(1));
+
+    for pokemon in pokemons.iter_mut() {
+        let (w, h) = pokemon.sprite.dimensions();
+
+        if w <= max_width && h <= max_height {
+            continue;
+        }
+
+        let scale = f64::min(
+            f64::from(max_width) / f64::from(w),
+            f64::from(max_height) / f64::from(h),
+        );
+
+        let width = ((f64::from(w) * scale) as u32).clamp(1, max_width);
+        let height = ((f64::from(h) * scale) as u32).clamp(1, max_height);
+        pokemon.sprite = pokemon
+            .sprite
+            .resize_exact(width, height, filter.filter_type());
     }
+}
 
-    fn compose_with_layout(
-        mut self,
-        pokemons: &[Pokemon],
-        layout: &SpriteLayout,
-    ) -> Result<DynamicImage, SpriteError> {
-        const SPRITE_SPACING: u32 = 1;
-        let mut y_offset = 0;
+/// Shrinks sprites like [`downscale_to`], then further until all of their rows,
+/// wrapped like [`combine_sprites`] wraps them, fit in `max_height` pixels.
+///
+/// `row_padding` pixels are counted for every row on top of its sprites, eg. for labels beneath them.
+/// Spacing between rows is never shrunk, so sprites end up a pixel tall if that alone doesn't fit.
+pub fn downscale_rows_to(
+    pokemons: &mut [Pokemon],
+    terminal: &dyn Terminal,
+    spacing: Spacing,
+    (max_width, max_height): (u32, u32),
+    row_padding: u32,
+    filter: ScaleFilter,
+) -> Result<(), SpriteError> {
+    if pokemons.is_empty() {
+        return Ok(());
+    }
 
-        for row_indices in &layout.rows {
-            let mut x_offset = 0;
-            let mut row_height = 0;
+    let originals: Vec<DynamicImage> = pokemons.iter().map(|x| x.sprite.clone()).collect();
+    let tallest = originals.iter().map(|x| x.height()).max().unwrap_or(1);
+    let mut limit = max_height.min(tallest);
 
-            // Calculate row height
-            for &pokemon_idx in row_indices {
-                row_height = row_height.max(pokemons[pokemon_idx].sprite.height());
+    loop {
+        // Always shrunk from the originals, resizing twice would blur them
+        for (pokemon, original) in pokemons.iter_mut().zip(&originals) {
+            pokemon.sprite = original.clone();
+        }
+        downscale_to(pokemons, max_width, limit, filter);
+
+        let (dimensions, layout) = CanvasDimensions::calculate_for_wrapped(
+            pokemons,
+            sprite_widths(pokemons),
+            terminal,
+            spacing,
+        )?;
+        let rows = u32::try_from(layout.rows.len()).unwrap_or(u32::MAX);
+        let height = dimensions
+            .height
+            .saturating_add(rows.saturating_mul(row_padding));
+
+        if height <= max_height || limit <= 1 {
+            return Ok(());
+        }
+
+        // Shrinking by how much is left over is only a guess, since narrower sprites may fit in fewer rows
+        let guess = u64::from(limit) * u64::from(max_height) / u64::from(height);
+        limit = u32::try_from(guess).unwrap_or(limit).min(limit - 1).max(1);
+    }
+}
+
+/// How sprites are shrunk to fit the space they're drawn in
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Fit {
+    /// Largest width of each sprite in pixels
+    pub max_width: u32,
+    /// Largest height of each sprite in pixels
+    pub max_height: u32,
+    /// Largest height of all rows of sprites together in pixels, if they're limited together
+    pub rows_height: Option<u32>,
+    /// Pixels beneath every row, eg. for labels
+    pub row_padding: u32,
+    pub filter: ScaleFilter,
+}
+
+impl Fit {
+    /// Sprites keep their size
+    pub const NONE: Self = Self {
+        max_width: u32::MAX,
+        max_height: u32::MAX,
+        rows_height: None,
+        row_padding: 0,
+        filter: ScaleFilter::Nearest,
+    };
+
+    /// Shrinks the sprites with [`downscale_rows_to`] if the rows are limited together,
+    /// otherwise each one with [`downscale_to`]
+    pub fn apply(
+        &self,
+        pokemons: &mut [Pokemon],
+        terminal: &dyn Terminal,
+        spacing: Spacing,
+    ) -> Result<(), SpriteError> {
+        match self.rows_height {
+            Some(height) => downscale_rows_to(
+                pokemons,
+                terminal,
+                spacing,
+                (self.max_width, height.min(self.max_height)),
+                self.row_padding,
+                self.filter,
+            ),
+            None => {
+                downscale_to(pokemons, self.max_width, self.max_height, self.filter);
+                Ok(())
             }
+        }
+    }
+}
 
-            // Place sprites in row
-            for (i, &pokemon_idx) in row_indices.iter().enumerate() {
-                let pokemon = &pokemons[pokemon_idx];
-                let sprite = &pokemon.sprite;
-                let (sprite_w, sprite_h) = sprite.dimensions();
+/// Shrinks every sprite which is wider than the terminal so it fits on one line.
+pub fn downscale_to_fit(pokemons: &mut [Pokemon], terminal: &dyn Terminal) {
+    downscale_to(pokemons, terminal.width(), u32::MAX, ScaleFilter::Nearest);
+}
 
-                // Align to bottom of row
-                let sprite_y = y_offset + row_height - sprite_h;
-
-                // Ensure position is within canvas bounds
-                if x_offset + sprite_w > self.canvas.width()
-                    || sprite_y + sprite_h > self.canvas.height()
-                {
-                    return Err(SpriteError::PositionOutOfBounds(format!(
-                        "Sprite at ({}, {}) with size {}x{} exceeds canvas {}x{}",
-                        x_offset,
-                        sprite_y,
-                        sprite_w,
-                        sprite_h,
-                        self.canvas.width(),
-                        self.canvas.height()
-                    )));
-                }
+/// Shrinks every sprite which would render taller than `lines` rows of text.
+/// Each row of text holds two pixels.
+pub fn downscale_to_lines(pokemons: &mut [Pokemon], lines: u32) {
+    downscale_to(
+        pokemons,
+        u32::MAX,
+        lines.saturating_mul(2),
+        ScaleFilter::Nearest,
+    );
+}
 
-                self.canvas.copy_from(sprite, x_offset, sprite_y)?;
+/// Crops rendered output to at most `max_lines` lines
+pub fn crop_lines(output: &str, max_lines: usize) -> String {
+    output
+        .lines()
+        .take(max_lines)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-                // Add spacing only between sprites, not after last in row
-                if i < row_indices.len() - 1 {
-                    x_offset += sprite_w + SPRITE_SPACING;
-                } else {
-                    x_offset += sprite_w;
-                }
-            }
+/// Combines pokemon sprites into one image, wrapping rows to fit the terminal
+pub fn combine_sprites(
+    pokemons: &[Pokemon],
+    terminal: &dyn Terminal,
+    options: LayoutOptions,
+) -> Result<DynamicImage, SpriteError> {
+    if pokemons.is_empty() {
+        return Err(SpriteError::EmptyInput);
+    }
 
-            y_offset += row_height + SPRITE_SPACING;
+    let (dimensions, layout) = CanvasDimensions::calculate_for_wrapped(
+        pokemons,
+        sprite_widths(pokemons),
+        terminal,
+        options.spacing,
+    )?;
+    Ok(layout.scene(pokemons, &dimensions, options).render())
+}
+
+/// Combines pokemon sprites and renders them as text
+pub fn render_sprites(
+    pokemons: &[Pokemon],
+    terminal: &dyn Terminal,
+    options: LayoutOptions,
+    renderer: &dyn Renderer,
+) -> Result<String, SpriteError> {
+    let mut out = String::new();
+    write_sprites(&mut out, pokemons, terminal, options, renderer)?;
+
+    Ok(out)
+}
+
+/// Rows of sprites [`write_sprites`] composes into one image at a time,
+/// so that showing a whole region never needs a canvas holding every sprite
+const CHUNK_ROWS: usize = 8;
+
+/// Combines pokemon sprites and writes them as text into `out`.
+///
+/// Many rows of sprites are composed and written a few rows at a time,
+/// each chunk padded to a whole amount of lines.
+pub fn write_sprites(
+    out: &mut impl fmt::Write,
+    pokemons: &[Pokemon],
+    terminal: &dyn Terminal,
+    options: LayoutOptions,
+    renderer: &dyn Renderer,
+) -> Result<(), SpriteError> {
+    if pokemons.is_empty() {
+        return Err(SpriteError::EmptyInput);
+    }
+
+    let (dimensions, layout) = CanvasDimensions::calculate_for_wrapped(
+        pokemons,
+        sprite_widths(pokemons),
+        terminal,
+        options.spacing,
+    )?;
+
+    if layout.rows.len() <= CHUNK_ROWS {
+        let combined = combine_sprites(pokemons, terminal, options)?;
+        renderer.write(out, &combined)?;
+
+        return Ok(());
+    }
+
+    let line_height = renderer.cell_size().1;
+    let chunks: Vec<&[Vec<usize>]> = layout.rows.chunks(CHUNK_ROWS).collect();
+
+    for (i, rows) in chunks.iter().enumerate() {
+        let heights: u32 = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&x| pokemons[x].sprite.height())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .sum();
+        // Spacing after the last row of a chunk separates it from the next one
+        let height = heights + rows.len() as u32 * options.spacing.y;
+
+        let chunk = CanvasDimensions {
+            width: dimensions.width,
+            height: height.div_ceil(line_height).max(1) * line_height,
+        };
+        let image = SpriteLayout {
+            rows: rows.to_vec(),
+            slots: layout.slots.clone(),
         }
+        .scene(pokemons, &chunk, options)
+        .render();
 
-        Ok(self.canvas)
+        if i != 0 {
+            out.write_char('\n')?;
+        }
+        renderer.write(out, &image)?;
     }
+
+    Ok(())
+}
+
+/// Combines pokemon sprites into separately composed rows,
+/// so that text can be placed between them.
+///
+/// Only the horizontal spacing applies, separating the rows is up to the caller.
+pub fn combine_rows(
+    pokemons: &[Pokemon],
+    terminal: &dyn Terminal,
+    options: LayoutOptions,
+) -> Result<Vec<SpriteRow>, SpriteError> {
+    combine_rows_with_slots(pokemons, sprite_widths(pokemons), terminal, options)
 }
 
-/// Combines pokemon sprites into one image
-pub fn combine_sprites(pokemons: &[Pokemon]) -> Result<DynamicImage, SpriteError> {
+/// Combines pokemon sprites into rows like [`combine_rows`],
+/// making room for the labels which will be placed beneath them with [`label_line`].
+///
+/// Sprites narrower than their label are centered in space as wide as the label.
+/// `labels` holds the labels of every line, each indexed like `pokemons`.
+///
+/// [`label_line`]: crate::labels::label_line
+pub fn combine_labeled_rows(
+    pokemons: &[Pokemon],
+    labels: &[&[Label]],
+    terminal: &dyn Terminal,
+    options: LayoutOptions,
+) -> Result<Vec<SpriteRow>, SpriteError> {
+    let slots = (0..pokemons.len())
+        .map(|i| {
+            labels
+                .iter()
+                .filter_map(|x| x.get(i))
+                .map(|x| x.width)
+                .fold(pokemons[i].sprite.width(), u32::max)
+        })
+        .collect();
+
+    combine_rows_with_slots(pokemons, slots, terminal, options)
+}
+
+fn combine_rows_with_slots(
+    pokemons: &[Pokemon],
+    slots: Vec<u32>,
+    terminal: &dyn Terminal,
+    options: LayoutOptions,
+) -> Result<Vec<SpriteRow>, SpriteError> {
+    let spacing = options.spacing;
+
     if pokemons.is_empty() {
         return Err(SpriteError::EmptyInput);
     }
 
-    let (dimensions, layout) = CanvasDimensions::calculate_for_wrapped(pokemons)?;
-    let composer = SpriteComposer::new(&dimensions);
-    composer.compose_with_layout(pokemons, &layout)
+    let (_, layout) = CanvasDimensions::calculate_for_wrapped(pokemons, slots, terminal, spacing)?;
+    let mut rows = Vec::with_capacity(layout.rows.len());
+
+    for row in layout.rows {
+        let mut positions = Vec::with_capacity(row.len());
+        let mut x = 0;
+        let mut height = 0;
+
+        for &index in &row {
+            let (sprite_width, sprite_height) = pokemons[index].sprite.dimensions();
+            let width = layout.slots[index].max(sprite_width);
+            positions.push(SpritePosition { index, x, width });
+            x += width + spacing.x;
+            height = height.max(sprite_height);
+        }
+
+        let dimensions = CanvasDimensions {
+            width: x.saturating_sub(spacing.x).max(1),
+            height: height.max(1),
+        };
+
+        let image = SpriteLayout {
+            rows: vec![row],
+            slots: layout.slots.clone(),
+        }
+        .scene(pokemons, &dimensions, options)
+        .render();
+
+        rows.push(SpriteRow { image, positions });
+    }
+
+    Ok(rows)
+}
+
+/// Columns between cells of a grid made by [`combine_grid`]
+pub const GRID_SPACING: u32 = 4;
+
+/// Combines pokemon sprites into a fixed grid with `columns` cells per row,
+/// like the in-game party screen.
+///
+/// Every cell is as wide as the widest sprite, sprites are centered
+/// horizontally in their cell and aligned to the bottom of their row.
+pub fn combine_grid(pokemons: &[Pokemon], columns: usize) -> Result<Vec<SpriteRow>, SpriteError> {
+    if pokemons.is_empty() {
+        return Err(SpriteError::EmptyInput);
+    }
+
+    let cell_width = pokemons.iter().map(|x| x.sprite.width()).max().unwrap_or(1);
+
+    let indices: Vec<usize> = (0..pokemons.len()).collect();
+    let mut rows = Vec::new();
+
+    for row in indices.chunks(columns.max(1)) {
+        let height = row
+            .iter()
+            .map(|&i| pokemons[i].sprite.height())
+            .max()
+            .unwrap_or(1);
+
+        let width = row.len() as u32 * (cell_width + GRID_SPACING) - GRID_SPACING;
+        let mut image = DynamicImage::new_rgba8(width, height.max(1));
+        let mut positions = Vec::with_capacity(row.len());
+
+        for (column, &index) in row.iter().enumerate() {
+            let sprite = &pokemons[index].sprite;
+            let (w, h) = sprite.dimensions();
+            let x = column as u32 * (cell_width + GRID_SPACING) + (cell_width - w) / 2;
+
+            imageops::overlay(&mut image, sprite, i64::from(x), i64::from(height - h));
+            positions.push(SpritePosition { index, x, width: w });
+        }
+
+        rows.push(SpriteRow { image, positions });
+    }
+
+    Ok(rows)
+}
+
+/// Amount of columns `text` takes up when printed, skipping the escape codes in it
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            width += 1;
+            continue;
+        }
+
+        // Control sequences like colors end with their first character from '@' to '~'
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+
+    width
+}
+
+/// Wraps text into lines of at most `width` columns, breaking on whitespace
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let line_width = visible_width(&line);
+        let word_width = visible_width(word);
+
+        if line_width > 0 && line_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+
+        line.push_str(word);
+    }
+
+    lines.push(line);
+    lines
+}
+
+/// Text placed next to a sprite
+pub enum Text {
+    /// A paragraph which gets wrapped to fit
+    Paragraph(String),
+    /// A preformatted line which is never wrapped
+    Line(Label),
+}
+
+/// Side of a sprite text is placed on
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Position {
+    Left,
+    #[default]
+    Right,
+}
+
+/// Renders a sprite and places text on one side of it, vertically centered,
+/// or beneath it if there isn't enough room left in the terminal.
+/// Paragraphs are wrapped to fit the remaining width.
+///
+/// Widths are measured without escape codes, so the text can be colored.
+pub fn compose_with_text(
+    img: &DynamicImage,
+    renderer: &dyn Renderer,
+    text: &[Text],
+    position: Position,
+    terminal: &dyn Terminal,
+) -> String {
+    let art = renderer.render(img);
+    compose_art(
+        &art,
+        renderer.columns(img.width()),
+        text,
+        position,
+        terminal,
+    )
+}
+
+/// Places text to the right of rendered art, see [`compose_with_text`].
+///
+/// `art` is expected to have lines which are all `art_width` columns wide.
+pub fn layout_text(art: &str, art_width: u32, text: &[Text], terminal: &dyn Terminal) -> String {
+    compose_art(art, art_width, text, Position::Right, terminal)
+}
+
+fn compose_art(
+    art: &str,
+    art_width: u32,
+    text: &[Text],
+    position: Position,
+    terminal: &dyn Terminal,
+) -> String {
+    const GAP: usize = 2;
+    const MIN_TEXT_WIDTH: usize = 20;
+
+    let art_width = art_width as usize;
+    let terminal_width = terminal.width() as usize;
+
+    let widest_line = text
+        .iter()
+        .filter_map(|x| match x {
+            Text::Line(label) => Some(label.width as usize),
+            Text::Paragraph(_) => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let beside = terminal_width.saturating_sub(art_width + GAP) >= MIN_TEXT_WIDTH.max(widest_line);
+
+    let text_width = if beside {
+        terminal_width - art_width - GAP
+    } else {
+        terminal_width
+    };
+
+    let lines: Vec<String> = text
+        .iter()
+        .flat_map(|x| match x {
+            Text::Paragraph(paragraph) => wrap_text(paragraph, text_width),
+            Text::Line(label) => vec![label.text.clone()],
+        })
+        .collect();
+
+    if !beside {
+        return format!("{art}\n{}", lines.join("\n"));
+    }
+
+    let art_lines: Vec<&str> = art.lines().collect();
+    let height = art_lines.len().max(lines.len());
+    let top = (art_lines.len().saturating_sub(lines.len())) / 2;
+    let blank = " ".repeat(art_width);
+    let gap = " ".repeat(GAP);
+    let column = lines.iter().map(|x| visible_width(x)).max().unwrap_or(0);
+
+    (0..height)
+        .map(|i| {
+            let art_line = art_lines.get(i).copied().unwrap_or(&blank);
+            let line = i.checked_sub(top).and_then(|x| lines.get(x));
+
+            match (position, line) {
+                (Position::Right, Some(line)) => format!("{art_line}{gap}{line}"),
+                (Position::Right, None) => art_line.to_string(),
+                (Position::Left, line) => {
+                    let line = line.map_or("", String::as_str);
+                    let padding = " ".repeat(column - visible_width(line));
+                    format!("{line}{padding}{gap}{art_line}")
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
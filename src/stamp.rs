@@ -0,0 +1,89 @@
+//! Credit stamps crediting the sprites' source on exported images.
+
+use std::str::FromStr;
+
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+
+use crate::font::BitmapFont;
+
+/// Credit for the embedded sprites
+pub const SPRITE_CREDIT: &str = "Sprites: PokeSprite";
+
+/// Color of stamps, readable on both light and dark backgrounds
+pub const STAMP_COLOR: Rgba<u8> = Rgba([160, 160, 160, 255]);
+
+/// Pixels between a stamp and the edges of the image
+const PADDING: u32 = 1;
+
+/// Error returned when a corner can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown corner: {0}, expected top-left, top-right, bottom-left or bottom-right")]
+pub struct ParseCornerError(String);
+
+/// Corner of an image a stamp is placed in
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl FromStr for Corner {
+    type Err = ParseCornerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "top-left" => Ok(Corner::TopLeft),
+            "top-right" => Ok(Corner::TopRight),
+            "bottom-left" => Ok(Corner::BottomLeft),
+            "bottom-right" => Ok(Corner::BottomRight),
+            _ => Err(ParseCornerError(s.to_string())),
+        }
+    }
+}
+
+/// Adds a line of `text` in a corner of an image.
+///
+/// The text goes into a strip above or beneath the image rather than over it,
+/// and the image is widened if the text doesn't fit.
+pub fn stamp(
+    image: &DynamicImage,
+    text: &str,
+    corner: Corner,
+    font: &BitmapFont,
+    color: Rgba<u8>,
+) -> DynamicImage {
+    let text_width = font.text_width(text, 1);
+    let strip = font.text_height(1) + PADDING * 2;
+
+    let width = image.width().max(text_width + PADDING * 2);
+    let height = image.height() + strip;
+
+    let top = matches!(corner, Corner::TopLeft | Corner::TopRight);
+    let left = matches!(corner, Corner::TopLeft | Corner::BottomLeft);
+
+    let mut canvas = RgbaImage::new(width, height);
+    let image_y = if top { strip } else { 0 };
+    imageops::overlay(
+        &mut canvas,
+        &image.to_rgba8(),
+        i64::from((width - image.width()) / 2),
+        i64::from(image_y),
+    );
+
+    let text_x = if left {
+        PADDING
+    } else {
+        width - text_width - PADDING
+    };
+    let text_y = if top {
+        PADDING
+    } else {
+        image.height() + PADDING
+    };
+    font.draw(&mut canvas, text_x, text_y, text, color, 1);
+
+    DynamicImage::ImageRgba8(canvas)
+}
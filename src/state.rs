@@ -0,0 +1,500 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    json::{JsonError, Value},
+    paths::{self, PathError},
+    usage::{Usage, WEEKDAYS},
+};
+
+/// Version of the state file schema written by this build
+pub const STATE_VERSION: u32 = 3;
+
+/// Maximum amount of entries kept in the history
+const HISTORY_LIMIT: usize = 100;
+
+/// Error types for state operations
+#[derive(Debug, thiserror::Error)]
+pub enum StateError {
+    #[error("Failed to access state file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to parse state file on line {0}: {1}")]
+    Parse(usize, String),
+
+    #[error("State file version {0} is newer than the supported version {1}")]
+    UnsupportedVersion(u32, u32),
+
+    #[error("{0}")]
+    Path(#[from] PathError),
+
+    #[error("Failed to parse exported state: {0}")]
+    Json(#[from] JsonError),
+
+    #[error("Invalid exported state: {0}")]
+    InvalidExport(&'static str),
+}
+
+/// Persistent data shared between pokeget invocations
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct State {
+    /// Recently displayed pokemon, oldest first
+    pub history: Vec<String>,
+
+    /// Encounter counters for shiny hunts, keyed by pokemon
+    pub counters: BTreeMap<String, u64>,
+
+    /// Pokemon registered in the living dex
+    pub living_dex: BTreeSet<String>,
+
+    /// Favorite pokemon
+    pub favorites: BTreeSet<String>,
+
+    /// User defined aliases and the pokemon they refer to
+    pub aliases: BTreeMap<String, String>,
+
+    /// Local usage statistics, only recorded when enabled
+    pub usage: Usage,
+
+    /// Identifiers of unlocked achievements
+    pub achievements: BTreeSet<String>,
+}
+
+/// Whether `name` can be written on a line of the state file and read back unchanged.
+///
+/// Lines are trimmed, `#` starts a comment, `[` a section and `=` splits keys from values,
+/// so names containing those would be read back as something else or not at all.
+fn storable(name: &str) -> bool {
+    !name.is_empty()
+        && name.trim() == name
+        && !name.starts_with('#')
+        && !name
+            .chars()
+            .any(|x| x.is_control() || matches!(x, '=' | '[' | ']'))
+}
+
+/// Splits a `key = value` line
+fn split_pair(line: &str) -> Option<(&str, &str)> {
+    line.split_once('=')
+        .map(|(key, value)| (key.trim(), value.trim()))
+}
+
+impl State {
+    /// Adds a pokemon to the history, dropping the oldest entries past the limit
+    pub fn record(&mut self, name: &str) {
+        self.history.push(name.to_string());
+
+        if self.history.len() > HISTORY_LIMIT {
+            let excess = self.history.len() - HISTORY_LIMIT;
+            self.history.drain(..excess);
+        }
+    }
+
+    /// Parses a state file, migrating it from older schema versions
+    pub fn parse(contents: &str) -> Result<Self, StateError> {
+        let mut state = Self::default();
+        let mut version = 0;
+        let mut section: Option<&str> = None;
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+                section = Some(name);
+                continue;
+            }
+
+            let err = || StateError::Parse(i + 1, line.to_string());
+
+            match section {
+                None => match split_pair(line) {
+                    Some(("version", value)) => {
+                        version = value.parse().map_err(|_| err())?;
+
+                        if version > STATE_VERSION {
+                            return Err(StateError::UnsupportedVersion(version, STATE_VERSION));
+                        }
+                    }
+                    _ => return Err(err()),
+                },
+                Some("history") => state.history.push(line.to_string()),
+                Some("counters") => {
+                    let (key, value) = split_pair(line).ok_or_else(err)?;
+                    let count = value.parse().map_err(|_| err())?;
+                    state.counters.insert(key.to_string(), count);
+                }
+                Some("living_dex") => {
+                    state.living_dex.insert(line.to_string());
+                }
+                Some("favorites") => {
+                    state.favorites.insert(line.to_string());
+                }
+                Some("aliases") => {
+                    let (key, value) = split_pair(line).ok_or_else(err)?;
+                    state.aliases.insert(key.to_string(), value.to_string());
+                }
+                Some("usage") => {
+                    let (key, value) = split_pair(line).ok_or_else(err)?;
+
+                    match key {
+                        "enabled" => state.usage.enabled = value.parse().map_err(|_| err())?,
+                        "shinies" => state.usage.shinies = value.parse().map_err(|_| err())?,
+                        "randoms" => state.usage.randoms = value.parse().map_err(|_| err())?,
+                        "notify" => state.usage.notify = value.parse().map_err(|_| err())?,
+                        day => {
+                            let i = WEEKDAYS.iter().position(|x| *x == day).ok_or_else(err)?;
+                            state.usage.weekdays[i] = value.parse().map_err(|_| err())?;
+                        }
+                    }
+                }
+                Some("displays") => {
+                    let (key, value) = split_pair(line).ok_or_else(err)?;
+                    let count = value.parse().map_err(|_| err())?;
+                    state.usage.displays.insert(key.to_string(), count);
+                }
+                Some("achievements") => {
+                    state.achievements.insert(line.to_string());
+                }
+                Some(_) => return Err(err()),
+            }
+        }
+
+        Ok(state.migrate(version))
+    }
+
+    /// Upgrades state read from an older schema version
+    fn migrate(self, from: u32) -> Self {
+        (from..STATE_VERSION).fold(self, |state, version| match version {
+            // Files from before versioning share the version 1 layout
+            0 => state,
+            // Versions 2 and 3 only added the usage and achievements sections
+            1 | 2 => state,
+            _ => unreachable!("no migration from state version {version}"),
+        })
+    }
+
+    /// Exports the portable parts of the state as JSON.
+    ///
+    /// History, usage statistics and achievements are left out since they only make sense
+    /// on the machine they were recorded on.
+    pub fn export_json(&self) -> String {
+        let names =
+            |set: &BTreeSet<String>| Value::Array(set.iter().cloned().map(Value::String).collect());
+
+        let export = Value::Object(vec![
+            (
+                "version".to_string(),
+                Value::Number(f64::from(STATE_VERSION)),
+            ),
+            ("favorites".to_string(), names(&self.favorites)),
+            ("living_dex".to_string(), names(&self.living_dex)),
+            (
+                "counters".to_string(),
+                Value::Object(
+                    self.counters
+                        .iter()
+                        .map(|(name, count)| (name.clone(), Value::Number(*count as f64)))
+                        .collect(),
+                ),
+            ),
+            (
+                "aliases".to_string(),
+                Value::Object(
+                    self.aliases
+                        .iter()
+                        .map(|(alias, name)| (alias.clone(), Value::String(name.clone())))
+                        .collect(),
+                ),
+            ),
+        ]);
+
+        format!("{export}\n")
+    }
+
+    /// Replaces the portable parts of the state with ones exported by [`State::export_json`].
+    ///
+    /// Exports naming pokemon in ways the state file can't hold are rejected as invalid.
+    pub fn import_json(&mut self, json: &str) -> Result<(), StateError> {
+        let value = Value::parse(json)?;
+
+        let version = value
+            .get("version")
+            .and_then(Value::as_f64)
+            .ok_or(StateError::InvalidExport("missing version"))?;
+
+        if version > f64::from(STATE_VERSION) {
+            return Err(StateError::UnsupportedVersion(
+                version as u32,
+                STATE_VERSION,
+            ));
+        }
+
+        let names = |key: &'static str| -> Result<BTreeSet<String>, StateError> {
+            let Some(items) = value.get(key) else {
+                return Ok(BTreeSet::new());
+            };
+
+            items
+                .as_array()
+                .ok_or(StateError::InvalidExport(key))?
+                .iter()
+                .map(|x| x.as_str().filter(|x| storable(x)).map(str::to_string))
+                .collect::<Option<_>>()
+                .ok_or(StateError::InvalidExport(key))
+        };
+
+        let entries = |key: &'static str| -> Result<&[(String, Value)], StateError> {
+            match value.get(key) {
+                Some(entries) => entries.as_object().ok_or(StateError::InvalidExport(key)),
+                None => Ok(&[]),
+            }
+        };
+
+        let counters = entries("counters")?
+            .iter()
+            .map(|(name, count)| match count.as_f64() {
+                Some(n) if storable(name) && n >= 0.0 && n.fract() == 0.0 => {
+                    Ok((name.clone(), n as u64))
+                }
+                _ => Err(StateError::InvalidExport("counters")),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let aliases = entries("aliases")?
+            .iter()
+            .map(|(alias, name)| match name.as_str() {
+                Some(name) if storable(alias) && storable(name) => {
+                    Ok((alias.clone(), name.to_string()))
+                }
+                _ => Err(StateError::InvalidExport("aliases")),
+            })
+            .collect::<Result<_, _>>()?;
+
+        self.favorites = names("favorites")?;
+        self.living_dex = names("living_dex")?;
+        self.counters = counters;
+        self.aliases = aliases;
+
+        Ok(())
+    }
+
+    /// Serializes the state into the state file format
+    pub fn serialize(&self) -> String {
+        let mut out = format!("version = {STATE_VERSION}\n");
+
+        out.push_str("\n[history]\n");
+        for name in &self.history {
+            let _ = writeln!(out, "{name}");
+        }
+
+        out.push_str("\n[counters]\n");
+        for (name, count) in &self.counters {
+            let _ = writeln!(out, "{name} = {count}");
+        }
+
+        out.push_str("\n[living_dex]\n");
+        for name in &self.living_dex {
+            let _ = writeln!(out, "{name}");
+        }
+
+        out.push_str("\n[favorites]\n");
+        for name in &self.favorites {
+            let _ = writeln!(out, "{name}");
+        }
+
+        out.push_str("\n[aliases]\n");
+        for (alias, name) in &self.aliases {
+            let _ = writeln!(out, "{alias} = {name}");
+        }
+
+        out.push_str("\n[usage]\n");
+        let _ = writeln!(out, "enabled = {}", self.usage.enabled);
+        let _ = writeln!(out, "shinies = {}", self.usage.shinies);
+        let _ = writeln!(out, "randoms = {}", self.usage.randoms);
+        let _ = writeln!(out, "notify = {}", self.usage.notify);
+        for (day, count) in WEEKDAYS.iter().zip(self.usage.weekdays) {
+            let _ = writeln!(out, "{day} = {count}");
+        }
+
+        out.push_str("\n[displays]\n");
+        for (name, count) in &self.usage.displays {
+            let _ = writeln!(out, "{name} = {count}");
+        }
+
+        out.push_str("\n[achievements]\n");
+        for id in &self.achievements {
+            let _ = writeln!(out, "{id}");
+        }
+
+        out
+    }
+}
+
+/// Error returned when a state scope can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown state: {0}, expected user or system")]
+pub struct ParseStateScopeError(String);
+
+/// Whose state file is used
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StateScope {
+    /// The current user's own state
+    #[default]
+    User,
+    /// State shared by every user on the system, eg. for a server's MOTD
+    System,
+}
+
+impl FromStr for StateScope {
+    type Err = ParseStateScopeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "user" => Ok(StateScope::User),
+            "system" => Ok(StateScope::System),
+            _ => Err(ParseStateScopeError(s.to_string())),
+        }
+    }
+}
+
+/// Handle to a state file on disk.
+///
+/// Reads take a shared lock and updates take an exclusive one,
+/// so concurrent invocations never see or write a half finished file.
+pub struct StateStore {
+    path: PathBuf,
+    /// Whether other users must be able to write the files too
+    shared: bool,
+}
+
+impl StateStore {
+    /// Creates a store for the state file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            shared: false,
+        }
+    }
+
+    /// Creates a store for the user's default state file
+    pub fn open_default() -> Result<Self, StateError> {
+        Self::open(StateScope::User)
+    }
+
+    /// Creates a store for the default state file of `scope`
+    pub fn open(scope: StateScope) -> Result<Self, StateError> {
+        Ok(match scope {
+            StateScope::User => Self::new(paths::state_dir()?.join("state")),
+            StateScope::System => Self {
+                shared: true,
+                ..Self::new(paths::system_state_dir()?.join("state"))
+            },
+        })
+    }
+
+    /// Path of the state file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Opens the lock file which guards the state file.
+    ///
+    /// A separate file is used since the state file itself gets replaced on every write.
+    fn lock_file(&self) -> Result<File, StateError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path.with_extension("lock"))?;
+
+        // Only the owner can change permissions, which they already did when creating it
+        let _ = self.share(&file);
+
+        Ok(file)
+    }
+
+    /// Lets every user write a file of a shared store, so users can take turns updating it
+    #[cfg(unix)]
+    fn share(&self, file: &File) -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        match self.shared {
+            true => file.set_permissions(fs::Permissions::from_mode(0o666)),
+            false => Ok(()),
+        }
+    }
+
+    /// Files are writable by every user by default on other platforms
+    #[cfg(not(unix))]
+    fn share(&self, _file: &File) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Reads the file without taking a lock, treating a missing file as empty state
+    fn read_unlocked(&self) -> Result<State, StateError> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => State::parse(&contents),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(State::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Atomically replaces the state file by writing to a temporary file and renaming it
+    fn write_unlocked(&self, state: &State) -> Result<(), StateError> {
+        let tmp = self
+            .path
+            .with_extension(format!("tmp.{}", std::process::id()));
+
+        let result = (|| {
+            let mut file = File::create(&tmp)?;
+            self.share(&file)?;
+            file.write_all(state.serialize().as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&tmp, &self.path)
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+
+        Ok(result?)
+    }
+
+    /// Loads the current state
+    pub fn load(&self) -> Result<State, StateError> {
+        let lock = self.lock_file()?;
+        lock.lock_shared()?;
+
+        self.read_unlocked()
+    }
+
+    /// Loads the state, applies `f` to it and saves the result, all under an exclusive lock
+    pub fn update<T>(&self, f: impl FnOnce(&mut State) -> T) -> Result<T, StateError> {
+        let lock = self.lock_file()?;
+        lock.lock()?;
+
+        let mut state = self.read_unlocked()?;
+        let result = f(&mut state);
+        self.write_unlocked(&state)?;
+
+        Ok(result)
+    }
+
+    /// Replaces the state entirely
+    pub fn save(&self, state: &State) -> Result<(), StateError> {
+        self.update(|current| *current = state.clone())
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{color, labels::Label, terminal::ColorDepth};
+
+/// Width of a full bar, which represents a stat of 255
+const BAR_WIDTH: u32 = 20;
+
+/// Base stats of a pokemon
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BaseStats {
+    pub hp: u8,
+    pub attack: u8,
+    pub defense: u8,
+    pub special_attack: u8,
+    pub special_defense: u8,
+    pub speed: u8,
+}
+
+impl BaseStats {
+    /// Sum of all base stats
+    pub fn total(&self) -> u32 {
+        [
+            self.hp,
+            self.attack,
+            self.defense,
+            self.special_attack,
+            self.special_defense,
+            self.speed,
+        ]
+        .iter()
+        .map(|x| u32::from(*x))
+        .sum()
+    }
+
+    /// Color of a stat's bar, going from red for low stats to cyan for high ones
+    fn color(value: u8) -> [u8; 3] {
+        match value {
+            0..=49 => [0xF3, 0x44, 0x44],
+            50..=79 => [0xFF, 0x7F, 0x0F],
+            80..=99 => [0xFF, 0xDD, 0x57],
+            100..=119 => [0xA0, 0xE5, 0x15],
+            120..=149 => [0x23, 0xCD, 0x5E],
+            _ => [0x00, 0xC2, 0xB8],
+        }
+    }
+
+    /// Renders the stats as lines of colored bar charts,
+    /// all in the `accent` color if set instead of colored by value
+    pub fn bars(&self, depth: ColorDepth, accent: Option<[u8; 3]>) -> Vec<Label> {
+        let rows = [
+            ("HP ", self.hp),
+            ("Atk", self.attack),
+            ("Def", self.defense),
+            ("SpA", self.special_attack),
+            ("SpD", self.special_defense),
+            ("Spe", self.speed),
+        ];
+
+        let mut lines: Vec<Label> = rows
+            .iter()
+            .map(|(name, value)| {
+                let filled = (u32::from(*value) * BAR_WIDTH).div_ceil(255).max(1);
+                let empty = BAR_WIDTH - filled;
+
+                let bar = match depth {
+                    ColorDepth::Monochrome => "█".repeat(filled as usize),
+                    _ => format!(
+                        "{}{}{}",
+                        color::fg(accent.unwrap_or(Self::color(*value)), depth),
+                        "█".repeat(filled as usize),
+                        color::RESET
+                    ),
+                };
+
+                Label {
+                    text: format!("{name} {bar}{} {value:>3}", "░".repeat(empty as usize)),
+                    width: 4 + BAR_WIDTH + 4,
+                }
+            })
+            .collect();
+
+        lines.push(Label::plain(&format!(
+            "Tot {}{:>4}",
+            " ".repeat(BAR_WIDTH as usize),
+            self.total()
+        )));
+
+        lines
+    }
+}
+
+// Generated by `build.rs` from `stats.csv`
+include!(concat!(env!("OUT_DIR"), "/stats.rs"));
+
+/// The base stats in `stats.csv`, backed by a table generated at compile time.
+pub struct StatTable {
+    entries: &'static [(&'static str, BaseStats)],
+}
+
+impl StatTable {
+    /// Creates a new [`StatTable`] from the embedded table
+    pub fn read() -> Self {
+        Self { entries: &ENTRIES }
+    }
+
+    /// Gets the base stats of a pokemon by filename
+    pub fn get(&self, filename: &str) -> Option<&BaseStats> {
+        self.entries
+            .binary_search_by(|(x, _)| (*x).cmp(filename))
+            .ok()
+            .map(|i| &self.entries[i].1)
+    }
+}
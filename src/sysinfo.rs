@@ -0,0 +1,167 @@
+//! A small gatherer of system information for `pokeget fetch`.
+//!
+//! Everything is read from `/proc`, `/etc` and environment variables where they exist,
+//! falling back to `uname` and `hostname`. Anything which can't be found is left out.
+
+use std::{env, fs, path::Path, process::Command, time::Duration};
+
+/// Information about the system pokeget is running on
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SystemInfo {
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub os: Option<String>,
+    pub kernel: Option<String>,
+    pub uptime: Option<Duration>,
+    pub shell: Option<String>,
+    pub terminal: Option<String>,
+    pub cpu: Option<String>,
+    /// Used and total memory in bytes
+    pub memory: Option<(u64, u64)>,
+}
+
+/// Reads a file into a trimmed string, `None` if it can't be read or is empty
+fn read_trimmed(path: &str) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    Some(contents.trim().to_string()).filter(|x| !x.is_empty())
+}
+
+/// Runs a command and returns its trimmed output, `None` if it fails
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    Some(stdout.trim().to_string()).filter(|x| !x.is_empty())
+}
+
+/// Reads an environment variable, `None` if it's unset or empty
+fn var(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|x| !x.is_empty())
+}
+
+/// The name of the distribution from `/etc/os-release`
+fn os_release() -> Option<String> {
+    let contents = fs::read_to_string("/etc/os-release").ok()?;
+
+    ["PRETTY_NAME", "NAME"].iter().find_map(|key| {
+        contents.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix('=')?;
+            Some(value.trim_matches('"').to_string()).filter(|x| !x.is_empty())
+        })
+    })
+}
+
+/// Used and total memory from `/proc/meminfo`
+fn meminfo() -> Option<(u64, u64)> {
+    let contents = fs::read_to_string("/proc/meminfo").ok()?;
+
+    let field = |key: &str| -> Option<u64> {
+        contents.lines().find_map(|line| {
+            let kib = line.strip_prefix(key)?.strip_prefix(':')?;
+            kib.trim().trim_end_matches("kB").trim().parse::<u64>().ok()
+        })
+    };
+
+    let total = field("MemTotal")?;
+    let available = field("MemAvailable")?;
+
+    Some((total.saturating_sub(available) * 1024, total * 1024))
+}
+
+/// The model of the first processor from `/proc/cpuinfo`
+fn cpu_model() -> Option<String> {
+    let contents = fs::read_to_string("/proc/cpuinfo").ok()?;
+
+    contents.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        let value = value.trim();
+        (key.trim() == "model name" && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+impl SystemInfo {
+    /// Gathers information about the system
+    pub fn gather() -> Self {
+        let uptime = read_trimmed("/proc/uptime").and_then(|x| {
+            let seconds = x.split_whitespace().next()?.parse::<f64>().ok()?;
+            Some(Duration::from_secs_f64(seconds))
+        });
+
+        let shell = var("SHELL").map(|x| {
+            Path::new(&x)
+                .file_name()
+                .map_or(x.clone(), |name| name.to_string_lossy().into_owned())
+        });
+
+        Self {
+            user: var("USER").or_else(|| var("USERNAME")),
+            host: read_trimmed("/proc/sys/kernel/hostname")
+                .or_else(|| var("HOSTNAME"))
+                .or_else(|| run("hostname", &[])),
+            os: os_release().or_else(|| run("uname", &["-s"])),
+            kernel: read_trimmed("/proc/sys/kernel/osrelease").or_else(|| run("uname", &["-r"])),
+            uptime,
+            shell,
+            terminal: var("TERM_PROGRAM").or_else(|| var("TERM")),
+            cpu: cpu_model(),
+            memory: meminfo(),
+        }
+    }
+
+    /// The pieces of information which were found, as labels and values
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        const MIB: u64 = 1024 * 1024;
+
+        [
+            ("OS", self.os.clone()),
+            ("Kernel", self.kernel.clone()),
+            ("Uptime", self.uptime.map(format_uptime)),
+            ("Shell", self.shell.clone()),
+            ("Terminal", self.terminal.clone()),
+            ("CPU", self.cpu.clone()),
+            (
+                "Memory",
+                self.memory
+                    .map(|(used, total)| format!("{} MiB / {} MiB", used / MIB, total / MIB)),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| Some((label, value?)))
+        .collect()
+    }
+
+    /// The `user@host` title, or whichever of the two is known
+    pub fn title(&self) -> Option<String> {
+        match (&self.user, &self.host) {
+            (Some(user), Some(host)) => Some(format!("{user}@{host}")),
+            (user, host) => user.clone().or_else(|| host.clone()),
+        }
+    }
+}
+
+/// Formats an uptime like "2 days, 3 hours, 4 mins", leaving out units which are zero
+pub fn format_uptime(uptime: Duration) -> String {
+    let minutes = uptime.as_secs() / 60;
+    let units = [
+        (minutes / (60 * 24), "day"),
+        (minutes / 60 % 24, "hour"),
+        (minutes % 60, "min"),
+    ];
+
+    let parts: Vec<String> = units
+        .iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| match amount {
+            1 => format!("1 {unit}"),
+            _ => format!("{amount} {unit}s"),
+        })
+        .collect();
+
+    match parts.is_empty() {
+        true => "0 mins".to_string(),
+        false => parts.join(", "),
+    }
+}
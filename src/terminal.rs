@@ -0,0 +1,398 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, Read, Write},
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+use terminal_size::{terminal_size, Height, Width};
+
+use crate::{presets, render::Backend, tmux};
+
+/// Width used when the real terminal size can't be determined
+const FALLBACK_WIDTH: u32 = 40;
+
+/// Amount of colors a terminal can display
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24-bit RGB colors
+    TrueColor,
+    /// The xterm 256 color palette
+    Ansi256,
+    /// The basic 16 ANSI colors
+    Ansi16,
+    /// No colors at all
+    Monochrome,
+}
+
+/// Error returned when a color depth can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown color depth: {0}, expected truecolor, 256, 16 or none")]
+pub struct ParseColorDepthError(String);
+
+impl FromStr for ColorDepth {
+    type Err = ParseColorDepthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "truecolor" | "24bit" => Ok(ColorDepth::TrueColor),
+            "256" => Ok(ColorDepth::Ansi256),
+            "16" => Ok(ColorDepth::Ansi16),
+            "none" | "monochrome" => Ok(ColorDepth::Monochrome),
+            _ => Err(ParseColorDepthError(s.to_string())),
+        }
+    }
+}
+
+/// Image protocol understood by a terminal
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Protocol {
+    /// Plain text made out of unicode characters and escape codes
+    Text,
+    /// DEC sixel graphics
+    Sixel,
+    /// The kitty graphics protocol
+    Kitty,
+}
+
+/// Capabilities of the terminal that output is rendered for
+pub trait Terminal {
+    /// Width of the terminal in columns
+    fn width(&self) -> u32;
+
+    /// Height of the terminal in lines, if it's known
+    fn height(&self) -> Option<u32>;
+
+    /// Colors supported by the terminal
+    fn color_depth(&self) -> ColorDepth;
+
+    /// Image protocol supported by the terminal
+    fn protocol(&self) -> Protocol;
+
+    /// Renderer known to work best in the terminal, if any
+    fn renderer(&self) -> Option<Backend>;
+}
+
+/// The terminal pokeget is actually running in, probed on demand
+#[derive(Debug, Default, Copy, Clone)]
+pub struct HostTerminal;
+
+impl Terminal for HostTerminal {
+    fn width(&self) -> u32 {
+        terminal_size()
+            .map(|(Width(w), _)| u32::from(w))
+            .unwrap_or(FALLBACK_WIDTH)
+    }
+
+    /// Unknown when stdout isn't a terminal, since output then doesn't scroll anything off screen
+    fn height(&self) -> Option<u32> {
+        terminal_size().map(|(_, Height(h))| u32::from(h))
+    }
+
+    /// Taken from the terminal's preset, or guessed from `COLORTERM` and `TERM`.
+    /// Setting `NO_COLOR` turns colors off entirely.
+    fn color_depth(&self) -> ColorDepth {
+        let var = |key: &str| tmux::var(key).unwrap_or_default();
+        let term = var("TERM");
+
+        // https://no-color.org
+        if env::var_os("NO_COLOR").is_some_and(|x| !x.is_empty()) {
+            return ColorDepth::Monochrome;
+        }
+
+        if let Some(preset) = presets::detect() {
+            return preset.color_depth;
+        }
+
+        if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") || term.ends_with("-direct") {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else if term == "dumb" {
+            ColorDepth::Monochrome
+        } else if term.is_empty() {
+            // Nothing to go by, most terminals today support at least 256 colors
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    /// Taken from the renderer of the terminal's preset, or guessed from `TERM`
+    fn protocol(&self) -> Protocol {
+        match presets::detect().map(|x| x.renderer) {
+            Some(Backend::Kitty) => Protocol::Kitty,
+            Some(Backend::Sixel) => Protocol::Sixel,
+            Some(_) => Protocol::Text,
+            None if tmux::var("TERM").is_some_and(|x| x.contains("sixel")) => Protocol::Sixel,
+            None => Protocol::Text,
+        }
+    }
+
+    fn renderer(&self) -> Option<Backend> {
+        presets::detect().map(|x| x.renderer)
+    }
+}
+
+/// A terminal with fixed capabilities, used in place of probing
+/// so layouts can be rendered and tested at arbitrary sizes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VirtualTerminal {
+    pub width: u32,
+    pub height: Option<u32>,
+    pub color_depth: ColorDepth,
+    pub protocol: Protocol,
+    pub renderer: Option<Backend>,
+}
+
+impl VirtualTerminal {
+    /// Creates a truecolor text terminal with the given width and an unknown height
+    pub fn new(width: u32) -> Self {
+        Self {
+            width,
+            height: None,
+            color_depth: ColorDepth::TrueColor,
+            protocol: Protocol::Text,
+            renderer: None,
+        }
+    }
+}
+
+impl VirtualTerminal {
+    /// Captures the current capabilities of another terminal, so they can be changed
+    pub fn probe(terminal: &dyn Terminal) -> Self {
+        Self {
+            width: terminal.width(),
+            height: terminal.height(),
+            color_depth: terminal.color_depth(),
+            protocol: terminal.protocol(),
+            renderer: terminal.renderer(),
+        }
+    }
+}
+
+impl Terminal for VirtualTerminal {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> Option<u32> {
+        self.height
+    }
+
+    fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    fn renderer(&self) -> Option<Backend> {
+        self.renderer
+    }
+}
+
+/// A key read by [`read_key`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    /// A letter pressed with Ctrl, in lowercase
+    Ctrl(char),
+    Enter,
+    Escape,
+    Backspace,
+    Tab,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    /// Anything else, like function keys
+    Unknown,
+}
+
+impl Key {
+    /// Parses the first key in `bytes`, returning it and the amount of bytes it took up
+    pub fn parse(bytes: &[u8]) -> (Self, usize) {
+        match bytes {
+            [0x1b, b'[' | b'O', b'A', ..] => (Key::Up, 3),
+            [0x1b, b'[' | b'O', b'B', ..] => (Key::Down, 3),
+            [0x1b, b'[' | b'O', b'C', ..] => (Key::Right, 3),
+            [0x1b, b'[' | b'O', b'D', ..] => (Key::Left, 3),
+            [0x1b, b'[', b'5', b'~', ..] => (Key::PageUp, 4),
+            [0x1b, b'[', b'6', b'~', ..] => (Key::PageDown, 4),
+            // Any other escape sequence, which runs until its final byte
+            [0x1b, b'[', rest @ ..] => {
+                let end = rest.iter().position(|x| (b'@'..=b'~').contains(x));
+                (Key::Unknown, end.map_or(bytes.len(), |x| x + 3))
+            }
+            [0x1b, ..] => (Key::Escape, 1),
+            [b'\r' | b'\n', ..] => (Key::Enter, 1),
+            [b'\t', ..] => (Key::Tab, 1),
+            [0x7f | 0x08, ..] => (Key::Backspace, 1),
+            [c @ 0x01..=0x1a, ..] => (Key::Ctrl(char::from(b'a' + c - 1)), 1),
+            _ => {
+                // UTF-8 characters take up to four bytes
+                let text = (1..=bytes.len().min(4))
+                    .find_map(|x| std::str::from_utf8(&bytes[..x]).ok().map(|s| (s, x)));
+
+                match text.and_then(|(s, x)| Some((s.chars().next()?, x))) {
+                    Some((c, len)) if !c.is_control() => (Key::Char(c), len),
+                    Some((_, len)) => (Key::Unknown, len),
+                    None => (Key::Unknown, 1),
+                }
+            }
+        }
+    }
+}
+
+/// Reads the keys pressed since the last read from `tty`, none once there's nothing left to read.
+///
+/// Escape sequences arrive in a single read, but fast typing or pasting can make up several keys.
+pub fn read_keys(tty: &mut impl Read) -> io::Result<Vec<Key>> {
+    let mut buffer = [0; 64];
+    let read = tty.read(&mut buffer)?;
+
+    let mut keys = Vec::new();
+    let mut rest = &buffer[..read];
+    while !rest.is_empty() {
+        let (key, len) = Key::parse(rest);
+        keys.push(key);
+        rest = &rest[len.max(1)..];
+    }
+
+    Ok(keys)
+}
+
+/// Reads keys from `/dev/tty` as they're pressed, restoring the terminal when dropped.
+///
+/// Keys aren't echoed and Ctrl-C is read as a key rather than stopping pokeget.
+/// Only changes anything where `stty` is available, elsewhere keys are read once enter is pressed.
+pub struct RawMode {
+    saved: Option<String>,
+}
+
+impl RawMode {
+    pub fn enable() -> Self {
+        let saved = stty(&["-g"]);
+        if saved.is_some() {
+            stty(&["-icanon", "-echo", "-isig", "-ixon", "min", "1"]);
+        }
+
+        Self { saved }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            stty(&[saved]);
+        }
+    }
+}
+
+/// Escape code showing the cursor again
+pub const SHOW_CURSOR: &str = "\x1b[?25h";
+
+/// Writes escape codes undoing changes to the terminal when dropped,
+/// and on unix also when pokeget is interrupted or terminated before that.
+pub struct Restore {
+    codes: &'static [&'static str],
+}
+
+impl Restore {
+    pub fn new(codes: &'static [&'static str]) -> Self {
+        #[cfg(unix)]
+        interrupt::set(codes);
+
+        Self { codes }
+    }
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        interrupt::clear();
+
+        let mut stdout = io::stdout();
+        for code in self.codes {
+            let _ = stdout.write_all(code.as_bytes());
+        }
+        let _ = stdout.flush();
+    }
+}
+
+/// Signal handlers writing the codes of the current [`Restore`] before pokeget exits
+#[cfg(unix)]
+mod interrupt {
+    use std::{
+        ptr,
+        sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+    };
+
+    use libc::c_int;
+
+    /// Signals which would otherwise stop pokeget without restoring the terminal
+    const SIGNALS: [c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+    /// The codes to write, as the parts of a slice since handlers can only use atomics
+    static CODES: AtomicPtr<&'static str> = AtomicPtr::new(ptr::null_mut());
+    static LEN: AtomicUsize = AtomicUsize::new(0);
+
+    pub fn set(codes: &'static [&'static str]) {
+        LEN.store(codes.len(), Ordering::SeqCst);
+        CODES.store(codes.as_ptr().cast_mut(), Ordering::SeqCst);
+
+        for signal in SIGNALS {
+            // SAFETY: `handle` only makes async signal safe calls
+            unsafe { libc::signal(signal, handle as extern "C" fn(c_int) as libc::sighandler_t) };
+        }
+    }
+
+    pub fn clear() {
+        for signal in SIGNALS {
+            // SAFETY: restores the default disposition
+            unsafe { libc::signal(signal, libc::SIG_DFL) };
+        }
+
+        CODES.store(ptr::null_mut(), Ordering::SeqCst);
+    }
+
+    extern "C" fn handle(signal: c_int) {
+        let codes = CODES.swap(ptr::null_mut(), Ordering::SeqCst);
+
+        if !codes.is_null() {
+            // SAFETY: `set` stored a 'static slice and its length
+            let codes = unsafe { std::slice::from_raw_parts(codes, LEN.load(Ordering::SeqCst)) };
+            for code in codes {
+                // SAFETY: write is async signal safe, and a failed write has nothing to undo
+                unsafe { libc::write(libc::STDOUT_FILENO, code.as_ptr().cast(), code.len()) };
+            }
+        }
+
+        // Stopped by the signal as if it was never handled, so the exit status reflects it
+        // SAFETY: signal and raise are async signal safe
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+}
+
+/// Runs `stty` on the terminal, returning what it printed
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(File::open("/dev/tty").ok()?)
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|x| x.status.success())?;
+
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|x| x.trim().to_string())
+}
@@ -0,0 +1,201 @@
+//! 16 color terminal schemes generated from the colors of a sprite,
+//! for `pokeget palette --export-theme`.
+//!
+//! The darkest and lightest colors of the sprite become the background and foreground.
+//! Every other slot keeps roughly the hue its name promises, eg. red stays reddish,
+//! but is turned towards the closest color of the sprite and takes on its saturation.
+
+use std::str::FromStr;
+
+use crate::{
+    color::{from_hsl, hex, to_hsl},
+    json::Value,
+    palette::Swatch,
+};
+
+/// Names of the 8 normal colors, in the order of their ANSI codes
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Hue each of the 6 colored slots is centered on, red to cyan
+const SLOT_HUES: [f32; 6] = [0.0, 120.0, 60.0, 240.0, 300.0, 180.0];
+
+/// Degrees a slot is turned at most towards the closest color of the sprite
+const MAX_HUE_SHIFT: f32 = 20.0;
+
+/// Colors less saturated than this count as grays, which don't take part in picking hues
+const MIN_SATURATION: f32 = 0.15;
+
+/// Error returned when a theme format can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown theme format: {0}, expected alacritty, kitty, xresources or wal")]
+pub struct ParseThemeFormatError(String);
+
+/// The terminal or tool a scheme is written for
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ThemeFormat {
+    /// Alacritty's TOML configuration
+    Alacritty,
+    /// Lines of kitty's configuration
+    Kitty,
+    /// X resources, as read by xterm and urxvt
+    Xresources,
+    /// pywal's `colors.json`
+    Wal,
+}
+
+impl FromStr for ThemeFormat {
+    type Err = ParseThemeFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "alacritty" => Ok(ThemeFormat::Alacritty),
+            "kitty" => Ok(ThemeFormat::Kitty),
+            "xresources" | "xrdb" => Ok(ThemeFormat::Xresources),
+            "wal" | "pywal" => Ok(ThemeFormat::Wal),
+            _ => Err(ParseThemeFormatError(s.to_string())),
+        }
+    }
+}
+
+/// A 16 color scheme
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Scheme {
+    pub background: [u8; 3],
+    pub foreground: [u8; 3],
+    pub cursor: [u8; 3],
+    /// The 8 normal colors followed by their 8 bright versions
+    pub colors: [[u8; 3]; 16],
+}
+
+/// Distance between two hues in degrees, at most 180
+fn hue_distance(a: f32, b: f32) -> f32 {
+    let distance = (a - b).rem_euclid(360.0);
+    distance.min(360.0 - distance)
+}
+
+impl Scheme {
+    /// Generates a scheme from the dominant colors of a sprite, the most common first
+    pub fn from_swatches(swatches: &[Swatch]) -> Self {
+        let hsl: Vec<(f32, f32, f32)> = swatches.iter().map(|x| to_hsl(x.rgb)).collect();
+        let colored: Vec<&(f32, f32, f32)> = hsl.iter().filter(|x| x.1 >= MIN_SATURATION).collect();
+
+        // The background and foreground are tinted with the most common color
+        let tint = colored.first().map_or(0.0, |x| x.0);
+        let darkest = hsl.iter().map(|x| x.2).fold(1.0, f32::min);
+        let lightest = hsl.iter().map(|x| x.2).fold(0.0, f32::max);
+
+        let background = from_hsl(tint, 0.2, darkest.min(0.1));
+        let foreground = from_hsl(tint, 0.15, lightest.max(0.85));
+
+        let mut colors = [[0; 3]; 16];
+        colors[0] = background;
+        colors[8] = from_hsl(tint, 0.15, darkest.min(0.1) + 0.25);
+        colors[7] = from_hsl(tint, 0.1, lightest.max(0.85) - 0.1);
+        colors[15] = foreground;
+
+        for (i, &target) in SLOT_HUES.iter().enumerate() {
+            let closest = colored
+                .iter()
+                .min_by(|a, b| hue_distance(a.0, target).total_cmp(&hue_distance(b.0, target)));
+
+            let (hue, saturation) = match closest {
+                Some(&&(hue, saturation, _)) => {
+                    // Turned towards the sprite's color the shorter way around
+                    let offset = (hue - target + 180.0).rem_euclid(360.0) - 180.0;
+                    let shift = offset.clamp(-MAX_HUE_SHIFT, MAX_HUE_SHIFT);
+                    (target + shift, saturation.clamp(0.4, 0.8))
+                }
+                None => (target, 0.5),
+            };
+
+            colors[i + 1] = from_hsl(hue, saturation, 0.55);
+            colors[i + 9] = from_hsl(hue, saturation, 0.7);
+        }
+
+        Self {
+            background,
+            foreground,
+            cursor: foreground,
+            colors,
+        }
+    }
+
+    /// Writes the scheme in `format`
+    pub fn export(&self, format: ThemeFormat) -> String {
+        let mut out = String::new();
+
+        match format {
+            ThemeFormat::Alacritty => {
+                out.push_str("[colors.primary]\n");
+                out.push_str(&format!("background = \"{}\"\n", hex(self.background)));
+                out.push_str(&format!("foreground = \"{}\"\n", hex(self.foreground)));
+                out.push_str("\n[colors.cursor]\n");
+                out.push_str(&format!("cursor = \"{}\"\n", hex(self.cursor)));
+                out.push_str(&format!("text = \"{}\"\n", hex(self.background)));
+
+                for (table, colors) in
+                    [("normal", &self.colors[..8]), ("bright", &self.colors[8..])]
+                {
+                    out.push_str(&format!("\n[colors.{table}]\n"));
+                    for (name, rgb) in COLOR_NAMES.iter().zip(colors) {
+                        out.push_str(&format!("{name} = \"{}\"\n", hex(*rgb)));
+                    }
+                }
+            }
+            ThemeFormat::Kitty => {
+                out.push_str(&format!("background {}\n", hex(self.background)));
+                out.push_str(&format!("foreground {}\n", hex(self.foreground)));
+                out.push_str(&format!("cursor {}\n", hex(self.cursor)));
+                for (i, rgb) in self.colors.iter().enumerate() {
+                    out.push_str(&format!("color{i} {}\n", hex(*rgb)));
+                }
+            }
+            ThemeFormat::Xresources => {
+                out.push_str(&format!("*.background: {}\n", hex(self.background)));
+                out.push_str(&format!("*.foreground: {}\n", hex(self.foreground)));
+                out.push_str(&format!("*.cursorColor: {}\n", hex(self.cursor)));
+                for (i, rgb) in self.colors.iter().enumerate() {
+                    out.push_str(&format!("*.color{i}: {}\n", hex(*rgb)));
+                }
+            }
+            ThemeFormat::Wal => {
+                let string = |rgb| Value::String(hex(rgb));
+                let special = [
+                    ("background", self.background),
+                    ("foreground", self.foreground),
+                    ("cursor", self.cursor),
+                ];
+                let json = Value::Object(vec![
+                    ("wallpaper".into(), Value::String(String::new())),
+                    ("alpha".into(), Value::String("100".into())),
+                    (
+                        "special".into(),
+                        Value::Object(
+                            special
+                                .into_iter()
+                                .map(|(key, rgb)| (key.to_string(), string(rgb)))
+                                .collect(),
+                        ),
+                    ),
+                    (
+                        "colors".into(),
+                        Value::Object(
+                            self.colors
+                                .iter()
+                                .enumerate()
+                                .map(|(i, rgb)| (format!("color{i}"), string(*rgb)))
+                                .collect(),
+                        ),
+                    ),
+                ]);
+
+                out.push_str(&json.to_string());
+                out.push('\n');
+            }
+        }
+
+        out
+    }
+}
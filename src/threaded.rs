@@ -0,0 +1,115 @@
+//! Thread-offload wrappers around the library facade, enabled by the `threaded` feature.
+//!
+//! These aren't async IO: every call spawns a thread that runs the blocking
+//! function and returns a future resolving to its result. That keeps the
+//! rendering off an executor's worker threads without depending on a
+//! particular runtime, at the cost of one thread per call.
+
+use std::{
+    any::Any,
+    fmt,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use image::DynamicImage;
+
+use crate::{sprites::SpriteError, Options, RenderError, ThumbnailOptions};
+
+type Outcome<T> = Result<T, Box<dyn Any + Send>>;
+
+struct Shared<T> {
+    outcome: Option<Outcome<T>>,
+    waker: Option<Waker>,
+}
+
+/// A future resolving to the result of a closure running on its own thread
+struct Background<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T> Future for Background<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut shared = self.shared.lock().unwrap_or_else(|x| x.into_inner());
+
+        match shared.outcome.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            // Surface panics from the background thread in the awaiting task
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Runs `f` on a new thread, returning a future for its result
+fn background<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Background<T> {
+    let shared = Arc::new(Mutex::new(Shared {
+        outcome: None,
+        waker: None,
+    }));
+
+    let handle = Arc::clone(&shared);
+    thread::spawn(move || {
+        let outcome = panic::catch_unwind(AssertUnwindSafe(f));
+        let mut shared = handle.lock().unwrap_or_else(|x| x.into_inner());
+        shared.outcome = Some(outcome);
+
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    });
+
+    Background { shared }
+}
+
+/// Runs [`crate::render`] on its own thread
+///
+/// # Errors
+///
+/// Returns [`RenderError`] if the pokemon can't be found or its sprite can't be drawn
+pub async fn render(pokemon: &str, options: Options) -> Result<String, RenderError> {
+    let pokemon = pokemon.to_string();
+    background(move || crate::render(&pokemon, options)).await
+}
+
+/// Runs [`crate::render_into`] on its own thread
+///
+/// The sprite is rendered on the thread and written into `out` once finished.
+///
+/// # Errors
+///
+/// Returns [`RenderError`] if the pokemon can't be found, its sprite can't be drawn
+/// or writing to `out` fails
+pub async fn render_into(
+    out: &mut (impl fmt::Write + Send),
+    pokemon: &str,
+    options: Options,
+) -> Result<(), RenderError> {
+    let rendered = render(pokemon, options).await?;
+    out.write_str(&rendered).map_err(SpriteError::from)?;
+
+    Ok(())
+}
+
+/// Runs [`crate::thumbnail_with`] on its own thread
+///
+/// # Errors
+///
+/// Returns [`RenderError`] if the pokemon can't be found
+pub async fn thumbnail(
+    pokemon: &str,
+    size: u32,
+    options: ThumbnailOptions,
+) -> Result<DynamicImage, RenderError> {
+    let pokemon = pokemon.to_string();
+    background(move || crate::thumbnail_with(&pokemon, size, options)).await
+}
@@ -0,0 +1,85 @@
+//! Running inside tmux.
+//!
+//! tmux only hands escape sequences it doesn't understand to the terminal around it when
+//! they're wrapped in its passthrough sequence, which needs `set -g allow-passthrough on`.
+//! Its panes also set `TERM` to tmux's own, so the outer terminal is asked of tmux instead.
+
+use std::{env, process::Command, sync::OnceLock};
+
+/// Starts a passthrough sequence, ended by the usual string terminator
+const PASSTHROUGH_START: &str = "\x1bPtmux;";
+
+/// Ends the DCS and APC sequences used by graphics protocols
+const STRING_TERMINATOR: &str = "\x1b\\";
+
+/// Whether pokeget is running inside tmux, from `$TMUX`
+pub fn detected() -> bool {
+    env::var_os("TMUX").is_some_and(|x| !x.is_empty())
+}
+
+/// The `TERM` of the terminal tmux is attached to, asked once
+fn client_term() -> Option<&'static str> {
+    static TERM: OnceLock<Option<String>> = OnceLock::new();
+
+    TERM.get_or_init(|| {
+        let output = Command::new("tmux")
+            .args(["display-message", "-p", "#{client_termname}"])
+            .output()
+            .ok()
+            .filter(|x| x.status.success())?;
+
+        let term = String::from_utf8(output.stdout).ok()?;
+        Some(term.trim().to_string()).filter(|x| !x.is_empty())
+    })
+    .as_deref()
+}
+
+/// Reads an environment variable as it's set in the terminal around tmux, if running in tmux.
+///
+/// `TERM` is the one of tmux's client and tmux's own `TERM_PROGRAM` is left out,
+/// other variables are inherited from the terminal tmux was started in anyway.
+pub fn var(key: &str) -> Option<String> {
+    let value = env::var(key).ok().filter(|x| !x.is_empty());
+    if !detected() {
+        return value;
+    }
+
+    match key {
+        "TERM" => client_term().map(str::to_string).or(value),
+        "TERM_PROGRAM" => value.filter(|x| x != "tmux"),
+        _ => value,
+    }
+}
+
+/// Wraps the DCS and APC sequences of graphics protocols in `text` in tmux's passthrough,
+/// doubling the escape characters inside them. Everything else is left for tmux to handle.
+pub fn passthrough(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut rest = text;
+
+    while let Some(start) = rest.find('\x1b') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let is_string = rest.starts_with("\x1bP") || rest.starts_with("\x1b_");
+        let end = rest[1..]
+            .find(STRING_TERMINATOR)
+            .map(|x| x + 1 + STRING_TERMINATOR.len());
+
+        match (is_string, end) {
+            (true, Some(end)) => {
+                out.push_str(PASSTHROUGH_START);
+                out.push_str(&rest[..end].replace('\x1b', "\x1b\x1b"));
+                out.push_str(STRING_TERMINATOR);
+                rest = &rest[end..];
+            }
+            _ => {
+                out.push('\x1b');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,76 @@
+//! Transformations applied to a pokemon's sprite after it's loaded.
+
+use std::str::FromStr;
+
+use image::{DynamicImage, Rgba};
+
+/// Error returned when a transform name can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown transform: {0}")]
+pub struct ParseTransformError(String);
+
+/// A change to a sprite, requested with a flag or inline as `name:transform`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Transform {
+    /// Mirror the sprite horizontally
+    Flip,
+    /// Paint the whole sprite in one dark color, like "Who's that Pokémon?"
+    Silhouette,
+}
+
+/// Color of silhouettes, dark but still visible on black terminals
+const SILHOUETTE: [u8; 3] = [48, 48, 64];
+
+impl Transform {
+    /// Applies the transform to a sprite
+    pub fn apply(self, sprite: &DynamicImage) -> DynamicImage {
+        match self {
+            Transform::Flip => sprite.fliph(),
+            Transform::Silhouette => {
+                let mut rgba = sprite.to_rgba8();
+
+                for pixel in rgba.pixels_mut().filter(|x| x[3] != 0) {
+                    let [r, g, b] = SILHOUETTE;
+                    *pixel = Rgba([r, g, b, pixel[3]]);
+                }
+
+                DynamicImage::ImageRgba8(rgba)
+            }
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = ParseTransformError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "flip" => Ok(Transform::Flip),
+            "silhouette" => Ok(Transform::Silhouette),
+            _ => Err(ParseTransformError(s.to_string())),
+        }
+    }
+}
+
+/// Splits inline transforms off the end of an argument,
+/// eg. "pikachu:flip" becomes "pikachu" and `[Transform::Flip]`.
+///
+/// Suffixes which aren't transforms are left alone, so names containing
+/// a colon like "type:null" still work.
+pub fn split_transforms(arg: &str) -> (&str, Vec<Transform>) {
+    let mut rest = arg;
+    let mut transforms = Vec::new();
+
+    while let Some((head, suffix)) = rest.rsplit_once(':') {
+        match suffix.parse() {
+            Ok(transform) => {
+                transforms.push(transform);
+                rest = head;
+            }
+            Err(_) => break,
+        }
+    }
+
+    transforms.reverse();
+    (rest, transforms)
+}
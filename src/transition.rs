@@ -0,0 +1,180 @@
+//! Transitions between two images, generated as a series of frames.
+//!
+//! Frames are meant to be drawn one after another with a [`crate::pacing::FramePacer`],
+//! ending on the image being transitioned to.
+
+use std::str::FromStr;
+
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+
+/// Colors of the ring closing and opening in [`Transition::Pokeball`], top and bottom half
+const POKEBALL_COLORS: [Rgba<u8>; 2] = [Rgba([227, 53, 13, 255]), Rgba([240, 240, 240, 255])];
+
+/// Thickness of the ring in [`Transition::Pokeball`] in pixels
+const RING_WIDTH: f32 = 2.0;
+
+/// Error returned when a transition can't be parsed
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown transition: {0}, expected none, fade, slide, wipe or pokeball")]
+pub struct ParseTransitionError(String);
+
+/// Style of the transition between two images
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Transition {
+    /// Switch to the next image right away
+    #[default]
+    None,
+    /// Blend the images into each other
+    Fade,
+    /// Push the current image out to the left with the next one
+    Slide,
+    /// Reveal the next image from left to right
+    Wipe,
+    /// Close a ring over the current image and open it on the next one
+    Pokeball,
+}
+
+impl FromStr for Transition {
+    type Err = ParseTransitionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(Transition::None),
+            "fade" => Ok(Transition::Fade),
+            "slide" => Ok(Transition::Slide),
+            "wipe" => Ok(Transition::Wipe),
+            "pokeball" => Ok(Transition::Pokeball),
+            _ => Err(ParseTransitionError(s.to_string())),
+        }
+    }
+}
+
+/// Places an image on a transparent canvas, centered horizontally and aligned to the bottom
+fn place(image: &DynamicImage, width: u32, height: u32) -> RgbaImage {
+    let mut canvas = RgbaImage::new(width, height);
+    let (x, y) = ((width - image.width()) / 2, height - image.height());
+    imageops::overlay(&mut canvas, &image.to_rgba8(), i64::from(x), i64::from(y));
+
+    canvas
+}
+
+/// Blends two pixels, `t` being how far along from `a` to `b`
+fn blend(a: Rgba<u8>, b: Rgba<u8>, t: f32) -> Rgba<u8> {
+    // A transparent pixel has no color worth fading from or to
+    let (a_rgb, b_rgb) = match (a[3], b[3]) {
+        (0, _) => (b, b),
+        (_, 0) => (a, a),
+        _ => (a, b),
+    };
+
+    let lerp = |x: u8, y: u8| (f32::from(x) + (f32::from(y) - f32::from(x)) * t).round() as u8;
+
+    Rgba([
+        lerp(a_rgb[0], b_rgb[0]),
+        lerp(a_rgb[1], b_rgb[1]),
+        lerp(a_rgb[2], b_rgb[2]),
+        lerp(a[3], b[3]),
+    ])
+}
+
+impl Transition {
+    /// Generates the frames of a transition from one image to another.
+    ///
+    /// Every frame is as large as both images, which are centered and aligned to the bottom.
+    /// The last of the `steps` frames is always the image transitioned to.
+    pub fn frames(self, from: &DynamicImage, to: &DynamicImage, steps: u32) -> Frames {
+        let (width, height) = (from.width().max(to.width()), from.height().max(to.height()));
+
+        Frames {
+            transition: self,
+            from: place(from, width, height),
+            to: place(to, width, height),
+            step: 0,
+            steps: match self {
+                Transition::None => 1,
+                _ => steps.max(1),
+            },
+        }
+    }
+
+    /// Draws the frame `t` of the way through the transition
+    fn frame(self, from: &RgbaImage, to: &RgbaImage, t: f32) -> RgbaImage {
+        let (width, height) = from.dimensions();
+
+        match self {
+            Transition::None => to.clone(),
+            Transition::Fade => RgbaImage::from_fn(width, height, |x, y| {
+                blend(*from.get_pixel(x, y), *to.get_pixel(x, y), t)
+            }),
+            Transition::Slide => {
+                let offset = (width as f32 * t).round() as u32;
+
+                RgbaImage::from_fn(width, height, |x, y| match x + offset < width {
+                    true => *from.get_pixel(x + offset, y),
+                    false => *to.get_pixel(x + offset - width, y),
+                })
+            }
+            Transition::Wipe => {
+                let edge = (width as f32 * t).round() as u32;
+
+                RgbaImage::from_fn(width, height, |x, y| match x < edge {
+                    true => *to.get_pixel(x, y),
+                    false => *from.get_pixel(x, y),
+                })
+            }
+            Transition::Pokeball => {
+                let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+                let max_radius = cx.hypot(cy);
+
+                // Closing over the current image for the first half, opening on the next one after
+                let (image, radius) = match t < 0.5 {
+                    true => (from, max_radius * (1.0 - t * 2.0)),
+                    false => (to, max_radius * (t * 2.0 - 1.0)),
+                };
+
+                RgbaImage::from_fn(width, height, |x, y| {
+                    let distance = (x as f32 + 0.5 - cx).hypot(y as f32 + 0.5 - cy);
+
+                    if distance < radius {
+                        *image.get_pixel(x, y)
+                    } else if distance < radius + RING_WIDTH {
+                        POKEBALL_COLORS[usize::from(y as f32 >= cy)]
+                    } else {
+                        Rgba([0, 0, 0, 0])
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// Iterator over the frames of a transition, created by [`Transition::frames`]
+pub struct Frames {
+    transition: Transition,
+    from: RgbaImage,
+    to: RgbaImage,
+    step: u32,
+    steps: u32,
+}
+
+impl Iterator for Frames {
+    type Item = DynamicImage;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.step >= self.steps {
+            return None;
+        }
+
+        self.step += 1;
+
+        let frame = match self.step == self.steps {
+            true => self.to.clone(),
+            false => {
+                let t = self.step as f32 / self.steps as f32;
+                self.transition.frame(&self.from, &self.to, t)
+            }
+        };
+
+        Some(DynamicImage::ImageRgba8(frame))
+    }
+}
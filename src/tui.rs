@@ -0,0 +1,273 @@
+//! An interactive browser for `pokeget browse`, enabled by the `tui` feature.
+//!
+//! Typing searches the list of pokemon on the left, and the sprite of the selected one
+//! is drawn on the right with [`HalfBlock`]. Everything is drawn with escape codes
+//! on the alternate screen while keys are read in [`RawMode`].
+
+use std::{
+    fs::File,
+    io::{self, Write},
+};
+
+use crate::{
+    forms::FormIndex,
+    list::{normalize_filename, List},
+    pokemon::{AttributesBuilder, Pokemon},
+    render::{HalfBlock, Renderer},
+    source::Sources,
+    sprites::{downscale_to, ScaleFilter},
+    terminal::{read_keys, HostTerminal, Key, RawMode, Terminal},
+};
+
+/// Columns taken up by the list, including the gap before the sprite
+const LIST_WIDTH: usize = 26;
+
+/// Lines the terminal is assumed to have when its height isn't known
+const FALLBACK_HEIGHT: u32 = 24;
+
+/// Switches to the alternate screen and hides the cursor
+const ENTER: &str = "\x1b[?1049h\x1b[?25l";
+
+/// Shows the cursor and switches back to the normal screen
+const LEAVE: &str = "\x1b[?25h\x1b[?1049l";
+
+/// Shown on the last line
+const HELP: &str = "type to search  ↑↓ select  ←→ form  tab shiny  ^F female  esc quit";
+
+/// What the sprite pane shows, drawn again only when it changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct View {
+    species: String,
+    shiny: bool,
+    female: bool,
+    form: String,
+    /// Size of the pane in columns and lines
+    size: (u32, u32),
+}
+
+/// State of the browser
+struct Browser<'a> {
+    list: &'a List,
+    query: String,
+    /// Filenames of the pokemon matching the query, in Dex order
+    matches: Vec<&'a str>,
+    selected: usize,
+    /// Index of the first match shown
+    scroll: usize,
+    shiny: bool,
+    female: bool,
+    /// Index into the forms of the selected pokemon, 0 being the default one
+    form: usize,
+    /// The last view drawn and its lines
+    drawn: Option<(View, Vec<String>)>,
+}
+
+impl<'a> Browser<'a> {
+    fn new(list: &'a List) -> Self {
+        let mut browser = Self {
+            list,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+            scroll: 0,
+            shiny: false,
+            female: false,
+            form: 0,
+            drawn: None,
+        };
+        browser.search();
+
+        browser
+    }
+
+    /// Updates the matches after the query changed, selecting the first one
+    fn search(&mut self) {
+        let query = self.query.to_lowercase();
+        let filename = normalize_filename(&self.query);
+
+        self.matches = self
+            .list
+            .filenames()
+            .filter(|x| {
+                x.contains(&filename) || self.list.format_name(x).to_lowercase().contains(&query)
+            })
+            .collect();
+        self.select(0);
+    }
+
+    /// Selects the match at `index`, going back to the default form
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.matches.len().saturating_sub(1));
+        self.form = 0;
+    }
+
+    /// Forms of the selected pokemon, the default one being empty
+    fn forms(&self) -> Vec<&'static str> {
+        let Some(species) = self.matches.get(self.selected) else {
+            return Vec::new();
+        };
+
+        let mut forms = vec![""];
+        forms.extend(FormIndex::embedded().forms(species));
+        forms
+    }
+
+    /// Handles a key press, returning false once the browser should close
+    fn handle(&mut self, key: Key, page: usize) -> bool {
+        let forms = self.forms().len().max(1);
+
+        match key {
+            Key::Escape | Key::Ctrl('c') => return false,
+            Key::Char(c) => {
+                self.query.push(c);
+                self.search();
+            }
+            Key::Backspace => {
+                self.query.pop();
+                self.search();
+            }
+            Key::Up => self.select(self.selected.saturating_sub(1)),
+            Key::Down => self.select(self.selected + 1),
+            Key::PageUp => self.select(self.selected.saturating_sub(page)),
+            Key::PageDown => self.select(self.selected + page),
+            Key::Left => self.form = (self.form + forms - 1) % forms,
+            Key::Right => self.form = (self.form + 1) % forms,
+            Key::Tab => self.shiny = !self.shiny,
+            Key::Ctrl('f') => self.female = !self.female,
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Lines of the sprite pane for `view`, with the name of the pokemon on the first one
+    fn render(&self, view: &View) -> Vec<String> {
+        let attributes = AttributesBuilder::new()
+            .with_form(&view.form)
+            .with_shiny(view.shiny)
+            .with_female(view.female)
+            .with_source(Sources::from_env())
+            .build();
+
+        let pokemon = attributes.and_then(|x| Pokemon::new(view.species.clone(), self.list, x));
+        let mut pokemon = match pokemon {
+            Ok(pokemon) => pokemon,
+            Err(err) => return vec![self.list.format_name(&view.species), err.to_string()],
+        };
+
+        let mut title = pokemon.name.clone();
+        if !view.form.is_empty() {
+            title.push_str(&format!(" ({})", view.form));
+        }
+        if pokemon.shiny {
+            title.push_str(" ★");
+        }
+        if pokemon.female {
+            title.push_str(" ♀");
+        }
+
+        let (width, height) = view.size;
+        downscale_to(
+            std::slice::from_mut(&mut pokemon),
+            width,
+            height.saturating_sub(1) * 2,
+            ScaleFilter::Nearest,
+        );
+
+        let renderer = HalfBlock::new(HostTerminal.color_depth());
+        let mut lines = vec![title];
+        lines.extend(renderer.render(&pokemon.sprite).lines().map(str::to_string));
+
+        lines
+    }
+
+    /// Draws the whole browser into a terminal of `width` by `height`
+    fn draw(&mut self, out: &mut impl Write, width: u32, height: u32) -> io::Result<()> {
+        // The search line and help line surround the list
+        let rows = height.saturating_sub(2) as usize;
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if rows > 0 && self.selected >= self.scroll + rows {
+            self.scroll = self.selected + 1 - rows;
+        }
+
+        write!(out, "\x1b[H\x1b[2J")?;
+        write!(out, "Search: {}", self.query)?;
+
+        for (row, filename) in self.matches.iter().skip(self.scroll).take(rows).enumerate() {
+            let index = self.scroll + row;
+            let id = self.list.id_of(filename).unwrap_or_default();
+            let entry: String = format!(" {id:04} {}", self.list.format_name(filename))
+                .chars()
+                .take(LIST_WIDTH - 2)
+                .collect();
+
+            write!(out, "\x1b[{};1H", row + 2)?;
+            match index == self.selected {
+                true => write!(out, "\x1b[7m{entry:<0$}\x1b[0m", LIST_WIDTH - 2)?,
+                false => write!(out, "{entry}")?,
+            }
+        }
+
+        if let Some(species) = self.matches.get(self.selected) {
+            let view = View {
+                species: species.to_string(),
+                shiny: self.shiny,
+                female: self.female,
+                form: self.forms().get(self.form).unwrap_or(&"").to_string(),
+                size: (
+                    width.saturating_sub(LIST_WIDTH as u32).max(1),
+                    height.saturating_sub(2).max(1),
+                ),
+            };
+
+            let lines = match self.drawn.take() {
+                Some((drawn, lines)) if drawn == view => lines,
+                _ => self.render(&view),
+            };
+
+            for (row, line) in lines.iter().enumerate() {
+                write!(out, "\x1b[{};{}H{line}", row + 2, LIST_WIDTH + 1)?;
+            }
+
+            self.drawn = Some((view, lines));
+        }
+
+        write!(out, "\x1b[{height};1H\x1b[2m{HELP}\x1b[0m")?;
+        out.flush()
+    }
+}
+
+/// Runs the browser until it's closed
+pub fn browse(list: &List) -> io::Result<()> {
+    let mut tty = File::open("/dev/tty")?;
+    let _raw = RawMode::enable();
+    let mut stdout = io::stdout().lock();
+
+    write!(stdout, "{ENTER}")?;
+    let result = run(&mut Browser::new(list), &mut tty, &mut stdout);
+    write!(stdout, "{LEAVE}")?;
+    stdout.flush()?;
+
+    result
+}
+
+fn run(browser: &mut Browser, tty: &mut File, out: &mut impl Write) -> io::Result<()> {
+    loop {
+        // Measured again every time, so resizing takes effect on the next key
+        let width = HostTerminal.width();
+        let height = HostTerminal.height().unwrap_or(FALLBACK_HEIGHT);
+        browser.draw(out, width, height)?;
+
+        let keys = read_keys(tty)?;
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        for key in keys {
+            if !browser.handle(key, height.saturating_sub(2) as usize) {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::{fmt, str::FromStr};
+
+/// Error returned when a type name can't be parsed.
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown pokemon type: {0}")]
+pub struct ParseTypeError(String);
+
+/// Elemental types of pokemon
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Normal,
+    Fire,
+    Water,
+    Grass,
+    Electric,
+    Ice,
+    Fighting,
+    Poison,
+    Ground,
+    Flying,
+    Psychic,
+    Bug,
+    Rock,
+    Ghost,
+    Dragon,
+    Dark,
+    Steel,
+    Fairy,
+}
+
+impl Type {
+    /// All types, in the order the games list them.
+    pub const ALL: [Type; 18] = [
+        Type::Normal,
+        Type::Fire,
+        Type::Water,
+        Type::Grass,
+        Type::Electric,
+        Type::Ice,
+        Type::Fighting,
+        Type::Poison,
+        Type::Ground,
+        Type::Flying,
+        Type::Psychic,
+        Type::Bug,
+        Type::Rock,
+        Type::Ghost,
+        Type::Dragon,
+        Type::Dark,
+        Type::Steel,
+        Type::Fairy,
+    ];
+
+    /// Returns the lowercase name of the type, as used in the dataset.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Type::Normal => "normal",
+            Type::Fire => "fire",
+            Type::Water => "water",
+            Type::Grass => "grass",
+            Type::Electric => "electric",
+            Type::Ice => "ice",
+            Type::Fighting => "fighting",
+            Type::Poison => "poison",
+            Type::Ground => "ground",
+            Type::Flying => "flying",
+            Type::Psychic => "psychic",
+            Type::Bug => "bug",
+            Type::Rock => "rock",
+            Type::Ghost => "ghost",
+            Type::Dragon => "dragon",
+            Type::Dark => "dark",
+            Type::Steel => "steel",
+            Type::Fairy => "fairy",
+        }
+    }
+
+    /// Returns the canonical color of the type as RGB.
+    pub fn color(&self) -> [u8; 3] {
+        match self {
+            Type::Normal => [0xA8, 0xA8, 0x78],
+            Type::Fire => [0xF0, 0x80, 0x30],
+            Type::Water => [0x68, 0x90, 0xF0],
+            Type::Grass => [0x78, 0xC8, 0x50],
+            Type::Electric => [0xF8, 0xD0, 0x30],
+            Type::Ice => [0x98, 0xD8, 0xD8],
+            Type::Fighting => [0xC0, 0x30, 0x28],
+            Type::Poison => [0xA0, 0x40, 0xA0],
+            Type::Ground => [0xE0, 0xC0, 0x68],
+            Type::Flying => [0xA8, 0x90, 0xF0],
+            Type::Psychic => [0xF8, 0x58, 0x88],
+            Type::Bug => [0xA8, 0xB8, 0x20],
+            Type::Rock => [0xB8, 0xA0, 0x38],
+            Type::Ghost => [0x70, 0x58, 0x98],
+            Type::Dragon => [0x70, 0x38, 0xF8],
+            Type::Dark => [0x70, 0x58, 0x48],
+            Type::Steel => [0xB8, 0xB8, 0xD0],
+            Type::Fairy => [0xEE, 0x99, 0xAC],
+        }
+    }
+}
+
+impl FromStr for Type {
+    type Err = ParseTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+
+        Type::ALL
+            .into_iter()
+            .find(|t| t.name() == lower)
+            .ok_or_else(|| ParseTypeError(s.to_string()))
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
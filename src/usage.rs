@@ -0,0 +1,112 @@
+//! Local, opt-in statistics about which pokemon get displayed.
+//!
+//! Nothing is recorded until enabled with `pokeget stats enable`,
+//! and the statistics never leave the state file.
+
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Names of the weekdays, starting on monday
+pub const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Usage statistics stored in the state file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Usage {
+    /// Whether displays are being recorded
+    pub enabled: bool,
+
+    /// How often each pokemon was displayed
+    pub displays: BTreeMap<String, u64>,
+
+    /// How many of the displayed pokemon were shiny
+    pub shinies: u64,
+
+    /// How many of the displayed pokemon were picked randomly
+    pub randoms: u64,
+
+    /// Whether newly unlocked achievements are announced after displaying
+    pub notify: bool,
+
+    /// Displays per weekday in UTC, starting on monday
+    pub weekdays: [u64; 7],
+}
+
+impl Default for Usage {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            displays: BTreeMap::new(),
+            shinies: 0,
+            randoms: 0,
+            notify: true,
+            weekdays: [0; 7],
+        }
+    }
+}
+
+/// Index into [`WEEKDAYS`] of a point in time, in UTC
+pub fn weekday(time: SystemTime) -> usize {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs() / 86400);
+
+    // The epoch was a thursday
+    ((days + 3) % 7) as usize
+}
+
+impl Usage {
+    /// Records a displayed pokemon if recording is enabled
+    pub fn record(&mut self, species: &str, shiny: bool, random: bool, time: SystemTime) {
+        if !self.enabled {
+            return;
+        }
+
+        *self.displays.entry(species.to_string()).or_default() += 1;
+        self.weekdays[weekday(time)] += 1;
+
+        if shiny {
+            self.shinies += 1;
+        }
+
+        if random {
+            self.randoms += 1;
+        }
+    }
+
+    /// Total amount of recorded displays
+    pub fn total(&self) -> u64 {
+        self.displays.values().sum()
+    }
+
+    /// The `n` most displayed pokemon, most displayed first
+    pub fn top(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut top: Vec<(&str, u64)> = self
+            .displays
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+            .collect();
+
+        top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        top.truncate(n);
+        top
+    }
+
+    /// Forgets everything recorded, keeping the settings
+    pub fn reset(&mut self) {
+        *self = Self {
+            enabled: self.enabled,
+            notify: self.notify,
+            ..Self::default()
+        };
+    }
+}
@@ -0,0 +1,224 @@
+//! Verification of the embedded sprites against a manifest of known hashes.
+//!
+//! Manifests use the format written by `sha256sum`: one `<hex digest>  <path>` line per sprite,
+//! with paths relative to the sprite directory. Lines starting with `#` are ignored.
+//!
+//! Manifests can be downloaded, which goes through `curl`, and signed with
+//! `ssh-keygen -Y sign -n pokeget`, which is checked with `ssh-keygen -Y verify`.
+
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::Data;
+
+/// Namespace and identity manifests are signed with
+pub const NAMESPACE: &str = "pokeget";
+
+/// Error types for reading manifests
+#[derive(Debug, thiserror::Error)]
+pub enum VerifyError {
+    #[error("Invalid manifest on line {0}: {1}")]
+    Parse(usize, String),
+
+    #[error("Failed to read {0}: {1}")]
+    Read(String, io::Error),
+
+    #[error("Failed to download {0}: {1}")]
+    Download(String, String),
+
+    #[error("Failed to run {0}: {1}")]
+    Tool(&'static str, io::Error),
+
+    #[error("Bad signature: {0}")]
+    Signature(String),
+}
+
+/// Whether `source` is a URL rather than a file
+pub fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// Reads a file, or downloads it with curl if `source` is a URL
+pub fn fetch(source: &str) -> Result<Vec<u8>, VerifyError> {
+    if !is_url(source) {
+        return fs::read(source).map_err(|err| VerifyError::Read(source.to_string(), err));
+    }
+
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", source])
+        .output()
+        .map_err(|err| VerifyError::Tool("curl", err))?;
+
+    match output.status.success() {
+        true => Ok(output.stdout),
+        false => Err(VerifyError::Download(
+            source.to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
+
+/// Checks that `signature` is a signature of `manifest` made with `key`.
+///
+/// `key` is an OpenSSH public key, eg. `ssh-ed25519 AAAA...`, or a file containing one.
+/// ssh-keygen only reads signatures and allowed signers from files, so both go through
+/// temporary files while the manifest is passed on stdin.
+pub fn check_signature(manifest: &[u8], signature: &[u8], key: &str) -> Result<(), VerifyError> {
+    const PROGRAM: &str = "ssh-keygen";
+
+    let key = match Path::new(key).is_file() {
+        true => fs::read_to_string(key).map_err(|err| VerifyError::Read(key.to_string(), err))?,
+        false => key.to_string(),
+    };
+
+    let base = env::temp_dir().join(format!("pokeget-{}", std::process::id()));
+    let signers = base.with_extension("signers");
+    let sig = base.with_extension("sig");
+
+    let written = fs::write(&signers, format!("{NAMESPACE} {}\n", key.trim()))
+        .and_then(|_| fs::write(&sig, signature));
+    let output = written.and_then(|_| {
+        let mut child = Command::new(PROGRAM)
+            .args(["-Y", "verify", "-I", NAMESPACE, "-n", NAMESPACE, "-f"])
+            .arg(&signers)
+            .arg("-s")
+            .arg(&sig)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Taken so stdin is closed once written, which is when ssh-keygen checks it
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(manifest)?;
+        }
+
+        child.wait_with_output()
+    });
+
+    let _ = fs::remove_file(&signers);
+    let _ = fs::remove_file(&sig);
+
+    let output = output.map_err(|err| VerifyError::Tool(PROGRAM, err))?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(VerifyError::Signature(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
+
+/// Expected SHA-256 digests of sprites, by path
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub digests: BTreeMap<String, String>,
+}
+
+/// Outcome of comparing sprites with a manifest
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Amount of sprites matching the manifest
+    pub verified: usize,
+    /// Sprites whose contents differ from the manifest
+    pub mismatched: Vec<String>,
+    /// Sprites in the manifest which don't exist
+    pub missing: Vec<String>,
+    /// Sprites which aren't in the manifest
+    pub unlisted: Vec<String>,
+}
+
+impl Report {
+    /// Whether every sprite matched and none were missing or unlisted
+    pub fn is_clean(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty() && self.unlisted.is_empty()
+    }
+}
+
+/// Formats a digest as lowercase hex
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+impl Manifest {
+    /// Parses a manifest in `sha256sum` format
+    pub fn parse(contents: &str) -> Result<Self, VerifyError> {
+        let mut digests = BTreeMap::new();
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |msg: &str| VerifyError::Parse(i + 1, msg.to_string());
+
+            let (digest, path) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| error("expected a digest and a path"))?;
+
+            if digest.len() != 64 || !digest.chars().all(|x| x.is_ascii_hexdigit()) {
+                return Err(error("expected a SHA-256 digest"));
+            }
+
+            // sha256sum marks files hashed in binary mode with a leading '*'
+            let path = path.trim_start().trim_start_matches('*');
+            digests.insert(path.to_string(), digest.to_lowercase());
+        }
+
+        Ok(Self { digests })
+    }
+
+    /// Builds the manifest of the embedded sprites
+    pub fn of_embedded() -> Self {
+        let digests = Data::iter()
+            .filter_map(|path| {
+                let file = Data::get(&path)?;
+                Some((path.to_string(), hex(&Sha256::digest(&file))))
+            })
+            .collect();
+
+        Self { digests }
+    }
+
+    /// Formats the manifest in `sha256sum` format
+    pub fn serialize(&self) -> String {
+        self.digests
+            .iter()
+            .map(|(path, digest)| format!("{digest}  {path}\n"))
+            .collect()
+    }
+
+    /// Compares the manifest against another one describing the actual sprites
+    pub fn compare(&self, actual: &Manifest) -> Report {
+        let mut report = Report::default();
+
+        for (path, digest) in &self.digests {
+            match actual.digests.get(path) {
+                Some(x) if x == digest => report.verified += 1,
+                Some(_) => report.mismatched.push(path.clone()),
+                None => report.missing.push(path.clone()),
+            }
+        }
+
+        report.unlisted = actual
+            .digests
+            .keys()
+            .filter(|x| !self.digests.contains_key(*x))
+            .cloned()
+            .collect();
+
+        report
+    }
+
+    /// Verifies the embedded sprites
+    pub fn verify_embedded(&self) -> Report {
+        self.compare(&Self::of_embedded())
+    }
+}
@@ -0,0 +1,91 @@
+//! Change detection for sprite directories, so long running modes can redraw edited sprites.
+//!
+//! Directories are polled rather than subscribed to, which works the same on every platform
+//! and is cheap for the few thousand files of a sprite pack.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Size and modification time of a file, which change whenever it's written
+type Stamp = (u64, Option<SystemTime>);
+
+/// Watches directories for sprites being added, changed or removed
+#[derive(Debug, Clone, Default)]
+pub struct Watcher {
+    dirs: Vec<PathBuf>,
+    stamps: HashMap<PathBuf, Stamp>,
+}
+
+/// Records the stamps of the PNG files under `dir`, keyed by their path relative to `root`
+fn scan(root: &Path, dir: &Path, stamps: &mut HashMap<PathBuf, Stamp>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let metadata = entry.metadata()?;
+
+        if metadata.is_dir() {
+            scan(root, &path, stamps)?;
+        } else if path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("png"))
+        {
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            // A sprite in an earlier directory shadows the same one in later directories
+            stamps
+                .entry(relative)
+                .or_insert((metadata.len(), metadata.modified().ok()));
+        }
+    }
+
+    Ok(())
+}
+
+impl Watcher {
+    /// Starts watching directories, in the order their sprites take precedence
+    pub fn new(dirs: Vec<PathBuf>) -> Self {
+        let mut watcher = Self {
+            dirs,
+            stamps: HashMap::new(),
+        };
+        watcher.stamps = watcher.snapshot();
+
+        watcher
+    }
+
+    /// The current stamps of every sprite, skipping directories which can't be read
+    fn snapshot(&self) -> HashMap<PathBuf, Stamp> {
+        let mut stamps = HashMap::new();
+
+        for dir in &self.dirs {
+            let _ = scan(dir, dir, &mut stamps);
+        }
+
+        stamps
+    }
+
+    /// Sprites added, changed or removed since the last poll, as paths relative to the sprite
+    /// directory like `regular/pikachu.png`. Removed sprites fall back to later sources.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let stamps = self.snapshot();
+
+        let mut changed: Vec<PathBuf> = stamps
+            .iter()
+            .filter(|(path, stamp)| self.stamps.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .chain(
+                self.stamps
+                    .keys()
+                    .filter(|x| !stamps.contains_key(*x))
+                    .cloned(),
+            )
+            .collect();
+        changed.sort();
+
+        self.stamps = stamps;
+        changed
+    }
+}